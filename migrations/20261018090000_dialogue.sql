@@ -0,0 +1,5 @@
+create table dialogue (
+    chat_id integer primary key,
+    state blob not null,
+    updated_at text not null default current_timestamp
+);
@@ -0,0 +1 @@
+alter table class add column archived_at text;
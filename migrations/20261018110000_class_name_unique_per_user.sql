@@ -0,0 +1,39 @@
+-- Class names used to be unique across all users. SQLite cannot drop a column
+-- constraint in place, so the table is rebuilt. Migrations run inside a
+-- transaction with foreign keys enabled, so dropping `class` cascades into
+-- `class_deduction_history`: its rows are copied aside and restored afterwards.
+create table class_deduction_history_backup as
+select * from class_deduction_history;
+
+create table class_new (
+    class_id integer primary key autoincrement,
+    name text not null,
+    quantity integer not null check (quantity >= 0),
+    created_at text not null default current_timestamp,
+    updated_at text not null default current_timestamp,
+    user_id integer not null,
+    archived_at text,
+    foreign key (user_id) references user(user_id) on delete cascade,
+    unique (user_id, name)
+);
+
+insert into class_new (class_id, name, quantity, created_at, updated_at, user_id, archived_at)
+select class_id, name, quantity, created_at, updated_at, user_id, archived_at
+from class;
+
+drop table class;
+
+alter table class_new rename to class;
+
+create trigger trg_class_updated_at
+after update on class
+for each row
+begin
+    update class set updated_at = current_timestamp where class_id = old.class_id;
+end;
+
+insert into class_deduction_history (class_deduction_history_id, created_at, class_id, user_id)
+select class_deduction_history_id, created_at, class_id, user_id
+from class_deduction_history_backup;
+
+drop table class_deduction_history_backup;
@@ -0,0 +1,13 @@
+alter table class add column valid_until text;
+
+create table class_freeze (
+    class_freeze_id integer primary key autoincrement,
+    frozen_from text not null,
+    frozen_until text not null,
+    days integer not null check (days > 0),
+    created_at text not null default current_timestamp,
+    class_id integer not null,
+    user_id integer not null,
+    foreign key (class_id) references class(class_id) on delete cascade,
+    foreign key (user_id) references user(user_id) on delete cascade
+);
@@ -0,0 +1,2 @@
+-- Null disables the low-balance alert for the class.
+alter table class add column low_balance_threshold integer default 1 check (low_balance_threshold >= 0);
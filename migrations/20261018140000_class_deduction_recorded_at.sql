@@ -0,0 +1,5 @@
+-- `created_at` is now the day the class took place and may be backdated;
+-- `recorded_at` keeps the moment the deduction was actually made.
+alter table class_deduction_history add column recorded_at text;
+
+update class_deduction_history set recorded_at = created_at;
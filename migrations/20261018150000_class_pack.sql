@@ -0,0 +1,23 @@
+-- Every purchase of a class pack is recorded separately; `class.quantity`
+-- becomes the sum of `remaining` over the class packs.
+create table class_pack (
+    class_pack_id integer primary key autoincrement,
+    quantity integer not null check (quantity > 0),
+    remaining integer not null check (remaining >= 0 and remaining <= quantity),
+    purchased_on text not null,
+    valid_until text,
+    created_at text not null default current_timestamp,
+    class_id integer not null,
+    user_id integer not null,
+    foreign key (class_id) references class(class_id) on delete cascade,
+    foreign key (user_id) references user(user_id) on delete cascade
+);
+
+-- Existing balances become a single pack bought on the day the class was added.
+insert into class_pack (quantity, remaining, purchased_on, valid_until, class_id, user_id)
+select quantity, quantity, date(created_at), valid_until, class_id, user_id
+from class
+where quantity > 0;
+
+alter table class_deduction_history
+add column class_pack_id integer references class_pack(class_pack_id) on delete set null;
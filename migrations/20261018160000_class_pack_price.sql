@@ -0,0 +1,3 @@
+-- Optional price of a pack in minor currency units (kopecks, cents).
+alter table class_pack add column price integer check (price >= 0);
+alter table class_pack add column currency text;
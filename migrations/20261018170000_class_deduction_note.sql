@@ -0,0 +1,2 @@
+-- Free-form comment on a deduction: teacher, topic, make-up lesson and so on.
+alter table class_deduction_history add column note text;
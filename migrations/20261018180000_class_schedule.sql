@@ -0,0 +1,31 @@
+-- Weekly timetable of a class: one row per weekday and start time (local).
+create table class_schedule (
+    class_schedule_id integer primary key autoincrement,
+    weekday integer not null check (weekday between 0 and 6), -- 0 is Monday
+    starts_at text not null, -- HH:MM
+    created_at text not null default current_timestamp,
+    class_id integer not null,
+    user_id integer not null,
+    unique (class_id, weekday, starts_at),
+    foreign key (class_id) references class(class_id) on delete cascade,
+    foreign key (user_id) references user(user_id) on delete cascade
+);
+
+-- "Did you attend?" prompts sent after scheduled slots and their answers.
+create table class_attendance (
+    class_attendance_id integer primary key autoincrement,
+    scheduled_for text not null, -- local YYYY-MM-DD HH:MM
+    status text not null default 'pending' check (status in ('pending', 'attended', 'skipped')),
+    answered_at text,
+    created_at text not null default current_timestamp,
+    class_schedule_id integer,
+    class_id integer not null,
+    user_id integer not null,
+    class_deduction_history_id integer,
+    unique (class_id, scheduled_for),
+    foreign key (class_schedule_id) references class_schedule(class_schedule_id) on delete set null,
+    foreign key (class_id) references class(class_id) on delete cascade,
+    foreign key (user_id) references user(user_id) on delete cascade,
+    foreign key (class_deduction_history_id)
+        references class_deduction_history(class_deduction_history_id) on delete set null
+);
@@ -0,0 +1,35 @@
+-- Offset from UTC the user lives in; null means the bot's own timezone.
+alter table user add column utc_offset_minutes integer;
+
+-- Background jobs run by the scheduler. `run_at` is the next (UTC) time the
+-- job is due; recurring jobs carry a cron expression evaluated at
+-- `utc_offset_minutes` and go back to `scheduled` after every run.
+create table job (
+    job_id integer primary key autoincrement,
+    kind text not null,
+    payload text not null default '{}',
+    run_at text not null,
+    cron text,
+    utc_offset_minutes integer not null default 0,
+    status text not null default 'scheduled'
+        check (status in ('scheduled', 'running', 'done', 'failed', 'missed', 'cancelled')),
+    attempts integer not null default 0,
+    last_run_at text,
+    last_error text,
+    -- Lets callers replace or cancel "the" job of some purpose without
+    -- remembering its id, e.g. `practice_reminder:42`.
+    unique_key text unique,
+    created_at text not null default current_timestamp,
+    updated_at text not null default current_timestamp,
+    user_id integer,
+    foreign key (user_id) references user(user_id) on delete cascade
+);
+
+create index idx_job_due on job (status, run_at);
+
+create trigger trg_job_updated_at
+after update on job
+for each row
+begin
+    update job set updated_at = current_timestamp where job_id = old.job_id;
+end;
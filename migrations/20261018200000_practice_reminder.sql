@@ -0,0 +1,3 @@
+-- Time of the daily practice reminder, `HH:MM` at the user's UTC offset;
+-- null when the reminder is off.
+alter table user add column practice_reminder_at text;
@@ -0,0 +1,3 @@
+-- Practice goals in minutes; null means no goal for that period.
+alter table user add column daily_practice_goal_mins integer;
+alter table user add column weekly_practice_goal_mins integer;
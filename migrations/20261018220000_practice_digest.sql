@@ -0,0 +1,3 @@
+-- Opt-in flags of the weekly and monthly practice digests.
+alter table user add column weekly_digest integer not null default 0;
+alter table user add column monthly_digest integer not null default 0;
@@ -0,0 +1,13 @@
+-- User-defined kinds of practice: technique, repertoire, theory and so on.
+create table practice_category (
+    practice_category_id integer primary key autoincrement,
+    name text not null,
+    created_at text not null default current_timestamp,
+    user_id integer not null,
+    unique (user_id, name),
+    foreign key (user_id) references user(user_id) on delete cascade
+);
+
+-- Entries of a deleted category stay in the log without a category.
+alter table daily_practice_log add column practice_category_id integer
+    references practice_category(practice_category_id) on delete set null;
@@ -0,0 +1,10 @@
+-- A running practice stopwatch, at most one per user. Time spent on pause is
+-- accumulated in `paused_secs`; `paused_at` is set while it is paused.
+create table practice_session (
+    practice_session_id integer primary key autoincrement,
+    user_id integer not null unique,
+    started_at text not null,
+    paused_at text,
+    paused_secs integer not null default 0,
+    foreign key (user_id) references user(user_id) on delete cascade
+);
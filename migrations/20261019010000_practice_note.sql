@@ -0,0 +1,2 @@
+-- Free-form comment on a practice entry: what was worked on, difficulties.
+alter table daily_practice_log add column note text;
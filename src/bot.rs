@@ -2,21 +2,37 @@ use std::{sync::Arc, time::Duration};
 
 use crate::{
     commands::Command,
-    config::Config,
+    config::{Config, DialogueStorageBackend},
+    dialogue_storage::{RedisDialogueStorage, SqliteDialogueStorage},
     handlers::{
         class::*,
+        class_schedule::receive_schedule_slot_handler,
         command::*,
         common::{idle_callback_handler, idle_message_handler, stale_callback_handler},
-        daily_practice_log::receive_minutes,
+        daily_practice_log::{
+            practice_date_callback_handler, practice_note_skip_callback_handler, receive_minutes,
+            receive_past_minutes, receive_practice_category_callback_handler,
+            receive_practice_category_message_handler, receive_practice_category_name_handler,
+            receive_practice_entry_minutes_handler, receive_practice_goal_handler,
+            receive_practice_note_handler, receive_practice_note_step_handler,
+            receive_practice_reminder_time_handler,
+        },
     },
+    jobs::{attendance_prompts, practice_digest, practice_reminder, practice_session},
     middlewares::*,
     rate_limiter::RedisRateLimiter,
-    state::State,
+    scheduler::Scheduler,
+    services::{
+        practice_digest::PRACTICE_DIGEST_JOB, practice_reminder::PRACTICE_REMINDER_JOB,
+        practice_session::PRACTICE_SESSION_TIMEOUT_JOB,
+    },
+    state::{DialogueStorage, State},
 };
 use dptree::case;
+use redis::aio::ConnectionManager;
 use sqlx::SqlitePool;
 use teloxide::{
-    dispatching::{HandlerExt, dialogue::InMemStorage},
+    dispatching::{HandlerExt, dialogue::Storage},
     prelude::*,
     utils::command::BotCommands,
 };
@@ -35,15 +51,24 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     let mut sqlite_opts = sqlx::sqlite::SqliteConnectOptions::new();
     sqlite_opts = sqlite_opts.filename(&config.database.path);
-    let db_pool = SqlitePool::connect_with(sqlite_opts).await?;
+    let db_pool = Arc::new(SqlitePool::connect_with(sqlite_opts).await?);
+
+    let redis_client = redis::Client::open(config.redis.url.as_str())?;
+    let redis_conn = ConnectionManager::new(redis_client).await?;
 
     let rate_limiter = RedisRateLimiter::new(
-        &config.redis.url,
+        redis_conn.clone(),
         config.redis.rate_limit,
         Duration::from_secs(config.redis.rate_interval_secs),
         "assistant-bot",
-    )
-    .await?;
+    );
+
+    let storage: Arc<DialogueStorage> = match config.dialogue_storage {
+        DialogueStorageBackend::Sqlite => SqliteDialogueStorage::new(db_pool.clone()).erase(),
+        DialogueStorageBackend::Redis => {
+            RedisDialogueStorage::new(redis_conn, "assistant-bot").erase()
+        }
+    };
 
     let bot = Bot::new(&config.bot_token);
     bot.set_my_commands(Command::bot_commands())
@@ -52,10 +77,30 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     let di = Arc::new(DI {
         config,
-        db_pool: Arc::new(db_pool),
+        db_pool,
         rate_limiter: Arc::new(rate_limiter),
     });
 
+    attendance_prompts::schedule(&di).await?;
+    let scheduler = Scheduler::new(di.db_pool.clone(), di.config.scheduler.clone())
+        .handler(
+            attendance_prompts::ATTENDANCE_PROMPTS_JOB,
+            attendance_prompts::handler(bot.clone(), di.clone()),
+        )
+        .handler(
+            PRACTICE_REMINDER_JOB,
+            practice_reminder::handler(bot.clone(), di.clone()),
+        )
+        .handler(
+            PRACTICE_DIGEST_JOB,
+            practice_digest::handler(bot.clone(), di.clone()),
+        )
+        .handler(
+            PRACTICE_SESSION_TIMEOUT_JOB,
+            practice_session::handler(bot.clone(), di.clone()),
+        );
+    tokio::spawn(scheduler.run());
+
     let handler = dptree::entry()
         .with_rate_limit()
         .branch(
@@ -64,31 +109,153 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 .branch(case![Command::Help].endpoint(help_handler))
                 .branch(case![Command::Start].endpoint(start_handler))
                 .branch(case![Command::MainMenu].endpoint(main_menu_handler))
-                .branch(case![Command::CancelOperation].endpoint(cancel_handler)),
+                .branch(case![Command::CancelOperation].endpoint(cancel_handler))
+                .branch(case![Command::Timezone(offset)].endpoint(timezone_handler))
+                .branch(case![Command::Search(query)].endpoint(search_handler)),
         )
         .branch(
             Update::filter_message()
-                .enter_dialogue::<Message, InMemStorage<State>, State>()
+                .enter_dialogue::<Message, DialogueStorage, State>()
                 .branch(case![State::Idle].endpoint(idle_message_handler))
                 .branch(case![State::AddingClassReceiveName].endpoint(receive_name))
                 .branch(
                     case![State::AddingClassReceiveQuantity { name }].endpoint(receive_quantity),
                 )
+                .branch(
+                    case![State::AddingClassReceiveValidUntil { name, quantity }]
+                        .endpoint(receive_valid_until),
+                )
+                .branch(
+                    case![State::AddingClassReceivePrice {
+                        name,
+                        quantity,
+                        valid_until
+                    }]
+                    .endpoint(receive_price),
+                )
                 .branch(
                     case![State::UpdatingClassReceiveQuantity { class_id }]
                         .endpoint(receive_quantity_handler),
                 )
-                .branch(case![State::AddingDailyPracticeReceiveMinutes].endpoint(receive_minutes)),
+                .branch(
+                    case![State::RenamingClassReceiveName { class_id }]
+                        .endpoint(receive_new_name_handler),
+                )
+                .branch(
+                    case![State::UpdatingClassReceiveValidUntil { class_id }]
+                        .endpoint(receive_new_valid_until_handler),
+                )
+                .branch(
+                    case![State::FreezingClassReceiveDays { class_id }]
+                        .endpoint(receive_freeze_days_handler),
+                )
+                .branch(
+                    case![State::UpdatingClassReceiveLowBalanceThreshold { class_id }]
+                        .endpoint(receive_low_balance_threshold_handler),
+                )
+                .branch(
+                    case![State::ToppingUpClassReceiveQuantity { class_id }]
+                        .endpoint(receive_top_up_quantity_handler),
+                )
+                .branch(
+                    case![State::ToppingUpClassReceiveValidUntil { class_id, quantity }]
+                        .endpoint(receive_top_up_valid_until_handler),
+                )
+                .branch(
+                    case![State::ToppingUpClassReceivePrice {
+                        class_id,
+                        quantity,
+                        valid_until
+                    }]
+                    .endpoint(receive_top_up_price_handler),
+                )
+                .branch(
+                    case![State::AddingDeductionNoteReceiveText {
+                        class_deduction_history_id
+                    }]
+                    .endpoint(receive_deduction_note_handler),
+                )
+                .branch(
+                    case![State::AddingClassScheduleReceiveSlot { class_id }]
+                        .endpoint(receive_schedule_slot_handler),
+                )
+                .branch(case![State::AddingDailyPracticeReceiveMinutes].endpoint(receive_minutes))
+                .branch(
+                    case![State::AddingPastDailyPracticeReceiveMinutes { practiced_on }]
+                        .endpoint(receive_past_minutes),
+                )
+                .branch(
+                    case![State::AddingDailyPracticeReceiveCategory {
+                        minutes,
+                        practiced_on
+                    }]
+                    .endpoint(receive_practice_category_message_handler),
+                )
+                .branch(
+                    case![State::EditingDailyPracticeReceiveMinutes {
+                        daily_practice_log_id
+                    }]
+                    .endpoint(receive_practice_entry_minutes_handler),
+                )
+                .branch(
+                    case![State::AddingDailyPracticeReceiveNote {
+                        minutes,
+                        practiced_on,
+                        practice_category_id
+                    }]
+                    .endpoint(receive_practice_note_step_handler),
+                )
+                .branch(
+                    case![State::AddingPracticeNoteReceiveText {
+                        daily_practice_log_id
+                    }]
+                    .endpoint(receive_practice_note_handler),
+                )
+                .branch(
+                    case![State::AddingPracticeCategoryReceiveName]
+                        .endpoint(receive_practice_category_name_handler),
+                )
+                .branch(
+                    case![State::SettingPracticeReminderReceiveTime]
+                        .endpoint(receive_practice_reminder_time_handler),
+                )
+                .branch(
+                    case![State::SettingPracticeGoalReceiveMinutes { period }]
+                        .endpoint(receive_practice_goal_handler),
+                ),
         )
         .branch(
             Update::filter_callback_query()
-                .enter_dialogue::<CallbackQuery, InMemStorage<State>, State>()
+                .enter_dialogue::<CallbackQuery, DialogueStorage, State>()
                 .branch(case![State::Idle].endpoint(idle_callback_handler))
+                .branch(
+                    case![State::AddingDailyPracticeReceiveMinutes]
+                        .endpoint(practice_date_callback_handler),
+                )
+                .branch(
+                    case![State::AddingPastDailyPracticeReceiveMinutes { practiced_on }]
+                        .endpoint(practice_date_callback_handler),
+                )
+                .branch(
+                    case![State::AddingDailyPracticeReceiveCategory {
+                        minutes,
+                        practiced_on
+                    }]
+                    .endpoint(receive_practice_category_callback_handler),
+                )
+                .branch(
+                    case![State::AddingDailyPracticeReceiveNote {
+                        minutes,
+                        practiced_on,
+                        practice_category_id
+                    }]
+                    .endpoint(practice_note_skip_callback_handler),
+                )
                 .endpoint(stale_callback_handler),
         );
 
     Dispatcher::builder(bot, handler)
-        .dependencies(dptree::deps![InMemStorage::<State>::new(), di])
+        .dependencies(dptree::deps![storage, di])
         .enable_ctrlc_handler()
         .build()
         .dispatch()
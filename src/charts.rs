@@ -0,0 +1,307 @@
+//! Practice charts rendered to PNG right in the bot.
+//!
+//! Images carry only numbers; everything that needs words goes into the
+//! caption of the photo.
+
+pub mod png;
+
+use chrono::{Datelike, Days, NaiveDate};
+
+const BACKGROUND: u8 = 0;
+const TEXT: u8 = 1;
+const GRID: u8 = 2;
+const BAR: u8 = 3;
+/// Heatmap cells from "no practice" to "the most practice".
+const HEAT: [u8; 5] = [4, 5, 6, 7, 8];
+
+const PALETTE: [[u8; 3]; 9] = [
+    [255, 255, 255],
+    [70, 70, 70],
+    [225, 225, 225],
+    [64, 160, 90],
+    [235, 237, 240],
+    [190, 230, 190],
+    [120, 200, 130],
+    [60, 160, 80],
+    [30, 100, 50],
+];
+
+/// 3×5 glyphs, one row per byte, the leftmost pixel in bit 2.
+fn glyph(c: char) -> Option<[u8; 5]> {
+    let rows = match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+const FONT_SCALE: u32 = 2;
+const GLYPH_WIDTH: u32 = 3 * FONT_SCALE;
+const GLYPH_HEIGHT: u32 = 5 * FONT_SCALE;
+const GLYPH_ADVANCE: u32 = GLYPH_WIDTH + FONT_SCALE;
+
+fn text_width(text: &str) -> u32 {
+    (text.chars().count() as u32 * GLYPH_ADVANCE).saturating_sub(FONT_SCALE)
+}
+
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![BACKGROUND; width as usize * height as usize],
+        }
+    }
+
+    /// Clipped to the canvas.
+    fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: u8) {
+        let x_end = x.saturating_add(width).min(self.width);
+        let y_end = y.saturating_add(height).min(self.height);
+        for row in y..y_end {
+            let start = (row * self.width) as usize;
+            self.pixels[start + x as usize..start + x_end as usize].fill(color);
+        }
+    }
+
+    /// Draws `text` with its top left corner at `x`, `y`; characters
+    /// without a glyph are left blank.
+    fn draw_text(&mut self, x: u32, y: u32, text: &str) {
+        for (i, c) in text.chars().enumerate() {
+            let Some(rows) = glyph(c) else {
+                continue;
+            };
+            let left = x + i as u32 * GLYPH_ADVANCE;
+            for (dy, bits) in rows.iter().enumerate() {
+                for dx in 0..3 {
+                    if bits & (0b100 >> dx) != 0 {
+                        self.fill_rect(
+                            left + dx * FONT_SCALE,
+                            y + dy as u32 * FONT_SCALE,
+                            FONT_SCALE,
+                            FONT_SCALE,
+                            TEXT,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn into_png(self) -> Vec<u8> {
+        png::encode_indexed(self.width, self.height, &PALETTE, &self.pixels)
+    }
+}
+
+/// One bar of a bar chart.
+pub struct Bar {
+    /// Shown under the bar; only digits and dots are drawn.
+    pub label: String,
+    pub minutes: u32,
+}
+
+/// The smallest "round" step that splits `0..=max` into at most five parts.
+fn axis_step(max: u32) -> u32 {
+    const STEPS: [u32; 10] = [5, 10, 15, 30, 60, 120, 180, 300, 600, 1200];
+    STEPS
+        .into_iter()
+        .find(|step| max.div_ceil(*step) <= 5)
+        .unwrap_or_else(|| max.div_ceil(5).max(1))
+}
+
+/// Minutes per bar with a labelled minutes axis.
+pub fn render_bar_chart(bars: &[Bar]) -> Vec<u8> {
+    const WIDTH: u32 = 800;
+    const HEIGHT: u32 = 400;
+    const LEFT: u32 = 60;
+    const RIGHT: u32 = 20;
+    const TOP: u32 = 20;
+    const BOTTOM: u32 = 40;
+    let plot_width = WIDTH - LEFT - RIGHT;
+    let plot_height = HEIGHT - TOP - BOTTOM;
+    let plot_bottom = TOP + plot_height;
+
+    let mut canvas = Canvas::new(WIDTH, HEIGHT);
+
+    let max = bars.iter().map(|b| b.minutes).max().unwrap_or(0);
+    let step = axis_step(max);
+    let axis_max = max.div_ceil(step).max(1) * step;
+    for i in 0..=axis_max / step {
+        let value = i * step;
+        let y =
+            plot_bottom - (u64::from(value) * u64::from(plot_height) / u64::from(axis_max)) as u32;
+        canvas.fill_rect(LEFT, y, plot_width, 1, GRID);
+        let label = value.to_string();
+        canvas.draw_text(
+            LEFT - 10 - text_width(&label),
+            y.saturating_sub(GLYPH_HEIGHT / 2),
+            &label,
+        );
+    }
+
+    if bars.is_empty() {
+        return canvas.into_png();
+    }
+
+    let slot = plot_width / bars.len() as u32;
+    let bar_width = (slot * 7 / 10).max(1);
+    let widest_label = bars.iter().map(|b| text_width(&b.label)).max().unwrap_or(0);
+    // Labels that would overlap are thinned out to every n-th bar.
+    let label_every = (widest_label + 2 * GLYPH_ADVANCE)
+        .div_ceil(slot.max(1))
+        .max(1) as usize;
+    for (i, bar) in bars.iter().enumerate() {
+        let slot_left = LEFT + i as u32 * slot;
+        let height = (u64::from(bar.minutes) * u64::from(plot_height) / u64::from(axis_max)) as u32;
+        canvas.fill_rect(
+            slot_left + (slot - bar_width) / 2,
+            plot_bottom - height,
+            bar_width,
+            height,
+            BAR,
+        );
+        if i % label_every == 0 {
+            let label_left = (slot_left + slot / 2).saturating_sub(text_width(&bar.label) / 2);
+            canvas.draw_text(label_left, plot_bottom + 12, &bar.label);
+        }
+    }
+    canvas.fill_rect(LEFT, plot_bottom, plot_width, 1, TEXT);
+
+    canvas.into_png()
+}
+
+/// Weeks shown by the heatmap, the current one included.
+pub const HEATMAP_WEEKS: u32 = 53;
+
+/// Heat level of a day, `0` for no practice and up to `4` for the days
+/// closest to `max`.
+fn heat_level(minutes: u32, max: u32) -> usize {
+    if minutes == 0 || max == 0 {
+        return 0;
+    }
+    1 + ((minutes - 1) * 4 / max).min(3) as usize
+}
+
+/// A calendar of the `HEATMAP_WEEKS` weeks up to `until`: a column per week starting on
+/// Monday, a cell per day, darker for more minutes. Month numbers mark the
+/// weeks where months begin. `days` are per-day totals.
+pub fn render_heatmap(days: &[(NaiveDate, u32)], until: NaiveDate) -> Vec<u8> {
+    const CELL: u32 = 14;
+    const GAP: u32 = 3;
+    const MARGIN: u32 = 20;
+    const TOP: u32 = 40;
+    let pitch = CELL + GAP;
+    let width = 2 * MARGIN + HEATMAP_WEEKS * pitch - GAP;
+    let height = TOP + 7 * pitch + 50;
+
+    let mut canvas = Canvas::new(width, height);
+
+    let until_monday = until - Days::new(until.weekday().num_days_from_monday().into());
+    let first_monday = until_monday - Days::new(u64::from(HEATMAP_WEEKS - 1) * 7);
+    let minutes_on = |date: NaiveDate| -> u32 {
+        days.iter()
+            .filter(|(day, _)| *day == date)
+            .map(|(_, minutes)| minutes)
+            .sum()
+    };
+    let max = days
+        .iter()
+        .filter(|(day, _)| (first_monday..=until).contains(day))
+        .map(|(_, minutes)| *minutes)
+        .max()
+        .unwrap_or(0);
+
+    let mut date = first_monday;
+    while date <= until {
+        let week = (date - first_monday).num_days() as u32 / 7;
+        let weekday = date.weekday().num_days_from_monday();
+        let x = MARGIN + week * pitch;
+        if date.day() == 1 {
+            canvas.draw_text(x, TOP - GLYPH_HEIGHT - 10, &date.month().to_string());
+        }
+        let color = HEAT[heat_level(minutes_on(date), max)];
+        canvas.fill_rect(x, TOP + weekday * pitch, CELL, CELL, color);
+        date = date + Days::new(1);
+    }
+
+    // Legend: "0 ▢▢▢▢▢ max".
+    let legend_top = TOP + 7 * pitch + 20;
+    let max_label = max.to_string();
+    let legend_width = HEAT.len() as u32 * pitch - GAP;
+    let legend_right = width - MARGIN - text_width(&max_label) - 8;
+    let legend_left = legend_right - legend_width;
+    canvas.draw_text(legend_left - 8 - text_width("0"), legend_top + 2, "0");
+    for (i, color) in HEAT.iter().enumerate() {
+        canvas.fill_rect(
+            legend_left + i as u32 * pitch,
+            legend_top,
+            CELL,
+            CELL,
+            *color,
+        );
+    }
+    canvas.draw_text(legend_right + 8, legend_top + 2, &max_label);
+
+    canvas.into_png()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png_size(png: &[u8]) -> (u32, u32) {
+        (
+            u32::from_be_bytes(png[16..20].try_into().unwrap()),
+            u32::from_be_bytes(png[20..24].try_into().unwrap()),
+        )
+    }
+
+    #[test]
+    fn test_axis_step() {
+        assert_eq!(axis_step(0), 5);
+        assert_eq!(axis_step(25), 5);
+        assert_eq!(axis_step(26), 10);
+        assert_eq!(axis_step(90), 30);
+        assert_eq!(axis_step(20000), 4000);
+    }
+
+    #[test]
+    fn test_heat_level() {
+        assert_eq!(heat_level(0, 100), 0);
+        assert_eq!(heat_level(1, 100), 1);
+        assert_eq!(heat_level(50, 100), 2);
+        assert_eq!(heat_level(100, 100), 4);
+    }
+
+    #[test]
+    fn test_render_charts() {
+        let bars: Vec<Bar> = (1..=31)
+            .map(|day| Bar {
+                label: day.to_string(),
+                minutes: day * 7,
+            })
+            .collect();
+        assert_eq!(png_size(&render_bar_chart(&bars)), (800, 400));
+        assert_eq!(png_size(&render_bar_chart(&[])), (800, 400));
+
+        let until = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let days = vec![(until, 45), (until - Days::new(3), 20)];
+        let (width, _) = png_size(&render_heatmap(&days, until));
+        assert_eq!(width, 2 * 20 + 53 * 17 - 3);
+    }
+}
@@ -0,0 +1,65 @@
+//! PNG encoding of palette images.
+//!
+//! Charts are made of a few flat colours, so every pixel is an index into a
+//! small palette. Rows of a chart mostly repeat the one above, which the
+//! `Up` filter turns into zeros that deflate down to a few kilobytes.
+
+use ::png::{BitDepth, ColorType, Compression, Encoder, FilterType};
+
+/// Encodes a `width`×`height` image whose pixels are indices into `palette`.
+pub fn encode_indexed(width: u32, height: u32, palette: &[[u8; 3]], pixels: &[u8]) -> Vec<u8> {
+    assert_eq!(pixels.len(), width as usize * height as usize);
+    assert!(!palette.is_empty() && palette.len() <= 256);
+
+    let mut png = vec![];
+    let mut encoder = Encoder::new(&mut png, width, height);
+    encoder.set_color(ColorType::Indexed);
+    encoder.set_depth(BitDepth::Eight);
+    encoder.set_palette(palette.iter().flatten().copied().collect::<Vec<u8>>());
+    encoder.set_compression(Compression::Best);
+    encoder.set_filter(FilterType::Up);
+
+    // Writing to a `Vec` cannot fail and the header matches the pixels.
+    let mut writer = encoder
+        .write_header()
+        .expect("PNG header is written to memory");
+    writer
+        .write_image_data(pixels)
+        .expect("PNG data is written to memory");
+    writer.finish().expect("PNG is written to memory");
+    png
+}
+
+#[cfg(test)]
+mod tests {
+    use ::png::{ColorType, Decoder, Transformations};
+
+    use super::*;
+
+    #[test]
+    fn test_encode_indexed() {
+        let palette = [[255, 255, 255], [0, 0, 0], [200, 30, 30]];
+        let pixels: Vec<u8> = (0..40 * 30).map(|i| (i % 7 % 3) as u8).collect();
+        let png = encode_indexed(40, 30, &palette, &pixels);
+
+        let mut decoder = Decoder::new(png.as_slice());
+        // Keep the palette indices rather than expanding them to RGB.
+        decoder.set_transformations(Transformations::IDENTITY);
+        let mut reader = decoder.read_info().unwrap();
+        let info = reader.info();
+        assert_eq!((info.width, info.height), (40, 30));
+        assert_eq!(info.color_type, ColorType::Indexed);
+        assert_eq!(info.palette.as_deref(), Some(palette.concat().as_slice()));
+
+        let mut decoded = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut decoded).unwrap();
+        assert_eq!(&decoded[..frame.buffer_size()], pixels.as_slice());
+    }
+
+    #[test]
+    fn test_repeated_rows_compress() {
+        let palette = [[255, 255, 255]];
+        let png = encode_indexed(800, 400, &palette, &vec![0; 800 * 400]);
+        assert!(png.len() < 4096, "{} bytes", png.len());
+    }
+}
@@ -11,6 +11,10 @@ pub enum Command {
     CancelOperation,
     #[command(description = "Помощь ℹ️")]
     Help,
+    #[command(description = "Часовой пояс, например /timezone +3 🕒")]
+    Timezone(String),
+    #[command(description = "Поиск по заметкам к практике, например /search гаммы 🔎")]
+    Search(String),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -22,9 +26,21 @@ pub enum MenuAction {
     ListClasses,
     ClassesDeductionHistory,
     UpdateQuantity,
+    EditClass,
+    DeleteClass,
+    ArchivedClasses,
+    ClassSpending,
     DailyPracticeLog,
     AddDailyPracticeEntry,
+    PracticeStopwatch,
     DailyPracticeLogHistory,
+    PracticeCharts,
+    PracticeReminder,
+    PracticeEntries,
+    PracticeCategories,
+    PracticeGoals,
+    PracticeDigests,
+    Export,
     MainMenu,
 }
 
@@ -38,9 +54,21 @@ impl MenuAction {
             MenuAction::ListClasses => "Список занятий",
             MenuAction::ClassesDeductionHistory => "История списаний",
             MenuAction::UpdateQuantity => "Обновить количество",
+            MenuAction::EditClass => "Изменить занятие",
+            MenuAction::DeleteClass => "Удалить занятие",
+            MenuAction::ArchivedClasses => "Архив занятий",
+            MenuAction::ClassSpending => "Расходы",
             MenuAction::DailyPracticeLog => "Дневник практик",
             MenuAction::AddDailyPracticeEntry => "Добавить запись",
+            MenuAction::PracticeStopwatch => "Секундомер",
             MenuAction::DailyPracticeLogHistory => "История практик",
+            MenuAction::PracticeCharts => "Графики",
+            MenuAction::PracticeReminder => "Напоминание",
+            MenuAction::PracticeEntries => "Записи",
+            MenuAction::PracticeCategories => "Категории",
+            MenuAction::PracticeGoals => "Цели",
+            MenuAction::PracticeDigests => "Сводки",
+            MenuAction::Export => "Экспорт",
             MenuAction::MainMenu => "Главное меню",
         }
     }
@@ -54,9 +82,21 @@ impl MenuAction {
             "Список занятий" => Some(MenuAction::ListClasses),
             "История списаний" => Some(MenuAction::ClassesDeductionHistory),
             "Обновить количество" => Some(MenuAction::UpdateQuantity),
+            "Изменить занятие" => Some(MenuAction::EditClass),
+            "Удалить занятие" => Some(MenuAction::DeleteClass),
+            "Архив занятий" => Some(MenuAction::ArchivedClasses),
+            "Расходы" => Some(MenuAction::ClassSpending),
             "Дневник практик" => Some(MenuAction::DailyPracticeLog),
             "Добавить запись" => Some(MenuAction::AddDailyPracticeEntry),
+            "Секундомер" => Some(MenuAction::PracticeStopwatch),
             "История практик" => Some(MenuAction::DailyPracticeLogHistory),
+            "Графики" => Some(MenuAction::PracticeCharts),
+            "Напоминание" => Some(MenuAction::PracticeReminder),
+            "Записи" => Some(MenuAction::PracticeEntries),
+            "Категории" => Some(MenuAction::PracticeCategories),
+            "Цели" => Some(MenuAction::PracticeGoals),
+            "Сводки" => Some(MenuAction::PracticeDigests),
+            "Экспорт" => Some(MenuAction::Export),
             "Главное меню" => Some(MenuAction::MainMenu),
             _ => None,
         }
@@ -12,12 +12,107 @@ pub struct RedisConfig {
     pub rate_limit: u16,
 }
 
+/// What happens when a class is deducted from an expired subscription.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExpiredDeductionPolicy {
+    #[default]
+    Warn,
+    Block,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ClassesConfig {
+    pub undo_deduction_window_secs: u64,
+    pub expired_deduction_policy: ExpiredDeductionPolicy,
+    /// Currency assumed when a price is entered without one.
+    pub default_currency: String,
+    /// How long after a scheduled class to ask whether it was attended.
+    pub attendance_prompt_delay_mins: u64,
+}
+
+impl Default for ClassesConfig {
+    fn default() -> Self {
+        Self {
+            undo_deduction_window_secs: 300,
+            expired_deduction_policy: ExpiredDeductionPolicy::default(),
+            default_currency: "RUB".to_string(),
+            attendance_prompt_delay_mins: 15,
+        }
+    }
+}
+
+/// What the scheduler does with a job whose time passed while the bot was
+/// down.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedJobPolicy {
+    /// Drop the missed run; recurring jobs wait for their next occurrence.
+    Skip,
+    /// Run once, however many occurrences were missed.
+    #[default]
+    RunOnce,
+    /// Run every missed occurrence of a recurring job.
+    RunAll,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    pub poll_interval_secs: u64,
+    pub missed_job_policy: MissedJobPolicy,
+    /// A job this late or less is not treated as missed.
+    pub missed_job_grace_secs: u64,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs: 15,
+            missed_job_policy: MissedJobPolicy::default(),
+            missed_job_grace_secs: 300,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct PracticeConfig {
+    /// A stopwatch left running this long is stopped automatically.
+    pub max_session_mins: u64,
+}
+
+impl Default for PracticeConfig {
+    fn default() -> Self {
+        Self {
+            max_session_mins: 240,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DialogueStorageBackend {
+    #[default]
+    Sqlite,
+    Redis,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
     pub debug: bool,
     pub bot_token: String,
     pub database: DatabaseConfig,
     pub redis: RedisConfig,
+    #[serde(default)]
+    pub classes: ClassesConfig,
+    #[serde(default)]
+    pub dialogue_storage: DialogueStorageBackend,
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
+    #[serde(default)]
+    pub practice: PracticeConfig,
 }
 
 impl Config {
@@ -0,0 +1,269 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use futures::future::BoxFuture;
+use redis::{AsyncCommands, aio::ConnectionManager};
+use serde::{Serialize, de::DeserializeOwned};
+use sqlx::SqlitePool;
+use teloxide::{dispatching::dialogue::Storage, types::ChatId};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DialogueStorageError {
+    #[error("dialogue serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] sqlx::Error),
+    #[error("redis error: {0}")]
+    Redis(#[from] redis::RedisError),
+    #[error("dialogue not found")]
+    DialogueNotFound,
+}
+
+/// A state saved by an older version of the bot may no longer deserialize.
+/// Such a dialogue starts over from the default state rather than failing
+/// every update of the chat.
+fn deserialize_dialogue<D: DeserializeOwned>(chat_id: ChatId, state: &[u8]) -> Option<D> {
+    match serde_json::from_slice(state) {
+        Ok(dialogue) => Some(dialogue),
+        Err(err) => {
+            log::warn!("Resetting unreadable dialogue of chat {}: {}", chat_id, err);
+            None
+        }
+    }
+}
+
+/// Keeps dialogues in the `dialogue` table of the main database, so a
+/// half-finished flow survives a restart of the bot.
+pub struct SqliteDialogueStorage<D> {
+    pool: Arc<SqlitePool>,
+    _state: PhantomData<fn() -> D>,
+}
+
+impl<D> SqliteDialogueStorage<D> {
+    pub fn new(pool: Arc<SqlitePool>) -> Arc<Self> {
+        Arc::new(Self {
+            pool,
+            _state: PhantomData,
+        })
+    }
+}
+
+impl<D> Storage<D> for SqliteDialogueStorage<D>
+where
+    D: Serialize + DeserializeOwned + Send + 'static,
+{
+    type Error = DialogueStorageError;
+
+    fn remove_dialogue(
+        self: Arc<Self>,
+        chat_id: ChatId,
+    ) -> BoxFuture<'static, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("delete from dialogue where chat_id = ?")
+                .bind(chat_id.0)
+                .execute(self.pool.as_ref())
+                .await?;
+
+            if result.rows_affected() == 0 {
+                return Err(DialogueStorageError::DialogueNotFound);
+            }
+            Ok(())
+        })
+    }
+
+    fn update_dialogue(
+        self: Arc<Self>,
+        chat_id: ChatId,
+        dialogue: D,
+    ) -> BoxFuture<'static, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let state = serde_json::to_vec(&dialogue)?;
+            sqlx::query(
+                "insert into dialogue (chat_id, state)
+                 values (?, ?)
+                 on conflict (chat_id) do update
+                 set state = excluded.state, updated_at = current_timestamp",
+            )
+            .bind(chat_id.0)
+            .bind(state)
+            .execute(self.pool.as_ref())
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn get_dialogue(
+        self: Arc<Self>,
+        chat_id: ChatId,
+    ) -> BoxFuture<'static, Result<Option<D>, Self::Error>> {
+        Box::pin(async move {
+            let row: Option<(Vec<u8>,)> =
+                sqlx::query_as("select state from dialogue where chat_id = ?")
+                    .bind(chat_id.0)
+                    .fetch_optional(self.pool.as_ref())
+                    .await?;
+
+            Ok(row.and_then(|(state,)| deserialize_dialogue(chat_id, &state)))
+        })
+    }
+}
+
+/// Keeps dialogues in Redis under `{namespace}:dialogue:{chat_id}`, sharing
+/// the connection with the rate limiter.
+pub struct RedisDialogueStorage<D> {
+    conn: ConnectionManager,
+    namespace: String,
+    _state: PhantomData<fn() -> D>,
+}
+
+impl<D> RedisDialogueStorage<D> {
+    pub fn new(conn: ConnectionManager, namespace: &str) -> Arc<Self> {
+        Arc::new(Self {
+            conn,
+            namespace: namespace.into(),
+            _state: PhantomData,
+        })
+    }
+
+    fn build_key(&self, chat_id: ChatId) -> String {
+        format!("{}:dialogue:{}", self.namespace, chat_id.0)
+    }
+}
+
+impl<D> Storage<D> for RedisDialogueStorage<D>
+where
+    D: Serialize + DeserializeOwned + Send + 'static,
+{
+    type Error = DialogueStorageError;
+
+    fn remove_dialogue(
+        self: Arc<Self>,
+        chat_id: ChatId,
+    ) -> BoxFuture<'static, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let mut conn = self.conn.clone();
+            let deleted: i64 = conn.del(self.build_key(chat_id)).await?;
+            if deleted == 0 {
+                return Err(DialogueStorageError::DialogueNotFound);
+            }
+            Ok(())
+        })
+    }
+
+    fn update_dialogue(
+        self: Arc<Self>,
+        chat_id: ChatId,
+        dialogue: D,
+    ) -> BoxFuture<'static, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let state = serde_json::to_vec(&dialogue)?;
+            let mut conn = self.conn.clone();
+            let _: () = conn.set(self.build_key(chat_id), state).await?;
+            Ok(())
+        })
+    }
+
+    fn get_dialogue(
+        self: Arc<Self>,
+        chat_id: ChatId,
+    ) -> BoxFuture<'static, Result<Option<D>, Self::Error>> {
+        Box::pin(async move {
+            let mut conn = self.conn.clone();
+            let state: Option<Vec<u8>> = conn.get(self.build_key(chat_id)).await?;
+            Ok(state.and_then(|state| deserialize_dialogue(chat_id, &state)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use teloxide::{dispatching::dialogue::Storage, types::ChatId};
+
+    use super::{DialogueStorageError, SqliteDialogueStorage};
+    use crate::{state::State, test_utils, utils};
+
+    #[tokio::test]
+    async fn test_sqlite_storage_roundtrip() -> anyhow::Result<()> {
+        let pool = test_utils::setup_db().await;
+        let storage = SqliteDialogueStorage::<State>::new(Arc::new(pool));
+        let chat_id = ChatId(42);
+
+        assert!(storage.clone().get_dialogue(chat_id).await?.is_none());
+
+        storage
+            .clone()
+            .update_dialogue(chat_id, State::UpdatingClassReceiveQuantity { class_id: 7 })
+            .await?;
+        let state = storage.clone().get_dialogue(chat_id).await?;
+        assert!(matches!(
+            state,
+            Some(State::UpdatingClassReceiveQuantity { class_id: 7 })
+        ));
+
+        storage
+            .clone()
+            .update_dialogue(
+                chat_id,
+                State::AddingClassReceiveQuantity {
+                    name: "Йога".into(),
+                },
+            )
+            .await?;
+        let state = storage.clone().get_dialogue(chat_id).await?;
+        assert!(matches!(
+            state,
+            Some(State::AddingClassReceiveQuantity { ref name }) if name == "Йога"
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_storage_resets_unreadable_dialogue() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        let storage = SqliteDialogueStorage::<State>::new(pool.clone());
+        let chat_id = ChatId(44);
+
+        // `AddingDailyPracticeReceiveCategory` as saved before it got `practiced_on`.
+        sqlx::query("insert into dialogue (chat_id, state) values (?, ?)")
+            .bind(chat_id.0)
+            .bind(br#"{"AddingDailyPracticeReceiveCategory":{"minutes":30}}"#.to_vec())
+            .execute(pool.as_ref())
+            .await?;
+        let state = storage.clone().get_dialogue(chat_id).await?;
+        assert!(matches!(
+            state,
+            Some(State::AddingDailyPracticeReceiveCategory { minutes: 30, ref practiced_on })
+                if *practiced_on == utils::today().to_string()
+        ));
+
+        sqlx::query("update dialogue set state = ? where chat_id = ?")
+            .bind(br#"{"NoSuchState":{}}"#.to_vec())
+            .bind(chat_id.0)
+            .execute(pool.as_ref())
+            .await?;
+        assert!(storage.clone().get_dialogue(chat_id).await?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_storage_remove() -> anyhow::Result<()> {
+        let pool = test_utils::setup_db().await;
+        let storage = SqliteDialogueStorage::<State>::new(Arc::new(pool));
+        let chat_id = ChatId(43);
+
+        let err = storage.clone().remove_dialogue(chat_id).await.unwrap_err();
+        assert!(matches!(err, DialogueStorageError::DialogueNotFound));
+
+        storage
+            .clone()
+            .update_dialogue(chat_id, State::AddingClassReceiveName)
+            .await?;
+        storage.clone().remove_dialogue(chat_id).await?;
+        assert!(storage.clone().get_dialogue(chat_id).await?.is_none());
+
+        Ok(())
+    }
+}
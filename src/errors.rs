@@ -17,3 +17,79 @@ pub struct NotEnoughClassQuantityToDeductError(pub u8);
 #[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
 #[error("Занятие с таким именем же существует. Пожалуйста, выберите другое имя.")]
 pub struct DuplicateClassNameError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Не удалось найти списание")]
+pub struct DeductionNotFoundError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Время для отмены списания истекло")]
+pub struct DeductionUndoExpiredError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Занятие изменилось после списания, отменить его уже нельзя")]
+pub struct ClassChangedSinceDeductionError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Занятие находится в архиве. Сначала восстановите его.")]
+pub struct ClassArchivedError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Срок действия абонемента истёк {0}")]
+pub struct ClassExpiredError(pub String);
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("У занятия не указан срок действия абонемента")]
+pub struct ClassHasNoExpiryError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Абонемент уже заморожен до {0}")]
+pub struct ClassAlreadyFrozenError(pub String);
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Нельзя выбрать дату в будущем")]
+pub struct FutureDateError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Слишком много занятий: остаток не может превышать {0}")]
+pub struct ClassQuantityLimitError(pub u8);
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Не удалось найти время в расписании")]
+pub struct ClassScheduleNotFoundError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Не удалось найти вопрос о посещении")]
+pub struct AttendanceNotFoundError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Вы уже ответили на этот вопрос")]
+pub struct AttendanceAlreadyAnsweredError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Расписание задания больше никогда не сработает")]
+pub struct JobTimingError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Не удалось найти запись о практике")]
+pub struct PracticeEntryNotFoundError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Нельзя записать практику за будущий день")]
+pub struct FuturePracticeDateError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Не удалось найти категорию практики")]
+pub struct PracticeCategoryNotFoundError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Категория с таким названием уже существует")]
+pub struct DuplicatePracticeCategoryError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Секундомер уже запущен")]
+pub struct PracticeSessionAlreadyStartedError;
+
+#[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
+#[error("Секундомер не запущен")]
+pub struct PracticeSessionNotStartedError;
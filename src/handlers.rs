@@ -1,4 +1,8 @@
 pub mod class;
+pub mod class_schedule;
 pub mod command;
 pub mod common;
 pub mod daily_practice_log;
+pub mod export;
+pub mod practice_chart;
+pub mod practice_session;
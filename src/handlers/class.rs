@@ -1,20 +1,27 @@
-use std::{error::Error, sync::Arc};
+use chrono::{Datelike, NaiveDate};
+use std::{error::Error, sync::Arc, time::Duration};
 use teloxide::{
-    dispatching::dialogue::InMemStorage, payloads::SendMessageSetters, prelude::*,
-    types::ParseMode, utils::html,
+    payloads::{AnswerCallbackQuerySetters, EditMessageTextSetters, SendMessageSetters},
+    prelude::*,
+    types::ParseMode,
+    utils::html,
 };
 
 use crate::{
     bot::DI,
     commands::MenuAction,
+    errors::DuplicateClassNameError,
+    handlers::daily_practice_log::user_today,
     keyboards::{self, MainMenuButton},
-    services::class::*,
-    state::State,
+    repositories::{class::Class, class_pack::PackPrice},
+    services::{class::*, job::get_user_utc_offset, spending::get_spending_report},
+    state::{BotDialogue, State},
+    utils,
 };
 
 pub async fn receive_name(
     bot: Bot,
-    dialogue: Dialogue<State, InMemStorage<State>>,
+    dialogue: BotDialogue,
     msg: Message,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     match msg.text() {
@@ -35,32 +42,333 @@ pub async fn receive_name(
 
 pub async fn receive_quantity(
     bot: Bot,
-    dialogue: Dialogue<State, InMemStorage<State>>,
+    dialogue: BotDialogue,
     name: String,
     msg: Message,
-    di: Arc<DI>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     match msg.text().map(|text| text.parse::<u8>()) {
         Some(Ok(quantity)) => {
-            let class = match add_class(di.db_pool.clone(), name, quantity, msg.chat.id.0).await {
+            bot.send_message(
+                msg.chat.id,
+                "Введите дату окончания абонемента (ДД.ММ.ГГГГ) или «-», если срока нет",
+            )
+            .await?;
+            dialogue
+                .update(State::AddingClassReceiveValidUntil { name, quantity })
+                .await?;
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Отправьте число").await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn receive_valid_until(
+    bot: Bot,
+    dialogue: BotDialogue,
+    (name, quantity): (String, u8),
+    msg: Message,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match msg.text().map(parse_valid_until) {
+        Some(Some(valid_until)) => {
+            bot.send_message(msg.chat.id, PRICE_PROMPT).await?;
+            dialogue
+                .update(State::AddingClassReceivePrice {
+                    name,
+                    quantity,
+                    valid_until: valid_until.map(|d| d.to_string()),
+                })
+                .await?;
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Отправьте дату в формате ДД.ММ.ГГГГ или «-»")
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn receive_price(
+    bot: Bot,
+    dialogue: BotDialogue,
+    (name, quantity, valid_until): (String, u8, Option<String>),
+    msg: Message,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match msg
+        .text()
+        .map(|text| parse_optional_price(text, &di.config.classes.default_currency))
+    {
+        Some(Some(price)) => {
+            let output = match add_class(
+                di.db_pool.clone(),
+                name,
+                quantity,
+                valid_until.and_then(|d| d.parse().ok()),
+                price,
+                msg.chat.id.0,
+            )
+            .await
+            {
                 Ok(_) => "✅ Занятие успешно добавлено!".to_string(),
                 Err(err) => err.to_string(),
             };
-            bot.send_message(msg.chat.id, class).await?;
+            bot.send_message(msg.chat.id, output).await?;
             dialogue.exit().await?;
         }
         _ => {
-            bot.send_message(msg.chat.id, "Отправьте число").await?;
+            bot.send_message(msg.chat.id, PRICE_FORMAT_HINT).await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn receive_new_valid_until_handler(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    di: Arc<DI>,
+    class_id: i64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match msg.text().map(parse_valid_until) {
+        Some(Some(valid_until)) => {
+            let output = match set_class_valid_until(
+                di.db_pool.clone(),
+                class_id,
+                msg.chat.id.0,
+                valid_until,
+            )
+            .await
+            {
+                Ok(class) => format!(
+                    "✅ Срок действия занятия {name} обновлён: {validity}",
+                    name = html::escape(&class.name),
+                    validity = format_validity(&class)
+                ),
+                Err(err) => html::escape(&err.to_string()),
+            };
+            dialogue.exit().await?;
+            bot.send_message(msg.chat.id, output)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Отправьте дату в формате ДД.ММ.ГГГГ или «-»")
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn receive_freeze_days_handler(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    di: Arc<DI>,
+    class_id: i64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match msg.text().and_then(|text| text.parse::<u16>().ok()) {
+        Some(days) if days > 0 => {
+            let output = match freeze_class(di.db_pool.clone(), class_id, msg.chat.id.0, days).await
+            {
+                Ok(class) => format!(
+                    "❄️ Занятие {name} заморожено на {days} дн. Новый срок: {validity}",
+                    name = html::escape(&class.name),
+                    validity = format_validity(&class)
+                ),
+                Err(err) => html::escape(&err.to_string()),
+            };
+            dialogue.exit().await?;
+            bot.send_message(msg.chat.id, output)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Отправьте целое число больше нуля")
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn receive_low_balance_threshold_handler(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    di: Arc<DI>,
+    class_id: i64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let threshold = match msg.text().map(str::trim) {
+        Some("-") => Some(None),
+        Some(text) => text.parse::<u8>().ok().map(Some),
+        None => None,
+    };
+
+    match threshold {
+        Some(threshold) => {
+            let output = match set_class_low_balance_threshold(
+                di.db_pool.clone(),
+                class_id,
+                msg.chat.id.0,
+                threshold,
+            )
+            .await
+            {
+                Ok(class) => match class.low_balance_threshold {
+                    Some(threshold) => format!(
+                        "✅ Напоминание для занятия {name} придёт, когда останется {threshold} или меньше",
+                        name = html::escape(&class.name)
+                    ),
+                    None => format!(
+                        "✅ Напоминание об остатке для занятия {name} выключено",
+                        name = html::escape(&class.name)
+                    ),
+                },
+                Err(err) => html::escape(&err.to_string()),
+            };
+            dialogue.exit().await?;
+            bot.send_message(msg.chat.id, output)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        None => {
+            bot.send_message(msg.chat.id, "Отправьте число или «-»")
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn receive_top_up_quantity_handler(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    class_id: i64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match msg.text().and_then(|text| text.parse::<u8>().ok()) {
+        Some(quantity) if quantity > 0 => {
+            bot.send_message(
+                msg.chat.id,
+                "До какого числа действует новый абонемент (ДД.ММ.ГГГГ)? Отправьте «-», если срока нет",
+            )
+            .await?;
+            dialogue
+                .update(State::ToppingUpClassReceiveValidUntil { class_id, quantity })
+                .await?;
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Отправьте целое число больше нуля")
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn receive_top_up_valid_until_handler(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    (class_id, quantity): (i64, u8),
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match msg.text().map(parse_valid_until) {
+        Some(Some(valid_until)) => {
+            bot.send_message(msg.chat.id, PRICE_PROMPT).await?;
+            dialogue
+                .update(State::ToppingUpClassReceivePrice {
+                    class_id,
+                    quantity,
+                    valid_until: valid_until.map(|d| d.to_string()),
+                })
+                .await?;
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Отправьте дату в формате ДД.ММ.ГГГГ или «-»")
+                .await?;
         }
     }
+    Ok(())
+}
 
+pub async fn receive_top_up_price_handler(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    di: Arc<DI>,
+    (class_id, quantity, valid_until): (i64, u8, Option<String>),
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match msg
+        .text()
+        .map(|text| parse_optional_price(text, &di.config.classes.default_currency))
+    {
+        Some(Some(price)) => {
+            let output = match top_up_class(
+                di.db_pool.clone(),
+                class_id,
+                msg.chat.id.0,
+                quantity,
+                valid_until.and_then(|d| d.parse().ok()),
+                price,
+            )
+            .await
+            {
+                Ok(class) => format!(
+                    "✅ Занятие {name} пополнено на {quantity}. Остаток: {balance}, {validity}",
+                    name = html::escape(&class.name),
+                    balance = class.quantity,
+                    validity = format_validity(&class)
+                ),
+                Err(err) => html::escape(&err.to_string()),
+            };
+            dialogue.exit().await?;
+            bot.send_message(msg.chat.id, output)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        _ => {
+            bot.send_message(msg.chat.id, PRICE_FORMAT_HINT).await?;
+        }
+    }
     Ok(())
 }
 
+const PRICE_PROMPT: &str = "Введите стоимость абонемента (например, «5000» или «60 EUR») или «-», если не хотите указывать";
+const PRICE_FORMAT_HINT: &str = "Отправьте сумму, например «5000» или «4500,50 RUB», или «-»";
+
+/// `-` skips the price; `None` means the text is not a valid price.
+fn parse_optional_price(text: &str, default_currency: &str) -> Option<Option<PackPrice>> {
+    match text.trim() {
+        "-" => Some(None),
+        text => utils::parse_price(text, default_currency)
+            .map(|(amount, currency)| Some(PackPrice { amount, currency })),
+    }
+}
+
+/// `-` means "no expiry date"; anything else must be a `ДД.ММ.ГГГГ` date.
+fn parse_valid_until(text: &str) -> Option<Option<NaiveDate>> {
+    if text.trim() == "-" {
+        return Some(None);
+    }
+    utils::parse_date(text).map(Some)
+}
+
+fn format_validity(class: &Class) -> String {
+    match class.valid_until_date() {
+        Some(valid_until) if class.is_expired(utils::today()) => {
+            format!("истёк {}", utils::format_date(valid_until))
+        }
+        Some(valid_until) => format!("до {}", utils::format_date(valid_until)),
+        None => "бессрочно".to_string(),
+    }
+}
+
 pub async fn receive_quantity_handler(
     bot: Bot,
     msg: Message,
-    dialogue: Dialogue<State, InMemStorage<State>>,
+    dialogue: BotDialogue,
     di: Arc<DI>,
     class_id: i64,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -91,6 +399,45 @@ pub async fn receive_quantity_handler(
     Ok(())
 }
 
+pub async fn receive_new_name_handler(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    di: Arc<DI>,
+    class_id: i64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match msg.text().map(str::trim) {
+        Some(text) if !text.is_empty() => {
+            match rename_class(di.db_pool.clone(), class_id, msg.chat.id.0, text.into()).await {
+                Ok(class) => {
+                    dialogue.exit().await?;
+                    bot.send_message(
+                        msg.chat.id,
+                        format!(
+                            "✅ Занятие переименовано в <b>{name}</b>",
+                            name = html::escape(&class.name)
+                        ),
+                    )
+                    .parse_mode(ParseMode::Html)
+                    .await?;
+                }
+                Err(err) if err.is::<DuplicateClassNameError>() => {
+                    // Keep the dialogue so the user can try another name right away.
+                    bot.send_message(msg.chat.id, err.to_string()).await?;
+                }
+                Err(err) => {
+                    dialogue.exit().await?;
+                    bot.send_message(msg.chat.id, err.to_string()).await?;
+                }
+            }
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Отправьте текст").await?;
+        }
+    }
+    Ok(())
+}
+
 pub async fn classes_menu_handler(
     bot: Bot,
     msg: Message,
@@ -108,6 +455,9 @@ pub async fn classes_menu_handler(
         MainMenuButton {
             text: MenuAction::ClassesDeductionHistory.label().to_string(),
         },
+        MainMenuButton {
+            text: MenuAction::ClassSpending.label().to_string(),
+        },
         MainMenuButton {
             text: MenuAction::MainMenu.label().to_string(),
         },
@@ -130,6 +480,15 @@ pub async fn class_settings_handler(
         MainMenuButton {
             text: MenuAction::UpdateQuantity.label().to_string(),
         },
+        MainMenuButton {
+            text: MenuAction::EditClass.label().to_string(),
+        },
+        MainMenuButton {
+            text: MenuAction::DeleteClass.label().to_string(),
+        },
+        MainMenuButton {
+            text: MenuAction::ArchivedClasses.label().to_string(),
+        },
         MainMenuButton {
             text: MenuAction::MainMenu.label().to_string(),
         },
@@ -156,7 +515,15 @@ pub async fn list_classes_handler(
 
     let formatted_classes: Vec<String> = classes
         .iter()
-        .map(|c| format!("{} ({})", html::escape(&c.name), c.quantity))
+        .map(|c| match c.valid_until {
+            Some(_) => format!(
+                "{} ({}) — {}",
+                html::escape(&c.name),
+                c.quantity,
+                format_validity(c)
+            ),
+            None => format!("{} ({})", html::escape(&c.name), c.quantity),
+        })
         .collect();
     let output = formatted_classes.join("\n");
     bot.send_message(msg.chat.id, output)
@@ -192,27 +559,196 @@ pub async fn deduct_class_callback_handler(
     q: &CallbackQuery,
     di: Arc<DI>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
     if let Some(ref data) = q.data
         && let Some((_, id)) = data.split_once(':')
     {
         let class_id: i64 = id.parse()?;
         let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+        let today = user_today(&di, telegram_user_id).await?;
+        bot.edit_message_text(message.chat.id, message.id, "Когда было занятие?")
+            .reply_markup(keyboards::make_deduction_date_inline_keyboard(
+                class_id, today,
+            ))
+            .await?;
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn deduct_calendar_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, args)) = data.split_once(':')
+        && let Some((id, month)) = args.split_once(':')
+        && let Some(month) = utils::parse_month(month)
+    {
+        let class_id: i64 = id.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+        let today = user_today(&di, telegram_user_id).await?;
+        let keyboard = keyboards::make_calendar_inline_keyboard(
+            month,
+            &format!("deduct_on:{}:", class_id),
+            &format!("deduct_calendar:{}:", class_id),
+            Some(today),
+        );
+        bot.edit_message_text(message.chat.id, message.id, "Выберите дату занятия")
+            .reply_markup(keyboard)
+            .await?;
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn deduct_on_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if let Some(ref data) = q.data
+        && let Some((_, args)) = data.split_once(':')
+        && let Some((id, date)) = args.split_once(':')
+    {
+        let class_id: i64 = id.parse()?;
+        let deducted_on: NaiveDate = date.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
         bot.answer_callback_query(q.id.clone()).await?;
 
-        let output = match deduct_class(di.db_pool.clone(), class_id, telegram_user_id).await {
+        let Some(message) = q.regular_message() else {
+            return Ok(());
+        };
+
+        match deduct_class(
+            di.db_pool.clone(),
+            class_id,
+            telegram_user_id,
+            deducted_on,
+            di.config.classes.expired_deduction_policy,
+        )
+        .await
+        {
+            Ok(deduction) => {
+                let today = user_today(&di, telegram_user_id).await?;
+                show_deduction_result(&bot, message, &deduction, deducted_on, today).await?;
+            }
+            Err(err) => {
+                bot.edit_message_text(message.chat.id, message.id, err.to_string())
+                    .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces `message` with the outcome of a deduction, with undo and note
+/// buttons, and warns separately when the balance has just run low. `today`
+/// is the user's, to tell backdated deductions apart.
+pub async fn show_deduction_result(
+    bot: &Bot,
+    message: &Message,
+    deduction: &ClassDeduction,
+    deducted_on: NaiveDate,
+    today: NaiveDate,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let class = &deduction.class;
+    let mut output = format!(
+        "✅ Занятие {name} успешно списано! Остаток: {quantity}",
+        name = class.name,
+        quantity = class.quantity
+    );
+    if deducted_on != today {
+        output.push_str(&format!(
+            "\nДата занятия: {}",
+            utils::format_date(deducted_on)
+        ));
+    }
+    if class.is_expired(deducted_on) {
+        output.push_str(&format!(
+            "\n\n⚠️ Срок действия абонемента {}",
+            format_validity(class)
+        ));
+    }
+    bot.edit_message_text(message.chat.id, message.id, output)
+        .reply_markup(keyboards::make_deduction_done_inline_keyboard(
+            deduction.class_deduction_history_id,
+        ))
+        .await?;
+
+    if deduction.low_balance_reached {
+        let alert = format!(
+            "⚠️ <b>Заканчиваются занятия: {name}</b>\nОсталось: {quantity}",
+            name = html::escape(&class.name),
+            quantity = class.quantity
+        );
+        bot.send_message(message.chat.id, alert)
+            .reply_markup(keyboards::make_low_balance_inline_keyboard(class.class_id))
+            .parse_mode(ParseMode::Html)
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn undo_deduction_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_deduction_history_id: i64 = id.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+        let window = Duration::from_secs(di.config.classes.undo_deduction_window_secs);
+
+        match undo_class_deduction(
+            di.db_pool.clone(),
+            class_deduction_history_id,
+            telegram_user_id,
+            window,
+        )
+        .await
+        {
             Ok(class) => {
-                format!(
-                    "✅ Занятие {name} успешно списано! Остаток: {quantity}",
+                bot.answer_callback_query(q.id.clone()).await?;
+                let output = format!(
+                    "↩️ Списание занятия {name} отменено. Остаток: {quantity}",
                     name = class.name,
                     quantity = class.quantity
-                )
+                );
+                if let Some(message) = q.regular_message() {
+                    bot.edit_message_text(message.chat.id, message.id, output)
+                        .await?;
+                }
+            }
+            Err(err) => {
+                bot.answer_callback_query(q.id.clone())
+                    .text(err.to_string())
+                    .show_alert(true)
+                    .await?;
+                if let Some(message) = q.regular_message() {
+                    bot.edit_message_reply_markup(message.chat.id, message.id)
+                        .await?;
+                }
             }
-            Err(err) => err.to_string(),
-        };
-
-        if let Some(message) = q.regular_message() {
-            bot.edit_message_text(message.chat.id, message.id, output)
-                .await?;
         }
     }
 
@@ -237,7 +773,7 @@ pub async fn update_quantity_handler(
 pub async fn update_class_quantity_callback_handler(
     bot: Bot,
     q: &CallbackQuery,
-    dialogue: &Dialogue<State, InMemStorage<State>>,
+    dialogue: &BotDialogue,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     bot.answer_callback_query(q.id.clone()).await?;
 
@@ -298,18 +834,7 @@ pub async fn list_classes_deduction_history_callback_handler(
     {
         let class_id: i64 = id.parse()?;
         let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
-
-        let histories =
-            get_class_deduction_histories(di.db_pool.clone(), class_id, telegram_user_id).await?;
-        if histories.is_empty() {
-            bot.edit_message_text(message.chat.id, message.id, "История списаний пуста")
-                .await?;
-            return Ok(());
-        }
-
-        let formatted_histories: Vec<String> = histories.iter().map(|s| s.to_string()).collect();
-        let output = formatted_histories.join("\n");
-        bot.edit_message_text(message.chat.id, message.id, output)
+        show_deduction_history_page(&bot, message, &di, telegram_user_id, class_id, None, 0)
             .await?;
     } else {
         bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
@@ -317,3 +842,767 @@ pub async fn list_classes_deduction_history_callback_handler(
     }
     Ok(())
 }
+
+/// Handles `history_page:{class_id}:{YYYY-MM|all}:{page}`.
+pub async fn deduction_history_page_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some((class_id, month, page)) = q.data.as_deref().and_then(parse_history_page_data) {
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+        show_deduction_history_page(&bot, message, &di, telegram_user_id, class_id, month, page)
+            .await?;
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+/// Parses `{prefix}:{class_id}:{YYYY-MM|all}:{page}`.
+fn parse_history_page_data(data: &str) -> Option<(i64, Option<NaiveDate>, u32)> {
+    let mut parts = data.split(':').skip(1);
+    let class_id: i64 = parts.next()?.parse().ok()?;
+    let month = match parts.next()? {
+        "all" => None,
+        month => Some(utils::parse_month(month)?),
+    };
+    let page: u32 = parts.next()?.parse().ok()?;
+    Some((class_id, month, page))
+}
+
+/// Swaps the navigation under a history page for one button per deduction.
+pub async fn deduction_history_notes_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((class_id, month, page)) = parse_history_page_data(data)
+        && let Some((_, args)) = data.split_once(':')
+    {
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+        let history = get_class_deduction_history_page(
+            di.db_pool.clone(),
+            class_id,
+            telegram_user_id,
+            month,
+            page,
+        )
+        .await?;
+        let utc_offset = get_user_utc_offset(di.db_pool.clone(), telegram_user_id).await?;
+        let keyboard = keyboards::make_deduction_notes_inline_keyboard(
+            &history.histories,
+            utc_offset,
+            format!("history_page:{}", args),
+        );
+        bot.edit_message_reply_markup(message.chat.id, message.id)
+            .reply_markup(keyboard)
+            .await?;
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+async fn show_deduction_history_page(
+    bot: &Bot,
+    message: &Message,
+    di: &DI,
+    telegram_user_id: i64,
+    class_id: i64,
+    month: Option<NaiveDate>,
+    page: u32,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let class = match get_class_by_id(di.db_pool.clone(), class_id, telegram_user_id).await {
+        Ok(class) => class,
+        Err(err) => {
+            bot.edit_message_text(message.chat.id, message.id, err.to_string())
+                .await?;
+            return Ok(());
+        }
+    };
+    let history = get_class_deduction_history_page(
+        di.db_pool.clone(),
+        class_id,
+        telegram_user_id,
+        month,
+        page,
+    )
+    .await?;
+    let utc_offset = get_user_utc_offset(di.db_pool.clone(), telegram_user_id).await?;
+    // Freezes are few, so they are shown once under the first unfiltered page.
+    let freezes = if month.is_none() && page == 0 {
+        get_class_freezes(di.db_pool.clone(), class_id, telegram_user_id).await?
+    } else {
+        vec![]
+    };
+
+    let period = match month {
+        Some(month) => format!(
+            "{} {}",
+            utils::get_russian_month_name(month.month()),
+            month.year()
+        ),
+        None => "всё время".to_string(),
+    };
+    let mut output = format!(
+        "<b>{name}</b>: история списаний за {period}",
+        name = html::escape(&class.name)
+    );
+    if history.page_count() > 1 {
+        output.push_str(&format!(
+            " (стр. {} из {})",
+            history.page + 1,
+            history.page_count()
+        ));
+    }
+    output.push('\n');
+
+    if history.histories.is_empty() {
+        output.push_str("\nСписаний нет");
+    }
+    for h in &history.histories {
+        output.push_str(&format!("\n{}", h.describe(utc_offset)));
+        if let Some(ref note) = h.note {
+            output.push_str(&format!("\n    📝 {}", html::escape(note)));
+        }
+    }
+    if !freezes.is_empty() {
+        output.push_str("\n\nЗаморозки:");
+        for f in &freezes {
+            output.push_str(&format!("\n{}", f));
+        }
+    }
+
+    let today = utils::today_in(utc_offset);
+    let current_month = today.with_day(1).unwrap_or(today);
+    let keyboard = keyboards::make_deduction_history_inline_keyboard(
+        class_id,
+        month,
+        history.page,
+        history.has_next(),
+        !history.histories.is_empty(),
+        current_month,
+    );
+    bot.edit_message_text(message.chat.id, message.id, output)
+        .reply_markup(keyboard)
+        .parse_mode(ParseMode::Html)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_class_handler(
+    bot: Bot,
+    msg: Message,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let classes = get_classes_by_user_id(di.db_pool.clone(), msg.chat.id.0).await?;
+    if classes.is_empty() {
+        bot.send_message(msg.chat.id, "У вас нет добавленных занятий")
+            .parse_mode(ParseMode::Html)
+            .await?;
+        return Ok(());
+    }
+
+    let keyboard = keyboards::make_class_list_inline_keyboard(classes, 2, "delete_class:");
+    let output = "Выберите занятие для удаления";
+    bot.send_message(msg.chat.id, output)
+        .reply_markup(keyboard)
+        .parse_mode(ParseMode::Html)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_class_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_id: i64 = id.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+
+        match get_class_by_id(di.db_pool.clone(), class_id, telegram_user_id).await {
+            Ok(class) => {
+                let mut output = format!(
+                    "Удалить занятие <b>{name}</b>? Вся история списаний будет удалена.",
+                    name = html::escape(&class.name),
+                );
+                let is_archived = class.archived_at.is_some();
+                if !is_archived {
+                    output.push_str(
+                        "\n\nЕсли хотите сохранить историю, отправьте занятие в архив: \
+                         оно пропадёт из списков, но его можно будет восстановить.",
+                    );
+                }
+                let keyboard = keyboards::make_delete_class_confirmation_inline_keyboard(
+                    class_id,
+                    !is_archived,
+                );
+                bot.edit_message_text(message.chat.id, message.id, output)
+                    .reply_markup(keyboard)
+                    .parse_mode(ParseMode::Html)
+                    .await?;
+            }
+            Err(err) => {
+                bot.edit_message_text(message.chat.id, message.id, err.to_string())
+                    .await?;
+            }
+        }
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn delete_class_confirm_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_id: i64 = id.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+
+        let output = match delete_class(di.db_pool.clone(), class_id, telegram_user_id).await {
+            Ok(class) => format!(
+                "🗑 Занятие <b>{name}</b> удалено",
+                name = html::escape(&class.name)
+            ),
+            Err(err) => html::escape(&err.to_string()),
+        };
+        bot.edit_message_text(message.chat.id, message.id, output)
+            .parse_mode(ParseMode::Html)
+            .await?;
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn archive_class_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_id: i64 = id.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+
+        let output = match archive_class(di.db_pool.clone(), class_id, telegram_user_id).await {
+            Ok(class) => format!(
+                "📦 Занятие <b>{name}</b> перенесено в архив",
+                name = html::escape(&class.name)
+            ),
+            Err(err) => html::escape(&err.to_string()),
+        };
+        bot.edit_message_text(message.chat.id, message.id, output)
+            .parse_mode(ParseMode::Html)
+            .await?;
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn class_action_cancel_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(message) = q.regular_message() {
+        bot.edit_message_text(message.chat.id, message.id, "Действие отменено")
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn archived_classes_handler(
+    bot: Bot,
+    msg: Message,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let classes = get_archived_classes_by_user_id(di.db_pool.clone(), msg.chat.id.0).await?;
+    if classes.is_empty() {
+        bot.send_message(msg.chat.id, "Архив занятий пуст")
+            .parse_mode(ParseMode::Html)
+            .await?;
+        return Ok(());
+    }
+
+    let keyboard = keyboards::make_class_list_inline_keyboard(classes, 2, "archived_class:");
+    let output = "Выберите занятие из архива";
+    bot.send_message(msg.chat.id, output)
+        .reply_markup(keyboard)
+        .parse_mode(ParseMode::Html)
+        .await?;
+    Ok(())
+}
+
+pub async fn archived_class_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_id: i64 = id.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+
+        match get_class_by_id(di.db_pool.clone(), class_id, telegram_user_id).await {
+            Ok(class) => {
+                let output = format!(
+                    "📦 <b>{name}</b> (остаток: {quantity})",
+                    name = html::escape(&class.name),
+                    quantity = class.quantity
+                );
+                bot.edit_message_text(message.chat.id, message.id, output)
+                    .reply_markup(keyboards::make_archived_class_inline_keyboard(class_id))
+                    .parse_mode(ParseMode::Html)
+                    .await?;
+            }
+            Err(err) => {
+                bot.edit_message_text(message.chat.id, message.id, err.to_string())
+                    .await?;
+            }
+        }
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn restore_class_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_id: i64 = id.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+
+        let output = match restore_class(di.db_pool.clone(), class_id, telegram_user_id).await {
+            Ok(class) => format!(
+                "♻️ Занятие <b>{name}</b> восстановлено. Остаток: {quantity}",
+                name = html::escape(&class.name),
+                quantity = class.quantity
+            ),
+            Err(err) => html::escape(&err.to_string()),
+        };
+        bot.edit_message_text(message.chat.id, message.id, output)
+            .parse_mode(ParseMode::Html)
+            .await?;
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn edit_class_handler(
+    bot: Bot,
+    msg: Message,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let classes = get_classes_by_user_id(di.db_pool.clone(), msg.chat.id.0).await?;
+    if classes.is_empty() {
+        bot.send_message(msg.chat.id, "У вас нет добавленных занятий")
+            .parse_mode(ParseMode::Html)
+            .await?;
+        return Ok(());
+    }
+
+    let keyboard = keyboards::make_class_list_inline_keyboard(classes, 2, "edit_class:");
+    let output = "Выберите занятие для изменения";
+    bot.send_message(msg.chat.id, output)
+        .reply_markup(keyboard)
+        .parse_mode(ParseMode::Html)
+        .await?;
+    Ok(())
+}
+
+pub async fn edit_class_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_id: i64 = id.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+
+        match get_class_by_id(di.db_pool.clone(), class_id, telegram_user_id).await {
+            Ok(class) => {
+                let packs = get_class_packs(di.db_pool.clone(), class_id, telegram_user_id)
+                    .await
+                    .unwrap_or_default();
+                let mut output = format!(
+                    "<b>{name}</b> (остаток: {quantity}, {validity})",
+                    name = html::escape(&class.name),
+                    quantity = class.quantity,
+                    validity = format_validity(&class)
+                );
+                if !packs.is_empty() {
+                    output.push_str("\n\nАбонементы:");
+                    for pack in packs {
+                        output.push_str(&format!("\n• {pack}"));
+                    }
+                }
+                output.push_str("\n\nЧто изменить?");
+                bot.edit_message_text(message.chat.id, message.id, output)
+                    .reply_markup(keyboards::make_edit_class_inline_keyboard(class_id))
+                    .parse_mode(ParseMode::Html)
+                    .await?;
+            }
+            Err(err) => {
+                bot.edit_message_text(message.chat.id, message.id, err.to_string())
+                    .await?;
+            }
+        }
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn rename_class_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    dialogue: &BotDialogue,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_id: i64 = id.parse()?;
+        dialogue
+            .update(State::RenamingClassReceiveName { class_id })
+            .await?;
+
+        if let Some(message) = q.regular_message() {
+            bot.edit_message_text(message.chat.id, message.id, "Введите новое название:")
+                .await?;
+        }
+    } else {
+        bot.send_message(q.from.id, "Ошибка").await?;
+    }
+
+    Ok(())
+}
+
+pub async fn set_valid_until_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    dialogue: &BotDialogue,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_id: i64 = id.parse()?;
+        dialogue
+            .update(State::UpdatingClassReceiveValidUntil { class_id })
+            .await?;
+
+        if let Some(message) = q.regular_message() {
+            bot.edit_message_text(
+                message.chat.id,
+                message.id,
+                "Введите дату окончания абонемента (ДД.ММ.ГГГГ) или «-», чтобы убрать срок:",
+            )
+            .await?;
+        }
+    } else {
+        bot.send_message(q.from.id, "Ошибка").await?;
+    }
+
+    Ok(())
+}
+
+pub async fn freeze_class_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    dialogue: &BotDialogue,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_id: i64 = id.parse()?;
+        dialogue
+            .update(State::FreezingClassReceiveDays { class_id })
+            .await?;
+
+        if let Some(message) = q.regular_message() {
+            bot.edit_message_text(
+                message.chat.id,
+                message.id,
+                "На сколько дней заморозить абонемент? Срок действия сдвинется на столько же.",
+            )
+            .await?;
+        }
+    } else {
+        bot.send_message(q.from.id, "Ошибка").await?;
+    }
+
+    Ok(())
+}
+
+pub async fn set_low_balance_threshold_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    dialogue: &BotDialogue,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_id: i64 = id.parse()?;
+        dialogue
+            .update(State::UpdatingClassReceiveLowBalanceThreshold { class_id })
+            .await?;
+
+        if let Some(message) = q.regular_message() {
+            bot.edit_message_text(
+                message.chat.id,
+                message.id,
+                "При каком остатке напоминать о пополнении? Отправьте число или «-», чтобы выключить:",
+            )
+            .await?;
+        }
+    } else {
+        bot.send_message(q.from.id, "Ошибка").await?;
+    }
+
+    Ok(())
+}
+
+pub async fn top_up_class_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    dialogue: &BotDialogue,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_id: i64 = id.parse()?;
+        dialogue
+            .update(State::ToppingUpClassReceiveQuantity { class_id })
+            .await?;
+
+        if let Some(message) = q.regular_message() {
+            bot.edit_message_text(
+                message.chat.id,
+                message.id,
+                "Сколько занятий в новом абонементе?",
+            )
+            .await?;
+        }
+    } else {
+        bot.send_message(q.from.id, "Ошибка").await?;
+    }
+
+    Ok(())
+}
+
+pub async fn class_spending_handler(
+    bot: Bot,
+    msg: Message,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let report = get_spending_report(di.db_pool.clone(), msg.chat.id.0).await?;
+    if report.classes.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "Цены абонементов не указаны. Укажите стоимость при добавлении или пополнении занятия.",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mut output = "💰 <b>Расходы на занятия</b>\n".to_string();
+    for class in &report.classes {
+        output.push_str(&format!("\n<b>{}</b>\n", html::escape(&class.name)));
+        match class.cost_per_class() {
+            Some(cost) => output.push_str(&format!(
+                "Посещено: {}, в среднем {} {} за занятие\n",
+                class.attended,
+                utils::format_money(cost),
+                class.currency
+            )),
+            None => output.push_str("Посещений пока нет\n"),
+        }
+        output.push_str(&format!(
+            "Оплачено вперёд: {} {}\n",
+            utils::format_money(class.prepaid_value),
+            class.currency
+        ));
+    }
+
+    if !report.months.is_empty() {
+        output.push_str("\n<b>По месяцам</b>\n");
+        for month in &report.months {
+            let label = match utils::parse_month(&month.month) {
+                Some(date) => format!(
+                    "{} {}",
+                    utils::get_russian_month_name(date.month()),
+                    date.year()
+                ),
+                None => month.month.clone(),
+            };
+            output.push_str(&format!(
+                "{}: {} {}\n",
+                label,
+                utils::format_money(month.total),
+                month.currency
+            ));
+        }
+    }
+
+    bot.send_message(msg.chat.id, output)
+        .parse_mode(ParseMode::Html)
+        .await?;
+    Ok(())
+}
+
+const MAX_NOTE_LENGTH: usize = 500;
+
+pub async fn deduction_note_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    dialogue: &BotDialogue,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_deduction_history_id: i64 = id.parse()?;
+        dialogue
+            .update(State::AddingDeductionNoteReceiveText {
+                class_deduction_history_id,
+            })
+            .await?;
+        // A new message keeps the deduction result and its undo button in place.
+        bot.send_message(
+            q.from.id,
+            "Напишите заметку к занятию (кто вёл, что проходили, отработка) или «-», чтобы удалить её",
+        )
+        .await?;
+    } else {
+        bot.send_message(q.from.id, "Ошибка").await?;
+    }
+
+    Ok(())
+}
+
+pub async fn receive_deduction_note_handler(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    di: Arc<DI>,
+    class_deduction_history_id: i64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match msg.text().map(str::trim) {
+        Some(text) if text.chars().count() > MAX_NOTE_LENGTH => {
+            bot.send_message(
+                msg.chat.id,
+                format!("Заметка слишком длинная: не больше {MAX_NOTE_LENGTH} символов"),
+            )
+            .await?;
+        }
+        Some(text) if !text.is_empty() => {
+            let note = (text != "-").then(|| text.to_string());
+            let utc_offset = get_user_utc_offset(di.db_pool.clone(), msg.chat.id.0).await?;
+            let output = match set_deduction_note(
+                di.db_pool.clone(),
+                class_deduction_history_id,
+                msg.chat.id.0,
+                note,
+            )
+            .await
+            {
+                Ok(history) if history.note.is_some() => {
+                    format!(
+                        "📝 Заметка к занятию {} сохранена",
+                        history.describe(utc_offset)
+                    )
+                }
+                Ok(history) => {
+                    format!("Заметка к занятию {} удалена", history.describe(utc_offset))
+                }
+                Err(err) => err.to_string(),
+            };
+            dialogue.exit().await?;
+            bot.send_message(msg.chat.id, output).await?;
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Отправьте текст").await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use std::sync::Arc;
+
+use chrono::Datelike;
+use teloxide::{
+    payloads::{AnswerCallbackQuerySetters, EditMessageTextSetters, SendMessageSetters},
+    prelude::*,
+    types::ParseMode,
+    utils::html,
+};
+
+use crate::{
+    bot::DI,
+    errors::{AttendanceAlreadyAnsweredError, AttendanceNotFoundError},
+    handlers::{class::show_deduction_result, daily_practice_log::user_today},
+    keyboards,
+    repositories::class_schedule::ClassSchedule,
+    services::{
+        class::get_class_by_id,
+        class_schedule::{
+            AttendanceAnswer, add_class_schedule, answer_attendance, get_class_schedules,
+            remove_class_schedule,
+        },
+    },
+    state::{BotDialogue, State},
+    utils,
+};
+
+fn format_schedule(class_name: &str, schedules: &[ClassSchedule]) -> String {
+    let mut output = format!("🗓 <b>{}</b>: расписание\n", html::escape(class_name));
+    if schedules.is_empty() {
+        output.push_str("\nРасписание не задано");
+    }
+    for schedule in schedules {
+        output.push_str(&format!("\n{}", schedule));
+    }
+    output.push_str("\n\nПосле каждого занятия бот спросит, были ли вы на нём.");
+    output
+}
+
+pub async fn class_schedule_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_id: i64 = id.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+        show_class_schedule(&bot, message, &di, class_id, telegram_user_id).await?;
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+async fn show_class_schedule(
+    bot: &Bot,
+    message: &Message,
+    di: &DI,
+    class_id: i64,
+    telegram_user_id: i64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let class = match get_class_by_id(di.db_pool.clone(), class_id, telegram_user_id).await {
+        Ok(class) => class,
+        Err(err) => {
+            bot.edit_message_text(message.chat.id, message.id, err.to_string())
+                .await?;
+            return Ok(());
+        }
+    };
+    let schedules = get_class_schedules(di.db_pool.clone(), class_id, telegram_user_id).await?;
+
+    bot.edit_message_text(
+        message.chat.id,
+        message.id,
+        format_schedule(&class.name, &schedules),
+    )
+    .reply_markup(keyboards::make_class_schedule_inline_keyboard(
+        class_id, &schedules,
+    ))
+    .parse_mode(ParseMode::Html)
+    .await?;
+    Ok(())
+}
+
+pub async fn schedule_add_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    dialogue: &BotDialogue,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_id: i64 = id.parse()?;
+        dialogue
+            .update(State::AddingClassScheduleReceiveSlot { class_id })
+            .await?;
+
+        if let Some(message) = q.regular_message() {
+            bot.edit_message_text(
+                message.chat.id,
+                message.id,
+                "Введите дни недели и время начала, например «пн, ср 19:30»:",
+            )
+            .await?;
+        }
+    } else {
+        bot.send_message(q.from.id, "Ошибка").await?;
+    }
+
+    Ok(())
+}
+
+pub async fn receive_schedule_slot_handler(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    di: Arc<DI>,
+    class_id: i64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match msg.text().and_then(utils::parse_schedule_slot) {
+        Some((weekdays, starts_at)) => {
+            let output = match add_class_schedule(
+                di.db_pool.clone(),
+                class_id,
+                msg.chat.id.0,
+                weekdays,
+                starts_at,
+            )
+            .await
+            {
+                Ok(schedules) => {
+                    let class =
+                        get_class_by_id(di.db_pool.clone(), class_id, msg.chat.id.0).await?;
+                    format_schedule(&class.name, &schedules)
+                }
+                Err(err) => html::escape(&err.to_string()),
+            };
+            dialogue.exit().await?;
+            bot.send_message(msg.chat.id, output)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        None => {
+            bot.send_message(
+                msg.chat.id,
+                "Не получилось разобрать. Пример: «вт, чт 18:00»",
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn schedule_remove_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let class_schedule_id: i64 = id.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+        match remove_class_schedule(di.db_pool.clone(), class_schedule_id, telegram_user_id).await {
+            Ok(class_id) => {
+                show_class_schedule(&bot, message, &di, class_id, telegram_user_id).await?;
+            }
+            Err(err) => {
+                bot.edit_message_text(message.chat.id, message.id, err.to_string())
+                    .await?;
+            }
+        }
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+/// Handles the "Был(а)" (`attended:`) and "Пропустил(а)" (`skipped:`)
+/// answers to an attendance prompt.
+pub async fn attendance_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let Some(message) = q.regular_message() else {
+        bot.answer_callback_query(q.id.clone()).await?;
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((answer, id)) = data.split_once(':')
+    {
+        let class_attendance_id: i64 = id.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+
+        match answer_attendance(
+            di.db_pool.clone(),
+            class_attendance_id,
+            telegram_user_id,
+            answer == "attended",
+            di.config.classes.expired_deduction_policy,
+        )
+        .await
+        {
+            Ok(AttendanceAnswer::Attended {
+                deduction,
+                scheduled_for,
+            }) => {
+                bot.answer_callback_query(q.id.clone()).await?;
+                let today = user_today(&di, telegram_user_id).await?;
+                show_deduction_result(&bot, message, &deduction, scheduled_for.date(), today)
+                    .await?;
+            }
+            Ok(AttendanceAnswer::Skipped {
+                class,
+                scheduled_for,
+            }) => {
+                bot.answer_callback_query(q.id.clone()).await?;
+                let output = format!(
+                    "Пропуск отмечен: <b>{name}</b>, {weekday} {date} в {time}",
+                    name = html::escape(&class.name),
+                    weekday = utils::get_russian_weekday_name(scheduled_for.weekday(), true),
+                    date = utils::format_date(scheduled_for.date()),
+                    time = scheduled_for.format("%H:%M")
+                );
+                bot.edit_message_text(message.chat.id, message.id, output)
+                    .parse_mode(ParseMode::Html)
+                    .await?;
+            }
+            Err(err) => {
+                bot.answer_callback_query(q.id.clone())
+                    .text(err.to_string())
+                    .show_alert(true)
+                    .await?;
+                // A failed deduction can be retried, e.g. after a top-up, so
+                // the buttons only go away once there is nothing to answer.
+                if err.is::<AttendanceAlreadyAnsweredError>() || err.is::<AttendanceNotFoundError>()
+                {
+                    bot.edit_message_reply_markup(message.chat.id, message.id)
+                        .await?;
+                }
+            }
+        }
+    } else {
+        bot.answer_callback_query(q.id.clone()).await?;
+    }
+
+    Ok(())
+}
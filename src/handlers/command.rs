@@ -1,20 +1,28 @@
 use std::{error::Error, sync::Arc};
 use teloxide::{
-    dispatching::dialogue::{InMemStorage, Storage},
     payloads::SendMessageSetters,
     prelude::*,
-    utils::command::BotCommands,
+    types::ParseMode,
+    utils::{command::BotCommands, html},
 };
 
 use crate::{
     bot::DI,
     commands::{Command, MenuAction},
     keyboards::{self, MainMenuButton},
-    services::user::*,
-    state::State,
+    services::{
+        daily_practice_log::search_practice_notes,
+        job::{get_user_utc_offset, set_user_utc_offset},
+        user::*,
+    },
+    state::DialogueStorage,
+    utils,
 };
 use teloxide::{Bot, types::Message};
 
+/// How many notes `/search` shows at most.
+const SEARCH_RESULTS_LIMIT: usize = 20;
+
 pub async fn start_handler(
     bot: Bot,
     msg: Message,
@@ -45,6 +53,9 @@ pub async fn main_menu_handler(bot: Bot, msg: Message) -> Result<(), Box<dyn Err
         MainMenuButton {
             text: MenuAction::DailyPracticeLog.label().to_string(),
         },
+        MainMenuButton {
+            text: MenuAction::Export.label().to_string(),
+        },
     ];
     let keyboard = keyboards::make_main_menu_keyboard(buttons, 2);
     bot.send_message(msg.chat.id, "Переход в главное меню")
@@ -56,10 +67,107 @@ pub async fn main_menu_handler(bot: Bot, msg: Message) -> Result<(), Box<dyn Err
 pub async fn cancel_handler(
     bot: Bot,
     msg: Message,
-    storage: Arc<InMemStorage<State>>,
+    storage: Arc<DialogueStorage>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let chat_id = msg.chat.id;
     let _ = storage.remove_dialogue(chat_id).await;
     bot.send_message(chat_id, "Отмена операции").await?;
     Ok(())
 }
+
+pub async fn timezone_handler(
+    bot: Bot,
+    msg: Message,
+    offset: String,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let chat_id = msg.chat.id;
+    if offset.trim().is_empty() {
+        let current = get_user_utc_offset(di.db_pool.clone(), chat_id.0).await?;
+        bot.send_message(
+            chat_id,
+            format!(
+                "Ваш часовой пояс: {}\nЧтобы изменить, отправьте, например, /timezone +3",
+                utils::format_utc_offset(current)
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let Some(utc_offset) = utils::parse_utc_offset(&offset) else {
+        bot.send_message(
+            chat_id,
+            "Не удалось распознать часовой пояс. Пример: /timezone +3 или /timezone +05:30",
+        )
+        .await?;
+        return Ok(());
+    };
+
+    set_user_utc_offset(di.db_pool.clone(), chat_id.0, utc_offset).await?;
+    bot.send_message(
+        chat_id,
+        format!(
+            "Часовой пояс изменён: {}",
+            utils::format_utc_offset(utc_offset)
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
+pub async fn search_handler(
+    bot: Bot,
+    msg: Message,
+    query: String,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let query = query.trim();
+    if query.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "Укажите, что искать в заметках к практике, например: /search гаммы",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let entries = search_practice_notes(
+        di.db_pool.clone(),
+        msg.chat.id.0,
+        query,
+        SEARCH_RESULTS_LIMIT,
+    )
+    .await?;
+    if entries.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "В заметках к практике ничего не нашлось по запросу «{}»",
+                query
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let utc_offset = get_user_utc_offset(di.db_pool.clone(), msg.chat.id.0).await?;
+    let mut output = format!("🔎 <b>{}</b>\n", html::escape(query));
+    for entry in &entries {
+        output.push_str(&format!(
+            "\n{}\n    📝 {}",
+            entry.describe(utc_offset),
+            html::escape(entry.note.as_deref().unwrap_or_default())
+        ));
+    }
+    if entries.len() == SEARCH_RESULTS_LIMIT {
+        output.push_str(&format!(
+            "\n\nПоказаны последние {} совпадений, уточните запрос",
+            SEARCH_RESULTS_LIMIT
+        ));
+    }
+    bot.send_message(msg.chat.id, output)
+        .parse_mode(ParseMode::Html)
+        .await?;
+    Ok(())
+}
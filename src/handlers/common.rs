@@ -1,18 +1,19 @@
 use std::sync::Arc;
-use teloxide::{
-    dispatching::dialogue::InMemStorage, payloads::AnswerCallbackQuerySetters, prelude::*,
-};
+use teloxide::{payloads::AnswerCallbackQuerySetters, prelude::*};
 
 use crate::{
     bot::DI,
     commands::MenuAction,
-    handlers::{class::*, command::main_menu_handler, daily_practice_log::*},
-    state::State,
+    handlers::{
+        class::*, class_schedule::*, command::main_menu_handler, daily_practice_log::*, export::*,
+        practice_chart::*, practice_session::*,
+    },
+    state::{BotDialogue, State},
 };
 
 pub async fn idle_message_handler(
     bot: Bot,
-    dialogue: Dialogue<State, InMemStorage<State>>,
+    dialogue: BotDialogue,
     msg: Message,
     di: Arc<DI>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -40,22 +41,51 @@ pub async fn idle_message_handler(
             Some(MenuAction::UpdateQuantity) => {
                 update_quantity_handler(bot, msg, di).await?;
             }
+            Some(MenuAction::EditClass) => {
+                edit_class_handler(bot, msg, di).await?;
+            }
+            Some(MenuAction::DeleteClass) => {
+                delete_class_handler(bot, msg, di).await?;
+            }
+            Some(MenuAction::ArchivedClasses) => {
+                archived_classes_handler(bot, msg, di).await?;
+            }
+            Some(MenuAction::ClassSpending) => {
+                class_spending_handler(bot, msg, di).await?;
+            }
             Some(MenuAction::DailyPracticeLog) => {
                 daily_practice_log_menu_handler(bot, msg).await?;
             }
             Some(MenuAction::AddDailyPracticeEntry) => {
-                bot.send_message(
-                    msg.chat.id,
-                    "Будет добавлена запись о вашей практике за сегодня.\nВведите количество минут:",
-                )
-                .await?;
-                dialogue
-                    .update(State::AddingDailyPracticeReceiveMinutes)
-                    .await?;
+                prompt_practice_minutes(&bot, msg.chat.id, &dialogue, &di).await?;
+            }
+            Some(MenuAction::PracticeStopwatch) => {
+                practice_stopwatch_handler(bot, msg, di).await?;
             }
             Some(MenuAction::DailyPracticeLogHistory) => {
                 list_daily_practice_log_history_handler(bot, msg, di).await?;
             }
+            Some(MenuAction::PracticeCharts) => {
+                practice_charts_handler(bot, msg).await?;
+            }
+            Some(MenuAction::PracticeReminder) => {
+                practice_reminder_handler(bot, msg, di).await?;
+            }
+            Some(MenuAction::PracticeEntries) => {
+                practice_entries_handler(bot, msg, di).await?;
+            }
+            Some(MenuAction::PracticeCategories) => {
+                practice_categories_handler(bot, msg, di).await?;
+            }
+            Some(MenuAction::PracticeGoals) => {
+                practice_goals_handler(bot, msg, di).await?;
+            }
+            Some(MenuAction::PracticeDigests) => {
+                practice_digests_handler(bot, msg, di).await?;
+            }
+            Some(MenuAction::Export) => {
+                export_handler(bot, msg).await?;
+            }
             Some(MenuAction::MainMenu) => {
                 main_menu_handler(bot, msg).await?;
             }
@@ -69,7 +99,7 @@ pub async fn idle_message_handler(
 
 pub async fn idle_callback_handler(
     bot: Bot,
-    dialogue: Dialogue<State, InMemStorage<State>>,
+    dialogue: BotDialogue,
     q: CallbackQuery,
     di: Arc<DI>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -81,12 +111,145 @@ pub async fn idle_callback_handler(
         Some(("deduct_class", _)) => {
             deduct_class_callback_handler(bot.clone(), &q, di).await?;
         }
+        Some(("deduct_calendar", _)) => {
+            deduct_calendar_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("deduct_on", _)) => {
+            deduct_on_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("calendar_noop", _)) => {
+            bot.answer_callback_query(q.id.clone()).await?;
+        }
+        Some(("undo_deduction", _)) => {
+            undo_deduction_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("edit_class", _)) => {
+            edit_class_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("rename_class", _)) => {
+            rename_class_callback_handler(bot.clone(), &q, &dialogue).await?;
+        }
+        Some(("set_valid_until", _)) => {
+            set_valid_until_callback_handler(bot.clone(), &q, &dialogue).await?;
+        }
+        Some(("top_up", _)) => {
+            top_up_class_callback_handler(bot.clone(), &q, &dialogue).await?;
+        }
+        Some(("freeze_class", _)) => {
+            freeze_class_callback_handler(bot.clone(), &q, &dialogue).await?;
+        }
+        Some(("class_schedule", _)) => {
+            class_schedule_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("schedule_add", _)) => {
+            schedule_add_callback_handler(bot.clone(), &q, &dialogue).await?;
+        }
+        Some(("schedule_remove", _)) => {
+            schedule_remove_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("attended" | "skipped", _)) => {
+            attendance_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("set_low_balance", _)) => {
+            set_low_balance_threshold_callback_handler(bot.clone(), &q, &dialogue).await?;
+        }
         Some(("update_quantity", _)) => {
             update_class_quantity_callback_handler(bot.clone(), &q, &dialogue).await?;
         }
         Some(("class_deduction_history", _)) => {
             list_classes_deduction_history_callback_handler(bot.clone(), &q, di).await?;
         }
+        Some(("history_page", _)) => {
+            deduction_history_page_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("history_notes", _)) => {
+            deduction_history_notes_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("deduction_note", _)) => {
+            deduction_note_callback_handler(bot.clone(), &q, &dialogue).await?;
+        }
+        Some(("practice_month", _)) => {
+            practice_month_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("practice_add", _)) => {
+            practice_add_callback_handler(bot.clone(), &q, &dialogue, di).await?;
+        }
+        Some(("practice_snooze", _)) => {
+            practice_snooze_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("practice_reminder_set", _)) => {
+            practice_reminder_set_callback_handler(bot.clone(), &q, &dialogue).await?;
+        }
+        Some((
+            "stopwatch_start" | "stopwatch_pause" | "stopwatch_resume" | "stopwatch_stop"
+            | "stopwatch_refresh",
+            _,
+        )) => {
+            stopwatch_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("export", _)) => {
+            export_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("practice_chart", _)) => {
+            practice_chart_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("practice_day_calendar", _)) => {
+            practice_day_calendar_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("practice_day", _)) => {
+            practice_day_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("practice_note", _)) => {
+            practice_note_callback_handler(bot.clone(), &q, &dialogue).await?;
+        }
+        Some(("practice_entries", _)) => {
+            practice_entries_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("practice_entry", _)) => {
+            practice_entry_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("practice_entry_edit", _)) => {
+            practice_entry_edit_callback_handler(bot.clone(), &q, &dialogue).await?;
+        }
+        Some(("practice_entry_delete", _)) => {
+            practice_entry_delete_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("practice_entry_delete_confirm", _)) => {
+            practice_entry_delete_confirm_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("practice_category_add", _)) => {
+            practice_category_add_callback_handler(bot.clone(), &q, &dialogue).await?;
+        }
+        Some(("practice_category_delete", _)) => {
+            practice_category_delete_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("practice_goal", _)) => {
+            practice_goal_callback_handler(bot.clone(), &q, &dialogue).await?;
+        }
+        Some(("digest_toggle", _)) => {
+            digest_toggle_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("practice_reminder_off", _)) => {
+            practice_reminder_off_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("delete_class", _)) => {
+            delete_class_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("delete_class_confirm", _)) => {
+            delete_class_confirm_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("archive_class", _)) => {
+            archive_class_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("class_action_cancel", _)) => {
+            class_action_cancel_callback_handler(bot.clone(), &q).await?;
+        }
+        Some(("archived_class", _)) => {
+            archived_class_callback_handler(bot.clone(), &q, di).await?;
+        }
+        Some(("restore_class", _)) => {
+            restore_class_callback_handler(bot.clone(), &q, di).await?;
+        }
         _ => {}
     }
 
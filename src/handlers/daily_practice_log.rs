@@ -1,21 +1,41 @@
 use std::sync::Arc;
 
+use chrono::{Datelike, NaiveDate, NaiveTime};
 use teloxide::{
     Bot,
-    dispatching::dialogue::InMemStorage,
-    payloads::SendMessageSetters,
-    prelude::{Dialogue, Requester},
-    types::Message,
+    payloads::{EditMessageTextSetters, SendMessageSetters},
+    prelude::Requester,
+    types::{CallbackQuery, ChatId, InlineKeyboardMarkup, Message, ParseMode},
+    utils::html,
 };
 
 use crate::{
     bot::DI,
     commands::MenuAction,
     keyboards::{self, MainMenuButton},
-    services::daily_practice_log::{add_daily_practice_entry, get_daily_practice_log_history},
-    state::State,
+    services::{
+        daily_practice_log::{
+            PracticeGoalPeriod, PracticeProgress, add_daily_practice_entry, delete_practice_entry,
+            get_practice_day_entries, get_practice_entry, get_practice_month,
+            get_practice_progress, get_recent_practice_entries, set_practice_entry_note,
+            set_practice_goal, update_practice_entry_minutes,
+        },
+        job::get_user_utc_offset,
+        practice_category::{
+            add_practice_category, delete_practice_category, get_practice_categories,
+        },
+        practice_digest::{DigestPeriod, get_digest_subscriptions, set_digest_subscription},
+        practice_reminder::{
+            disable_practice_reminder, get_practice_reminder, set_practice_reminder,
+            snooze_practice_reminder,
+        },
+    },
+    state::{BotDialogue, State},
+    utils,
 };
 
+const ADD_PRACTICE_PROMPT: &str = "Будет добавлена запись о вашей практике за сегодня. Если вы занимались в другой день, выберите его ниже.\n\nВведите количество минут:";
+
 pub async fn daily_practice_log_menu_handler(
     bot: Bot,
     msg: Message,
@@ -24,9 +44,30 @@ pub async fn daily_practice_log_menu_handler(
         MainMenuButton {
             text: MenuAction::AddDailyPracticeEntry.label().to_string(),
         },
+        MainMenuButton {
+            text: MenuAction::PracticeStopwatch.label().to_string(),
+        },
         MainMenuButton {
             text: MenuAction::DailyPracticeLogHistory.label().to_string(),
         },
+        MainMenuButton {
+            text: MenuAction::PracticeCharts.label().to_string(),
+        },
+        MainMenuButton {
+            text: MenuAction::PracticeEntries.label().to_string(),
+        },
+        MainMenuButton {
+            text: MenuAction::PracticeCategories.label().to_string(),
+        },
+        MainMenuButton {
+            text: MenuAction::PracticeGoals.label().to_string(),
+        },
+        MainMenuButton {
+            text: MenuAction::PracticeReminder.label().to_string(),
+        },
+        MainMenuButton {
+            text: MenuAction::PracticeDigests.label().to_string(),
+        },
         MainMenuButton {
             text: MenuAction::MainMenu.label().to_string(),
         },
@@ -37,21 +78,174 @@ pub async fn daily_practice_log_menu_handler(
     Ok(())
 }
 
-pub async fn receive_minutes(
+/// How many of the latest entries "Записи" offers to edit.
+const RECENT_ENTRIES_LIMIT: u32 = 10;
+
+/// Maximum length of a practice note, in characters.
+const MAX_NOTE_LENGTH: usize = 500;
+
+/// Notes in the month view are cut to this many characters; the day and
+/// entry views show them in full.
+const MONTH_NOTE_PREVIEW_LENGTH: usize = 60;
+
+const NOTE_STEP_PROMPT: &str =
+    "Добавьте заметку: над чем работали, что не получалось. Или нажмите «Пропустить».";
+
+/// Maximum length of a practice category name, in characters.
+const MAX_CATEGORY_NAME_LENGTH: usize = 32;
+
+/// Today in the user's offset: the practice diary counts days in it, as the
+/// reminder and the digests do.
+pub async fn user_today(di: &DI, telegram_user_id: i64) -> anyhow::Result<NaiveDate> {
+    let utc_offset = get_user_utc_offset(di.db_pool.clone(), telegram_user_id).await?;
+    Ok(utils::today_in(utc_offset))
+}
+
+/// Asks for the minutes of a new entry, for today unless another day is
+/// picked with the buttons.
+pub async fn prompt_practice_minutes(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &BotDialogue,
+    di: &DI,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let today = user_today(di, chat_id.0).await?;
+    bot.send_message(chat_id, ADD_PRACTICE_PROMPT)
+        .reply_markup(keyboards::make_practice_date_inline_keyboard(today))
+        .await?;
+    dialogue
+        .update(State::AddingDailyPracticeReceiveMinutes)
+        .await?;
+    Ok(())
+}
+
+/// Handles the day buttons under the minutes prompt: `practice_on:YYYY-MM-DD`
+/// and `practice_calendar:YYYY-MM`.
+pub async fn practice_date_callback_handler(
+    bot: Bot,
+    q: CallbackQuery,
+    dialogue: BotDialogue,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+    let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+    let today = user_today(&di, telegram_user_id).await?;
+
+    match q.data.as_deref().and_then(|data| data.split_once(':')) {
+        Some(("practice_on", date)) => {
+            let practiced_on: NaiveDate = date.parse()?;
+            if practiced_on == today {
+                dialogue
+                    .update(State::AddingDailyPracticeReceiveMinutes)
+                    .await?;
+            } else {
+                dialogue
+                    .update(State::AddingPastDailyPracticeReceiveMinutes {
+                        practiced_on: practiced_on.to_string(),
+                    })
+                    .await?;
+            }
+            bot.edit_message_text(
+                message.chat.id,
+                message.id,
+                format!(
+                    "Запись о практике за {}.\n\nВведите количество минут:",
+                    utils::format_date(practiced_on)
+                ),
+            )
+            .await?;
+        }
+        Some(("practice_calendar", month)) => {
+            if let Some(month) = utils::parse_month(month) {
+                let keyboard = keyboards::make_calendar_inline_keyboard(
+                    month,
+                    "practice_on:",
+                    "practice_calendar:",
+                    Some(today),
+                );
+                bot.edit_message_text(message.chat.id, message.id, "Выберите день практики")
+                    .reply_markup(keyboard)
+                    .await?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+async fn save_practice_entry(
+    di: &DI,
+    telegram_user_id: i64,
+    minutes: u16,
+    practice_category_id: Option<i64>,
+    practiced_on: NaiveDate,
+    note: Option<String>,
+) -> anyhow::Result<String> {
+    let output = match add_daily_practice_entry(
+        di.db_pool.clone(),
+        minutes,
+        telegram_user_id,
+        practice_category_id,
+        practiced_on,
+        note,
+    )
+    .await
+    {
+        Ok(_) => {
+            let today = user_today(di, telegram_user_id).await?;
+            let progress =
+                get_practice_progress(di.db_pool.clone(), telegram_user_id, today).await?;
+            let mut output = "✅ Запись успешно добавлена!".to_string();
+            if practiced_on != today {
+                output.push_str(&format!(
+                    "\nДата практики: {}",
+                    utils::format_date(practiced_on)
+                ));
+            }
+            format!("{}\n\n{}", output, format_practice_progress(&progress))
+        }
+        Err(err) => err.to_string(),
+    };
+    Ok(output)
+}
+
+async fn receive_minutes_on(
     bot: Bot,
-    dialogue: Dialogue<State, InMemStorage<State>>,
+    dialogue: BotDialogue,
     msg: Message,
     di: Arc<DI>,
+    practiced_on: NaiveDate,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     match msg.text().map(|text| text.parse::<u16>()) {
         Some(Ok(minutes)) => {
-            let output =
-                match add_daily_practice_entry(di.db_pool.clone(), minutes, msg.chat.id.0).await {
-                    Ok(_) => "✅ Запись успешно добавлена!".to_string(),
-                    Err(err) => err.to_string(),
-                };
-            bot.send_message(msg.chat.id, output).await?;
-            dialogue.exit().await?;
+            let categories = get_practice_categories(di.db_pool.clone(), msg.chat.id.0).await?;
+            if categories.is_empty() {
+                bot.send_message(msg.chat.id, NOTE_STEP_PROMPT)
+                    .reply_markup(keyboards::make_practice_note_skip_inline_keyboard())
+                    .await?;
+                dialogue
+                    .update(State::AddingDailyPracticeReceiveNote {
+                        minutes,
+                        practiced_on: practiced_on.to_string(),
+                        practice_category_id: None,
+                    })
+                    .await?;
+            } else {
+                bot.send_message(msg.chat.id, "Что вы практиковали?")
+                    .reply_markup(keyboards::make_practice_category_pick_inline_keyboard(
+                        &categories,
+                    ))
+                    .await?;
+                dialogue
+                    .update(State::AddingDailyPracticeReceiveCategory {
+                        minutes,
+                        practiced_on: practiced_on.to_string(),
+                    })
+                    .await?;
+            }
         }
         _ => {
             bot.send_message(msg.chat.id, "Отправьте целое число")
@@ -62,26 +256,1136 @@ pub async fn receive_minutes(
     Ok(())
 }
 
+pub async fn receive_minutes(
+    bot: Bot,
+    dialogue: BotDialogue,
+    msg: Message,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let today = user_today(&di, msg.chat.id.0).await?;
+    receive_minutes_on(bot, dialogue, msg, di, today).await
+}
+
+pub async fn receive_past_minutes(
+    bot: Bot,
+    dialogue: BotDialogue,
+    msg: Message,
+    di: Arc<DI>,
+    practiced_on: String,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let practiced_on: NaiveDate = practiced_on.parse()?;
+    receive_minutes_on(bot, dialogue, msg, di, practiced_on).await
+}
+
+pub async fn receive_practice_category_message_handler(
+    bot: Bot,
+    msg: Message,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.send_message(msg.chat.id, "Выберите категорию кнопкой выше")
+        .await?;
+    Ok(())
+}
+
+/// Handles `practice_category:{id|none}` while an entry waits for its
+/// category.
+pub async fn receive_practice_category_callback_handler(
+    bot: Bot,
+    q: CallbackQuery,
+    dialogue: BotDialogue,
+    (minutes, practiced_on): (u16, String),
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    let practice_category_id = match q.data.as_deref().and_then(|data| data.split_once(':')) {
+        Some(("practice_category", "none")) => None,
+        Some(("practice_category", id)) => Some(id.parse::<i64>()?),
+        _ => return Ok(()),
+    };
+    bot.edit_message_text(message.chat.id, message.id, NOTE_STEP_PROMPT)
+        .reply_markup(keyboards::make_practice_note_skip_inline_keyboard())
+        .await?;
+    dialogue
+        .update(State::AddingDailyPracticeReceiveNote {
+            minutes,
+            practiced_on,
+            practice_category_id,
+        })
+        .await?;
+    Ok(())
+}
+
+/// The last, optional step of a new entry: a note typed as a message.
+pub async fn receive_practice_note_step_handler(
+    bot: Bot,
+    dialogue: BotDialogue,
+    msg: Message,
+    di: Arc<DI>,
+    (minutes, practiced_on, practice_category_id): (u16, String, Option<i64>),
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match msg.text().map(str::trim) {
+        // A tapped menu button is not meant as a note.
+        Some(text) if MenuAction::parse(text).is_some() => {
+            bot.send_message(
+                msg.chat.id,
+                "Напишите заметку или нажмите «Пропустить» выше",
+            )
+            .await?;
+        }
+        Some(text) if text.chars().count() > MAX_NOTE_LENGTH => {
+            bot.send_message(
+                msg.chat.id,
+                format!("Заметка слишком длинная: не больше {MAX_NOTE_LENGTH} символов"),
+            )
+            .await?;
+        }
+        Some(text) if !text.is_empty() => {
+            let practiced_on: NaiveDate = practiced_on.parse()?;
+            let output = save_practice_entry(
+                &di,
+                msg.chat.id.0,
+                minutes,
+                practice_category_id,
+                practiced_on,
+                Some(text.to_string()),
+            )
+            .await?;
+            dialogue.exit().await?;
+            bot.send_message(msg.chat.id, output).await?;
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Отправьте текст").await?;
+        }
+    }
+    Ok(())
+}
+
+/// Handles `practice_note_skip:`: saves the new entry without a note.
+pub async fn practice_note_skip_callback_handler(
+    bot: Bot,
+    q: CallbackQuery,
+    dialogue: BotDialogue,
+    di: Arc<DI>,
+    (minutes, practiced_on, practice_category_id): (u16, String, Option<i64>),
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+    if q.data.as_deref() != Some("practice_note_skip:") {
+        return Ok(());
+    }
+
+    let practiced_on: NaiveDate = practiced_on.parse()?;
+    let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+    let output = save_practice_entry(
+        &di,
+        telegram_user_id,
+        minutes,
+        practice_category_id,
+        practiced_on,
+        None,
+    )
+    .await?;
+    bot.edit_message_text(message.chat.id, message.id, output)
+        .await?;
+    dialogue.exit().await?;
+    Ok(())
+}
+
+/// Handles `practice_note:{id}`: a note for an already saved entry.
+pub async fn practice_note_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    dialogue: &BotDialogue,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let daily_practice_log_id: i64 = id.parse()?;
+        dialogue
+            .update(State::AddingPracticeNoteReceiveText {
+                daily_practice_log_id,
+            })
+            .await?;
+        bot.send_message(
+            q.from.id,
+            "Напишите заметку к практике (над чем работали, что не получалось) или «-», чтобы удалить её",
+        )
+        .await?;
+    } else {
+        bot.send_message(q.from.id, "Ошибка").await?;
+    }
+
+    Ok(())
+}
+
+pub async fn receive_practice_note_handler(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    di: Arc<DI>,
+    daily_practice_log_id: i64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match msg.text().map(str::trim) {
+        Some(text) if text.chars().count() > MAX_NOTE_LENGTH => {
+            bot.send_message(
+                msg.chat.id,
+                format!("Заметка слишком длинная: не больше {MAX_NOTE_LENGTH} символов"),
+            )
+            .await?;
+        }
+        Some(text) if !text.is_empty() => {
+            let note = (text != "-").then(|| text.to_string());
+            let utc_offset = get_user_utc_offset(di.db_pool.clone(), msg.chat.id.0).await?;
+            let output = match set_practice_entry_note(
+                di.db_pool.clone(),
+                daily_practice_log_id,
+                msg.chat.id.0,
+                note,
+            )
+            .await
+            {
+                Ok(entry) if entry.note.is_some() => {
+                    format!(
+                        "📝 Заметка к практике {} сохранена",
+                        entry.describe(utc_offset)
+                    )
+                }
+                Ok(entry) => format!("Заметка к практике {} удалена", entry.describe(utc_offset)),
+                Err(err) => err.to_string(),
+            };
+            dialogue.exit().await?;
+            bot.send_message(msg.chat.id, output).await?;
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Отправьте текст").await?;
+        }
+    }
+    Ok(())
+}
+
 pub async fn list_daily_practice_log_history_handler(
     bot: Bot,
     msg: Message,
     di: Arc<DI>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let logs = get_daily_practice_log_history(di.db_pool.clone(), msg.chat.id.0).await?;
-    if logs.is_empty() {
-        bot.send_message(msg.chat.id, "История практик пуста.")
+    let month = current_month(user_today(&di, msg.chat.id.0).await?);
+    let (output, keyboard) = render_practice_month(&di, msg.chat.id.0, month).await?;
+    bot.send_message(msg.chat.id, output)
+        .reply_markup(keyboard)
+        .parse_mode(ParseMode::Html)
+        .await?;
+
+    Ok(())
+}
+
+/// Handles `practice_month:YYYY-MM` from the "◀ / ▶" buttons.
+pub async fn practice_month_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, month)) = data.split_once(':')
+        && let Some(month) = utils::parse_month(month)
+    {
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+        let (output, keyboard) = render_practice_month(&di, telegram_user_id, month).await?;
+        bot.edit_message_text(message.chat.id, message.id, output)
+            .reply_markup(keyboard)
+            .parse_mode(ParseMode::Html)
+            .await?;
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+/// Handles `practice_day_calendar:YYYY-MM` from "🔍 Записи за день".
+pub async fn practice_day_calendar_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, month)) = data.split_once(':')
+        && let Some(month) = utils::parse_month(month)
+    {
+        let keyboard = keyboards::make_calendar_inline_keyboard(
+            month,
+            "practice_day:",
+            "practice_day_calendar:",
+            Some(user_today(&di, q.from.id.0.try_into().unwrap()).await?),
+        );
+        bot.edit_message_text(message.chat.id, message.id, "Выберите день")
+            .reply_markup(keyboard)
+            .await?;
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+/// Handles `practice_day:YYYY-MM-DD`: the separate entries of the day.
+pub async fn practice_day_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, date)) = data.split_once(':')
+    {
+        let day: NaiveDate = date.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+        let entries = get_practice_day_entries(di.db_pool.clone(), telegram_user_id, day).await?;
+        let utc_offset = get_user_utc_offset(di.db_pool.clone(), telegram_user_id).await?;
+        let mut output = format!(
+            "<b>{} ({})</b>\n",
+            utils::format_date(day),
+            utils::get_russian_weekday_name(day.weekday(), true)
+        );
+        if entries.is_empty() {
+            output.push_str("\nВ этот день практик не было");
+        } else {
+            let total: u32 = entries.iter().map(|e| u32::from(e.minutes)).sum();
+            output.push_str(&format!(
+                "\nЗаписей: {}, всего {} мин\n",
+                entries.len(),
+                total
+            ));
+            for entry in &entries {
+                let time = entry
+                    .local_created_at(utc_offset)
+                    .map(|dt| dt.format("%H:%M").to_string())
+                    .unwrap_or_default();
+                output.push_str(&format!("\n{} — {} мин", time, entry.minutes));
+                if let Some(ref note) = entry.note {
+                    output.push_str(&format!("\n    📝 {}", html::escape(note)));
+                }
+            }
+            output.push_str("\n\nВыберите запись, чтобы изменить или удалить её.");
+        }
+        let keyboard = if entries.is_empty() {
+            keyboards::make_practice_month_inline_keyboard(
+                day.with_day(1).unwrap_or(day),
+                current_month(utils::today_in(utc_offset)),
+            )
+        } else {
+            keyboards::make_practice_day_inline_keyboard(&entries, utc_offset)
+        };
+        bot.edit_message_text(message.chat.id, message.id, output)
+            .reply_markup(keyboard)
+            .parse_mode(ParseMode::Html)
+            .await?;
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+fn current_month(today: NaiveDate) -> NaiveDate {
+    today.with_day(1).unwrap_or(today)
+}
+
+async fn render_practice_month(
+    di: &DI,
+    telegram_user_id: i64,
+    month: NaiveDate,
+) -> anyhow::Result<(String, InlineKeyboardMarkup)> {
+    let practice = get_practice_month(di.db_pool.clone(), telegram_user_id, month).await?;
+    let today = user_today(di, telegram_user_id).await?;
+
+    let mut output = format!(
+        "<b>{} {}</b>\n",
+        utils::get_russian_month_name(month.month()),
+        month.year()
+    );
+    if practice.days.is_empty() {
+        output.push_str("\nВ этом месяце практик не было");
+    } else {
+        let render_days = |with_notes: bool| {
+            let mut days = String::new();
+            for day in &practice.days {
+                days.push_str(&format!(
+                    "\n{} ({}) — {} мин",
+                    day.date.format("%d.%m"),
+                    utils::get_russian_weekday_name(day.date.weekday(), true),
+                    day.minutes
+                ));
+                if day.sessions > 1 {
+                    days.push_str(&format!(" (записей: {})", day.sessions));
+                }
+                if with_notes {
+                    for note in &day.notes {
+                        days.push_str(&format!(
+                            "\n    📝 {}",
+                            html::escape(&utils::shorten(note, MONTH_NOTE_PREVIEW_LENGTH))
+                        ));
+                    }
+                } else if !day.notes.is_empty() {
+                    days.push_str(" 📝");
+                }
+            }
+            days
+        };
+
+        let mut summary = format!(
+            "\n\nВсего: {:.1} ч\nВ среднем: {:.0} мин в день",
+            practice.total_minutes() as f32 / 60.0,
+            practice.average_minutes(today)
+        );
+        if practice.categories.iter().any(|c| c.name.is_some()) {
+            summary.push_str("\n\n<b>По категориям</b>");
+            let total = practice.total_minutes().max(1);
+            for category in &practice.categories {
+                summary.push_str(&format!(
+                    "\n• {} — {} мин ({}%)",
+                    html::escape(category.name.as_deref().unwrap_or("Без категории")),
+                    category.minutes,
+                    category.minutes * 100 / total
+                ));
+            }
+        }
+
+        // With many notes the month would not fit into one message, so they
+        // are left to the day view and only marked here.
+        let days = render_days(true);
+        let length = [&output, &days, &summary]
+            .iter()
+            .map(|part| part.chars().count())
+            .sum::<usize>();
+        let days = if length > utils::MAX_MESSAGE_LENGTH {
+            render_days(false)
+        } else {
+            days
+        };
+        output.push_str(&days);
+        output.push_str(&summary);
+        output = utils::fit_message(output);
+    }
+
+    let keyboard = keyboards::make_practice_month_inline_keyboard(month, current_month(today));
+    Ok((output, keyboard))
+}
+
+async fn format_practice_reminder(di: &DI, telegram_user_id: i64) -> anyhow::Result<String> {
+    let utc_offset = get_user_utc_offset(di.db_pool.clone(), telegram_user_id).await?;
+    let output = match get_practice_reminder(di.db_pool.clone(), telegram_user_id).await? {
+        Some(remind_at) => format!(
+            "⏰ Напоминание о практике: каждый день в {} ({})\n\nЕсли к этому времени в дневнике нет записи за сегодня, бот напомнит о ней.",
+            remind_at.format("%H:%M"),
+            utils::format_utc_offset(utc_offset)
+        ),
+        None => format!(
+            "⏰ Напоминание о практике выключено\n\nЧасовой пояс: {}. Изменить: /timezone",
+            utils::format_utc_offset(utc_offset)
+        ),
+    };
+    Ok(output)
+}
+
+pub async fn practice_reminder_handler(
+    bot: Bot,
+    msg: Message,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let output = format_practice_reminder(&di, msg.chat.id.0).await?;
+    let enabled = get_practice_reminder(di.db_pool.clone(), msg.chat.id.0)
+        .await?
+        .is_some();
+    bot.send_message(msg.chat.id, output)
+        .reply_markup(keyboards::make_practice_reminder_inline_keyboard(enabled))
+        .await?;
+    Ok(())
+}
+
+pub async fn practice_reminder_set_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    dialogue: &BotDialogue,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    dialogue
+        .update(State::SettingPracticeReminderReceiveTime)
+        .await?;
+    if let Some(message) = q.regular_message() {
+        bot.edit_message_text(
+            message.chat.id,
+            message.id,
+            "Во сколько напоминать? Введите время, например 20:30:",
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+pub async fn receive_practice_reminder_time_handler(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match msg
+        .text()
+        .and_then(|text| NaiveTime::parse_from_str(text.trim(), "%H:%M").ok())
+    {
+        Some(remind_at) => {
+            let output =
+                match set_practice_reminder(di.db_pool.clone(), msg.chat.id.0, remind_at).await {
+                    Ok(()) => format_practice_reminder(&di, msg.chat.id.0).await?,
+                    Err(err) => err.to_string(),
+                };
+            dialogue.exit().await?;
+            bot.send_message(msg.chat.id, output)
+                .reply_markup(keyboards::make_practice_reminder_inline_keyboard(true))
+                .await?;
+        }
+        None => {
+            bot.send_message(msg.chat.id, "Введите время в формате ЧЧ:ММ, например 20:30")
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn practice_reminder_off_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+    let output = match disable_practice_reminder(di.db_pool.clone(), telegram_user_id).await {
+        Ok(()) => format_practice_reminder(&di, telegram_user_id).await?,
+        Err(err) => err.to_string(),
+    };
+    bot.edit_message_text(message.chat.id, message.id, output)
+        .reply_markup(keyboards::make_practice_reminder_inline_keyboard(false))
+        .await?;
+    Ok(())
+}
+
+/// Handles "✍️ Записать практику" under a reminder.
+pub async fn practice_add_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    dialogue: &BotDialogue,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(message) = q.regular_message() {
+        bot.edit_message_reply_markup(message.chat.id, message.id)
             .await?;
+    }
+    prompt_practice_minutes(&bot, q.from.id.into(), dialogue, &di).await?;
+    Ok(())
+}
+
+/// Handles `practice_snooze:{minutes}` under a reminder.
+pub async fn practice_snooze_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
         return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, minutes)) = data.split_once(':')
+    {
+        let minutes: u16 = minutes.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+        let output =
+            match snooze_practice_reminder(di.db_pool.clone(), telegram_user_id, minutes).await {
+                Ok(remind_at) => format!("⏰ Напомню в {}", remind_at.format("%H:%M")),
+                Err(err) => err.to_string(),
+            };
+        bot.edit_message_text(message.chat.id, message.id, output)
+            .await?;
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+fn format_goal_line(label: &str, minutes: u32, goal: Option<u16>) -> String {
+    match goal {
+        Some(goal) if minutes >= u32::from(goal) => {
+            format!("{}: {} из {} мин ✅", label, minutes, goal)
+        }
+        Some(goal) => format!(
+            "{}: {} из {} мин ({}%)",
+            label,
+            minutes,
+            goal,
+            minutes * 100 / u32::from(goal.max(1))
+        ),
+        None => format!("{}: {} мин", label, minutes),
     }
+}
+
+fn format_practice_progress(progress: &PracticeProgress) -> String {
+    let mut output = format!(
+        "{}\n{}",
+        format_goal_line("Сегодня", progress.today_minutes, progress.goals.daily),
+        format_goal_line(
+            "На этой неделе",
+            progress.week_minutes,
+            progress.goals.weekly
+        )
+    );
+    output.push_str(&format!(
+        "\n🔥 Серия: {} дн. Рекорд: {} дн.",
+        progress.current_streak, progress.longest_streak
+    ));
+    output
+}
 
-    let total_hours = logs.iter().map(|log| log.minutes as f32).sum::<f32>() / 60.0;
-    let formatted_logs: Vec<String> = logs.iter().map(|s| s.to_string()).collect();
+pub async fn practice_goals_handler(
+    bot: Bot,
+    msg: Message,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let today = user_today(&di, msg.chat.id.0).await?;
+    let progress = get_practice_progress(di.db_pool.clone(), msg.chat.id.0, today).await?;
+    let goal = |goal: Option<u16>| match goal {
+        Some(goal) => format!("{} мин", goal),
+        None => "не задана".to_string(),
+    };
     let output = format!(
-        "Всего: {:.1} часов\n\n{}",
-        total_hours,
-        formatted_logs.join("\n")
+        "🎯 Цель на день: {}\n🎯 Цель на неделю: {}\n\n{}\n\nСерия — дни подряд, когда дневная цель выполнена (без цели — дни с практикой).",
+        goal(progress.goals.daily),
+        goal(progress.goals.weekly),
+        format_practice_progress(&progress)
     );
-    bot.send_message(msg.chat.id, output).await?;
+    bot.send_message(msg.chat.id, output)
+        .reply_markup(keyboards::make_practice_goals_inline_keyboard())
+        .await?;
+    Ok(())
+}
+
+/// Handles `practice_goal:daily` and `practice_goal:weekly`.
+pub async fn practice_goal_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    dialogue: &BotDialogue,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+
+    let period = match q.data.as_deref().and_then(|data| data.split_once(':')) {
+        Some((_, "daily")) => PracticeGoalPeriod::Daily,
+        Some((_, "weekly")) => PracticeGoalPeriod::Weekly,
+        _ => {
+            bot.send_message(q.from.id, "Ошибка").await?;
+            return Ok(());
+        }
+    };
+    dialogue
+        .update(State::SettingPracticeGoalReceiveMinutes { period })
+        .await?;
 
+    if let Some(message) = q.regular_message() {
+        let prompt = match period {
+            PracticeGoalPeriod::Daily => "Сколько минут в день вы хотите практиковать?",
+            PracticeGoalPeriod::Weekly => "Сколько минут в неделю вы хотите практиковать?",
+        };
+        bot.edit_message_text(
+            message.chat.id,
+            message.id,
+            format!("{}\nОтправьте 0, чтобы убрать цель.", prompt),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+pub async fn receive_practice_goal_handler(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    di: Arc<DI>,
+    period: PracticeGoalPeriod,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match msg.text().map(|text| text.trim().parse::<u16>()) {
+        Some(Ok(minutes)) => {
+            let minutes = (minutes > 0).then_some(minutes);
+            let output =
+                match set_practice_goal(di.db_pool.clone(), msg.chat.id.0, period, minutes).await {
+                    Ok(_) => match minutes {
+                        Some(_) => "🎯 Цель сохранена",
+                        None => "Цель убрана",
+                    }
+                    .to_string(),
+                    Err(err) => err.to_string(),
+                };
+            dialogue.exit().await?;
+            bot.send_message(msg.chat.id, output).await?;
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Отправьте целое число минут")
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+fn format_digest_settings(weekly: bool, monthly: bool) -> String {
+    let status = |enabled: bool| {
+        if enabled {
+            "включена"
+        } else {
+            "выключена"
+        }
+    };
+    format!(
+        "📬 Сводки по практике и занятиям\n\nЗа неделю, по понедельникам: {}\nЗа месяц, 1-го числа: {}",
+        status(weekly),
+        status(monthly)
+    )
+}
+
+pub async fn practice_digests_handler(
+    bot: Bot,
+    msg: Message,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let (weekly, monthly) = get_digest_subscriptions(di.db_pool.clone(), msg.chat.id.0).await?;
+    bot.send_message(msg.chat.id, format_digest_settings(weekly, monthly))
+        .reply_markup(keyboards::make_digest_settings_inline_keyboard(
+            weekly, monthly,
+        ))
+        .await?;
+    Ok(())
+}
+
+/// Handles `digest_toggle:{week|month}:{on|off}`.
+pub async fn digest_toggle_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    let toggle = q
+        .data
+        .as_deref()
+        .and_then(|data| data.split_once(':'))
+        .and_then(|(_, rest)| rest.split_once(':'));
+    let (period, enabled) = match toggle {
+        Some(("week", enabled @ ("on" | "off"))) => (DigestPeriod::Week, enabled == "on"),
+        Some(("month", enabled @ ("on" | "off"))) => (DigestPeriod::Month, enabled == "on"),
+        _ => {
+            bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+    match set_digest_subscription(di.db_pool.clone(), telegram_user_id, period, enabled).await {
+        Ok((weekly, monthly)) => {
+            bot.edit_message_text(
+                message.chat.id,
+                message.id,
+                format_digest_settings(weekly, monthly),
+            )
+            .reply_markup(keyboards::make_digest_settings_inline_keyboard(
+                weekly, monthly,
+            ))
+            .await?;
+        }
+        Err(err) => {
+            bot.edit_message_text(message.chat.id, message.id, err.to_string())
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+fn format_practice_categories(count: usize) -> String {
+    if count == 0 {
+        "🏷 Категорий практики пока нет.\n\nДобавьте, например, «Техника», «Репертуар» или «Теория» — тогда при каждой записи бот спросит, что вы практиковали.".to_string()
+    } else {
+        "🏷 Категории практики\n\nНажмите на категорию, чтобы удалить её. Записи удалённой категории останутся без категории.".to_string()
+    }
+}
+
+pub async fn practice_categories_handler(
+    bot: Bot,
+    msg: Message,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let categories = get_practice_categories(di.db_pool.clone(), msg.chat.id.0).await?;
+    bot.send_message(msg.chat.id, format_practice_categories(categories.len()))
+        .reply_markup(keyboards::make_practice_categories_inline_keyboard(
+            &categories,
+        ))
+        .await?;
+    Ok(())
+}
+
+pub async fn practice_category_add_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    dialogue: &BotDialogue,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    dialogue
+        .update(State::AddingPracticeCategoryReceiveName)
+        .await?;
+    if let Some(message) = q.regular_message() {
+        bot.edit_message_text(message.chat.id, message.id, "Введите название категории:")
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn receive_practice_category_name_handler(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let name = msg.text().map(str::trim).unwrap_or_default();
+    if name.is_empty() || name.chars().count() > MAX_CATEGORY_NAME_LENGTH {
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "Введите название длиной до {} символов",
+                MAX_CATEGORY_NAME_LENGTH
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    match add_practice_category(di.db_pool.clone(), msg.chat.id.0, name).await {
+        Ok(_) => {
+            dialogue.exit().await?;
+            let categories = get_practice_categories(di.db_pool.clone(), msg.chat.id.0).await?;
+            bot.send_message(msg.chat.id, format_practice_categories(categories.len()))
+                .reply_markup(keyboards::make_practice_categories_inline_keyboard(
+                    &categories,
+                ))
+                .await?;
+        }
+        // Let them try another name.
+        Err(err) => {
+            bot.send_message(msg.chat.id, err.to_string()).await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn practice_category_delete_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let practice_category_id: i64 = id.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+        if let Err(err) =
+            delete_practice_category(di.db_pool.clone(), practice_category_id, telegram_user_id)
+                .await
+        {
+            bot.edit_message_text(message.chat.id, message.id, err.to_string())
+                .await?;
+            return Ok(());
+        }
+        let categories = get_practice_categories(di.db_pool.clone(), telegram_user_id).await?;
+        bot.edit_message_text(
+            message.chat.id,
+            message.id,
+            format_practice_categories(categories.len()),
+        )
+        .reply_markup(keyboards::make_practice_categories_inline_keyboard(
+            &categories,
+        ))
+        .await?;
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+async fn render_practice_entries(
+    di: &DI,
+    telegram_user_id: i64,
+) -> anyhow::Result<(String, InlineKeyboardMarkup)> {
+    let entries =
+        get_recent_practice_entries(di.db_pool.clone(), telegram_user_id, RECENT_ENTRIES_LIMIT)
+            .await?;
+    let utc_offset = get_user_utc_offset(di.db_pool.clone(), telegram_user_id).await?;
+    let output = if entries.is_empty() {
+        "Записей о практике пока нет".to_string()
+    } else {
+        "Последние записи. Выберите запись, чтобы изменить или удалить её:".to_string()
+    };
+    Ok((
+        output,
+        keyboards::make_practice_entries_inline_keyboard(&entries, utc_offset),
+    ))
+}
+
+pub async fn practice_entries_handler(
+    bot: Bot,
+    msg: Message,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let (output, keyboard) = render_practice_entries(&di, msg.chat.id.0).await?;
+    bot.send_message(msg.chat.id, output)
+        .reply_markup(keyboard)
+        .await?;
+    Ok(())
+}
+
+/// Handles the "« Назад" button of an entry.
+pub async fn practice_entries_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+    let (output, keyboard) = render_practice_entries(&di, telegram_user_id).await?;
+    bot.edit_message_text(message.chat.id, message.id, output)
+        .reply_markup(keyboard)
+        .await?;
+    Ok(())
+}
+
+pub async fn practice_entry_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let daily_practice_log_id: i64 = id.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+        match get_practice_entry(di.db_pool.clone(), daily_practice_log_id, telegram_user_id).await
+        {
+            Ok(entry) => {
+                let utc_offset = get_user_utc_offset(di.db_pool.clone(), telegram_user_id).await?;
+                let mut output = entry.describe(utc_offset);
+                if let Some(ref note) = entry.note {
+                    output.push_str(&format!("\n📝 {}", note));
+                }
+                bot.edit_message_text(message.chat.id, message.id, output)
+                    .reply_markup(keyboards::make_practice_entry_inline_keyboard(
+                        daily_practice_log_id,
+                    ))
+                    .await?;
+            }
+            Err(err) => {
+                bot.edit_message_text(message.chat.id, message.id, err.to_string())
+                    .await?;
+            }
+        }
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn practice_entry_edit_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    dialogue: &BotDialogue,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let daily_practice_log_id: i64 = id.parse()?;
+        dialogue
+            .update(State::EditingDailyPracticeReceiveMinutes {
+                daily_practice_log_id,
+            })
+            .await?;
+
+        if let Some(message) = q.regular_message() {
+            bot.edit_message_text(
+                message.chat.id,
+                message.id,
+                "Введите правильное количество минут:",
+            )
+            .await?;
+        }
+    } else {
+        bot.send_message(q.from.id, "Ошибка").await?;
+    }
+
+    Ok(())
+}
+
+pub async fn receive_practice_entry_minutes_handler(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    di: Arc<DI>,
+    daily_practice_log_id: i64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match msg.text().map(|text| text.parse::<u16>()) {
+        Some(Ok(minutes)) => {
+            dialogue.exit().await?;
+            match update_practice_entry_minutes(
+                di.db_pool.clone(),
+                daily_practice_log_id,
+                msg.chat.id.0,
+                minutes,
+            )
+            .await
+            {
+                Ok(()) => {
+                    let (output, keyboard) = render_practice_entries(&di, msg.chat.id.0).await?;
+                    bot.send_message(msg.chat.id, format!("✅ Запись изменена\n\n{}", output))
+                        .reply_markup(keyboard)
+                        .await?;
+                }
+                Err(err) => {
+                    bot.send_message(msg.chat.id, err.to_string()).await?;
+                }
+            }
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Отправьте целое число")
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn practice_entry_delete_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let daily_practice_log_id: i64 = id.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+        match get_practice_entry(di.db_pool.clone(), daily_practice_log_id, telegram_user_id).await
+        {
+            Ok(entry) => {
+                let utc_offset = get_user_utc_offset(di.db_pool.clone(), telegram_user_id).await?;
+                let output = format!("Удалить запись?\n\n{}", entry.describe(utc_offset));
+                bot.edit_message_text(message.chat.id, message.id, output)
+                    .reply_markup(
+                        keyboards::make_delete_practice_entry_confirmation_inline_keyboard(
+                            daily_practice_log_id,
+                        ),
+                    )
+                    .await?;
+            }
+            Err(err) => {
+                bot.edit_message_text(message.chat.id, message.id, err.to_string())
+                    .await?;
+            }
+        }
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn practice_entry_delete_confirm_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    if let Some(ref data) = q.data
+        && let Some((_, id)) = data.split_once(':')
+    {
+        let daily_practice_log_id: i64 = id.parse()?;
+        let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+        match delete_practice_entry(di.db_pool.clone(), daily_practice_log_id, telegram_user_id)
+            .await
+        {
+            Ok(()) => {
+                let (output, keyboard) = render_practice_entries(&di, telegram_user_id).await?;
+                bot.edit_message_text(
+                    message.chat.id,
+                    message.id,
+                    format!("🗑 Запись удалена\n\n{}", output),
+                )
+                .reply_markup(keyboard)
+                .await?;
+            }
+            Err(err) => {
+                bot.edit_message_text(message.chat.id, message.id, err.to_string())
+                    .await?;
+            }
+        }
+    } else {
+        bot.edit_message_text(message.chat.id, message.id, "Произошла ошибка")
+            .await?;
+    }
     Ok(())
 }
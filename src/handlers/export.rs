@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use teloxide::{
+    payloads::{SendDocumentSetters, SendMessageSetters},
+    prelude::*,
+    types::InputFile,
+};
+
+use crate::{
+    bot::DI,
+    keyboards,
+    services::export::get_user_export,
+    spreadsheet::{self, xlsx},
+    utils,
+};
+
+pub async fn export_handler(
+    bot: Bot,
+    msg: Message,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.send_message(
+        msg.chat.id,
+        "📤 Экспорт данных\n\nЗанятия, история списаний и дневник практик будут выгружены в файлы. Выберите формат:",
+    )
+    .reply_markup(keyboards::make_export_inline_keyboard())
+    .await?;
+    Ok(())
+}
+
+/// Handles `export:csv` and `export:xlsx`: CSV comes as a file per table,
+/// XLSX as a single workbook with a sheet per table.
+pub async fn export_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    let Some((_, format)) = q.data.as_deref().and_then(|data| data.split_once(':')) else {
+        return Ok(());
+    };
+    let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+    let export = get_user_export(di.db_pool.clone(), telegram_user_id).await?;
+    if export.is_empty() {
+        bot.send_message(
+            message.chat.id,
+            "Пока нечего выгружать: нет ни занятий, ни записей о практике",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let files: Vec<(String, Vec<u8>)> = match format {
+        "csv" => export
+            .tables
+            .iter()
+            .map(|table| (format!("{}.csv", table.name), spreadsheet::to_csv(table)))
+            .collect(),
+        "xlsx" => vec![("export.xlsx".to_string(), xlsx::to_xlsx(&export.tables))],
+        _ => {
+            bot.send_message(message.chat.id, "Произошла ошибка")
+                .await?;
+            return Ok(());
+        }
+    };
+    let caption = format!(
+        "Время указано в часовом поясе {}",
+        utils::format_utc_offset(export.utc_offset)
+    );
+    let last = files.len() - 1;
+    for (index, (file_name, content)) in files.into_iter().enumerate() {
+        let request = bot.send_document(
+            message.chat.id,
+            InputFile::memory(content).file_name(file_name),
+        );
+        // One caption under the last file is enough.
+        if index == last {
+            request.caption(caption.clone()).await?;
+        } else {
+            request.await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use std::sync::Arc;
+
+use chrono::{Datelike, Days, NaiveDate};
+use teloxide::{
+    payloads::{SendMessageSetters, SendPhotoSetters},
+    prelude::*,
+    types::InputFile,
+};
+
+use crate::{
+    bot::DI,
+    charts::{self, Bar},
+    handlers::daily_practice_log::user_today,
+    keyboards,
+    services::daily_practice_log::{get_practice_daily_totals, group_practice_totals},
+    utils,
+};
+
+/// Charts offered by `practice_chart:{days:N|weeks:N|year}`.
+enum PracticeChart {
+    Days(u64),
+    Weeks(u64),
+    Year,
+}
+
+impl PracticeChart {
+    /// Only the periods the keyboard offers, so a crafted callback cannot ask
+    /// for a chart of any length.
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "days:7" => Some(Self::Days(7)),
+            "days:30" => Some(Self::Days(30)),
+            "weeks:12" => Some(Self::Weeks(12)),
+            "weeks:26" => Some(Self::Weeks(26)),
+            "year" => Some(Self::Year),
+            _ => None,
+        }
+    }
+}
+
+fn monday_of(date: NaiveDate) -> NaiveDate {
+    date - Days::new(date.weekday().num_days_from_monday().into())
+}
+
+fn format_total(days: &[(NaiveDate, u32)]) -> String {
+    let minutes: u32 = days.iter().map(|(_, minutes)| minutes).sum();
+    format!(
+        "Дней с практикой: {}, всего: {:.1} ч",
+        days.len(),
+        minutes as f32 / 60.0
+    )
+}
+
+pub async fn practice_charts_handler(
+    bot: Bot,
+    msg: Message,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.send_message(
+        msg.chat.id,
+        "📊 Графики практики\n\nВыберите, что показать:",
+    )
+    .reply_markup(keyboards::make_practice_charts_inline_keyboard())
+    .await?;
+    Ok(())
+}
+
+/// Handles `practice_chart:...` and sends the chart as a photo, keeping the
+/// chart picker in place to choose another one.
+pub async fn practice_chart_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    let Some(chart) = q
+        .data
+        .as_deref()
+        .and_then(|data| data.split_once(':'))
+        .and_then(|(_, chart)| PracticeChart::parse(chart))
+    else {
+        bot.send_message(message.chat.id, "Произошла ошибка")
+            .await?;
+        return Ok(());
+    };
+    let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+    let today = user_today(&di, telegram_user_id).await?;
+    let from = match chart {
+        PracticeChart::Days(count) => today - Days::new(count - 1),
+        PracticeChart::Weeks(count) => monday_of(today) - Days::new((count - 1) * 7),
+        PracticeChart::Year => {
+            monday_of(today) - Days::new((u64::from(charts::HEATMAP_WEEKS) - 1) * 7)
+        }
+    };
+
+    let days = get_practice_daily_totals(di.db_pool.clone(), telegram_user_id, from, today).await?;
+    if days.is_empty() {
+        bot.send_message(
+            message.chat.id,
+            format!(
+                "С {} по {} практик не было",
+                utils::format_date(from),
+                utils::format_date(today)
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let period = format!(
+        "{} — {}",
+        utils::format_date(from),
+        utils::format_date(today)
+    );
+    let (png, caption) = match chart {
+        PracticeChart::Days(_) => {
+            let bars: Vec<Bar> = group_practice_totals(&days, from, today, 1)
+                .into_iter()
+                .map(|(date, minutes)| Bar {
+                    label: date.day().to_string(),
+                    minutes,
+                })
+                .collect();
+            (
+                charts::render_bar_chart(&bars),
+                format!(
+                    "Минуты практики по дням, {}\n{}",
+                    period,
+                    format_total(&days)
+                ),
+            )
+        }
+        PracticeChart::Weeks(_) => {
+            let bars: Vec<Bar> = group_practice_totals(&days, from, today, 7)
+                .into_iter()
+                .map(|(monday, minutes)| Bar {
+                    label: monday.format("%d.%m").to_string(),
+                    minutes,
+                })
+                .collect();
+            (
+                charts::render_bar_chart(&bars),
+                format!(
+                    "Минуты практики по неделям, {}\nПод столбцами — понедельники недель.\n{}",
+                    period,
+                    format_total(&days)
+                ),
+            )
+        }
+        PracticeChart::Year => (
+            charts::render_heatmap(&days, today),
+            format!(
+                "Практика за год, {}\n{}\n\nСтолбцы — недели, строки — дни с понедельника по воскресенье, сверху — номера месяцев. Чем темнее клетка, тем больше минут.",
+                period,
+                format_total(&days)
+            ),
+        ),
+    };
+
+    bot.send_photo(
+        message.chat.id,
+        InputFile::memory(png).file_name("practice_chart.png"),
+    )
+    .caption(caption)
+    .await?;
+    Ok(())
+}
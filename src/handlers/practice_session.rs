@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use chrono::{TimeDelta, Utc};
+use teloxide::{
+    payloads::{EditMessageTextSetters, SendMessageSetters},
+    prelude::*,
+};
+
+use crate::{
+    bot::DI,
+    keyboards,
+    repositories::practice_session::PracticeSession,
+    services::{
+        job::get_user_utc_offset,
+        practice_session::{
+            StoppedPracticeSession, get_practice_session, pause_practice_session,
+            resume_practice_session, start_practice_session, stop_practice_session,
+        },
+    },
+};
+
+/// The configured limit after which a running stopwatch is stopped.
+pub fn max_session_duration(di: &DI) -> TimeDelta {
+    i64::try_from(di.config.practice.max_session_mins)
+        .ok()
+        .and_then(TimeDelta::try_minutes)
+        .unwrap_or(TimeDelta::MAX)
+}
+
+fn format_duration(duration: TimeDelta) -> String {
+    let minutes = duration.num_minutes();
+    if minutes < 60 {
+        format!("{} мин", minutes)
+    } else {
+        format!("{} ч {:02} мин", minutes / 60, minutes % 60)
+    }
+}
+
+async fn format_stopwatch(
+    di: &DI,
+    telegram_user_id: i64,
+    session: Option<&PracticeSession>,
+) -> anyhow::Result<String> {
+    let Some(session) = session else {
+        return Ok("⏱ Секундомер практики\n\nНажмите «▶ Начать практику», когда начнёте заниматься, и «⏹ Закончить» в конце — минуты сами запишутся в дневник.".to_string());
+    };
+
+    let utc_offset = get_user_utc_offset(di.db_pool.clone(), telegram_user_id).await?;
+    let started_at = session
+        .started_at_utc()
+        .map(|dt| dt.with_timezone(&utc_offset).format("%H:%M").to_string())
+        .unwrap_or_default();
+    let elapsed = format_duration(session.elapsed(Utc::now()));
+    let output = if session.is_paused() {
+        format!("⏸ Практика на паузе: {elapsed}\nНачало: {started_at}")
+    } else {
+        format!("⏱ Идёт практика: {elapsed}\nНачало: {started_at}")
+    };
+    Ok(output)
+}
+
+fn format_stopped(stopped: &StoppedPracticeSession, max_duration: TimeDelta) -> String {
+    if stopped.daily_practice_log_id.is_none() {
+        return "⏹ Практика заняла меньше минуты — запись не добавлена".to_string();
+    }
+    let mut output = format!(
+        "⏹ Практика закончена: {} мин записано в дневник",
+        stopped.minutes
+    );
+    if stopped.capped {
+        output.push_str(&format!(
+            "\n\nСекундомер шёл дольше {}, поэтому записан этот максимум. Если вы занимались меньше, исправьте запись в разделе «Записи».",
+            format_duration(max_duration)
+        ));
+    }
+    output
+}
+
+pub async fn practice_stopwatch_handler(
+    bot: Bot,
+    msg: Message,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let session = get_practice_session(di.db_pool.clone(), msg.chat.id.0).await?;
+    let output = format_stopwatch(&di, msg.chat.id.0, session.as_ref()).await?;
+    bot.send_message(msg.chat.id, output)
+        .reply_markup(keyboards::make_stopwatch_inline_keyboard(session.as_ref()))
+        .await?;
+    Ok(())
+}
+
+/// Handles the stopwatch buttons: `stopwatch_start:`, `stopwatch_pause:`,
+/// `stopwatch_resume:`, `stopwatch_stop:` and `stopwatch_refresh:`.
+pub async fn stopwatch_callback_handler(
+    bot: Bot,
+    q: &CallbackQuery,
+    di: Arc<DI>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+
+    let Some((action, _)) = q.data.as_deref().and_then(|data| data.split_once(':')) else {
+        return Ok(());
+    };
+    let telegram_user_id: i64 = q.from.id.0.try_into().unwrap();
+    let now = Utc::now();
+    let max_duration = max_session_duration(&di);
+
+    let result = match action {
+        "stopwatch_start" => {
+            start_practice_session(di.db_pool.clone(), telegram_user_id, now, max_duration)
+                .await
+                .map(Some)
+        }
+        "stopwatch_pause" => pause_practice_session(di.db_pool.clone(), telegram_user_id, now)
+            .await
+            .map(Some),
+        "stopwatch_resume" => resume_practice_session(di.db_pool.clone(), telegram_user_id, now)
+            .await
+            .map(Some),
+        "stopwatch_stop" => {
+            match stop_practice_session(di.db_pool.clone(), telegram_user_id, now, max_duration)
+                .await
+            {
+                Ok(stopped) => {
+                    let request = bot.edit_message_text(
+                        message.chat.id,
+                        message.id,
+                        format_stopped(&stopped, max_duration),
+                    );
+                    match stopped.daily_practice_log_id {
+                        Some(daily_practice_log_id) => {
+                            request
+                                .reply_markup(keyboards::make_practice_note_inline_keyboard(
+                                    daily_practice_log_id,
+                                ))
+                                .await?
+                        }
+                        None => request.await?,
+                    };
+                }
+                Err(err) => {
+                    bot.edit_message_text(message.chat.id, message.id, err.to_string())
+                        .await?;
+                }
+            }
+            return Ok(());
+        }
+        _ => get_practice_session(di.db_pool.clone(), telegram_user_id).await,
+    };
+
+    match result {
+        Ok(session) => {
+            let output = format_stopwatch(&di, telegram_user_id, session.as_ref()).await?;
+            // Refreshing within the same minute leaves the text as it was,
+            // which Telegram rejects as "message is not modified".
+            if message.text() != Some(output.as_str()) {
+                bot.edit_message_text(message.chat.id, message.id, output)
+                    .reply_markup(keyboards::make_stopwatch_inline_keyboard(session.as_ref()))
+                    .await?;
+            }
+        }
+        Err(err) => {
+            bot.edit_message_text(message.chat.id, message.id, err.to_string())
+                .await?;
+        }
+    }
+    Ok(())
+}
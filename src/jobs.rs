@@ -0,0 +1,4 @@
+pub mod attendance_prompts;
+pub mod practice_digest;
+pub mod practice_reminder;
+pub mod practice_session;
@@ -0,0 +1,85 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{Datelike, Offset, Utc};
+use teloxide::{
+    payloads::SendMessageSetters,
+    prelude::*,
+    types::{ChatId, ParseMode},
+    utils::html,
+};
+
+use crate::{
+    bot::DI,
+    keyboards,
+    scheduler::{JobContext, JobHandler, cron::CronSchedule},
+    services::{
+        class_schedule::claim_due_attendance_prompts,
+        job::{JobTiming, schedule_job},
+    },
+    utils,
+};
+
+pub const ATTENDANCE_PROMPTS_JOB: &str = "attendance_prompts";
+
+/// Slots missed for longer than this (e.g. while the bot was down) are not
+/// asked about any more.
+const MAX_LATENESS: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Puts the every-minute check of the timetable in the job queue.
+pub async fn schedule(di: &DI) -> anyhow::Result<()> {
+    schedule_job(
+        di.db_pool.clone(),
+        ATTENDANCE_PROMPTS_JOB,
+        serde_json::json!({}),
+        None,
+        JobTiming::Cron {
+            schedule: "* * * * *".parse::<CronSchedule>()?,
+            utc_offset: Utc.fix(),
+        },
+        Some(ATTENDANCE_PROMPTS_JOB),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Sends a "did you attend?" prompt shortly after every scheduled class.
+pub fn handler(bot: Bot, di: Arc<DI>) -> impl JobHandler {
+    move |_: JobContext| {
+        let bot = bot.clone();
+        let di = di.clone();
+        async move { send_due_prompts(&bot, &di).await }
+    }
+}
+
+async fn send_due_prompts(bot: &Bot, di: &DI) -> anyhow::Result<()> {
+    let delay = Duration::from_secs(di.config.classes.attendance_prompt_delay_mins * 60);
+    let prompts =
+        claim_due_attendance_prompts(di.db_pool.clone(), Utc::now(), delay, MAX_LATENESS).await?;
+
+    for prompt in prompts {
+        let output = format!(
+            "🗓 <b>{name}</b>, {weekday} {date} в {time}\nВы были на занятии?",
+            name = html::escape(&prompt.class_name),
+            weekday = utils::get_russian_weekday_name(prompt.scheduled_for.weekday(), true),
+            date = utils::format_date(prompt.scheduled_for.date()),
+            time = prompt.scheduled_for.format("%H:%M")
+        );
+        let sent = bot
+            .send_message(ChatId(prompt.telegram_id), output)
+            .reply_markup(keyboards::make_attendance_inline_keyboard(
+                prompt.class_attendance_id,
+            ))
+            .parse_mode(ParseMode::Html)
+            .await;
+        // One blocked chat must not stop the prompts of other users.
+        if let Err(err) = sent {
+            log::warn!(
+                "Failed to send attendance prompt {} to {}: {}",
+                prompt.class_attendance_id,
+                prompt.telegram_id,
+                err
+            );
+        }
+    }
+    Ok(())
+}
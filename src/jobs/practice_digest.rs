@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use chrono::Datelike;
+use teloxide::{
+    payloads::SendMessageSetters,
+    prelude::*,
+    types::{ChatId, ParseMode},
+    utils::html,
+};
+
+use crate::{
+    bot::DI,
+    scheduler::{JobContext, JobHandler},
+    services::{
+        job::get_user_utc_offset,
+        practice_digest::{DigestPeriod, PracticeDigest, get_practice_digest},
+    },
+    utils,
+};
+
+/// Sends the user a summary of the previous week or month.
+pub fn handler(bot: Bot, di: Arc<DI>) -> impl JobHandler {
+    move |ctx: JobContext| {
+        let bot = bot.clone();
+        let di = di.clone();
+        async move {
+            let Some(telegram_id) = ctx.telegram_id else {
+                anyhow::bail!("practice digest has no user");
+            };
+            let period: DigestPeriod = serde_json::from_value(ctx.payload["period"].clone())?;
+            // The period is over when the job was due, not when it got to run.
+            let utc_offset = get_user_utc_offset(di.db_pool.clone(), telegram_id).await?;
+            let today = ctx.scheduled_for.with_timezone(&utc_offset).date_naive();
+
+            let digest =
+                get_practice_digest(di.db_pool.clone(), telegram_id, period, today).await?;
+            if digest.total_minutes == 0
+                && digest.previous_total_minutes == 0
+                && digest.classes.is_empty()
+            {
+                return Ok(());
+            }
+            bot.send_message(ChatId(telegram_id), format_digest(&digest))
+                .parse_mode(ParseMode::Html)
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+fn format_digest(digest: &PracticeDigest) -> String {
+    let mut output = match digest.period {
+        DigestPeriod::Week => format!(
+            "📊 <b>Итоги недели {} – {}</b>\n",
+            digest.from.format("%d.%m"),
+            digest.until.format("%d.%m")
+        ),
+        DigestPeriod::Month => format!(
+            "📊 <b>Итоги месяца: {} {}</b>\n",
+            utils::get_russian_month_name(digest.from.month()),
+            digest.from.year()
+        ),
+    };
+
+    if digest.total_minutes == 0 {
+        output.push_str("\nПрактик не было");
+    } else {
+        output.push_str(&format!(
+            "\nПрактика: {} мин за {} дн.\nВ среднем: {:.0} мин в день",
+            digest.total_minutes,
+            digest.practice_days,
+            digest.average_minutes()
+        ));
+        if let Some((date, minutes)) = digest.best_day {
+            output.push_str(&format!(
+                "\nЛучший день: {} {} — {} мин",
+                utils::get_russian_weekday_name(date.weekday(), true),
+                date.format("%d.%m"),
+                minutes
+            ));
+        }
+    }
+
+    let previous = match digest.period {
+        DigestPeriod::Week => "прошлой неделей",
+        DigestPeriod::Month => "прошлым месяцем",
+    };
+    let difference = i64::from(digest.total_minutes) - i64::from(digest.previous_total_minutes);
+    if digest.previous_total_minutes > 0 {
+        output.push_str(&format!(
+            "\nПо сравнению с {}: {:+} мин ({:+.0}%)",
+            previous,
+            difference,
+            difference as f32 * 100.0 / digest.previous_total_minutes as f32
+        ));
+    } else if digest.total_minutes > 0 {
+        output.push_str(&format!(
+            "\nПо сравнению с {}: {:+} мин",
+            previous, difference
+        ));
+    }
+
+    if !digest.classes.is_empty() {
+        output.push_str("\n\nЗанятия:");
+        for (name, attended) in &digest.classes {
+            output.push_str(&format!("\n• {} — {}", html::escape(name), attended));
+        }
+    }
+    output
+}
@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use teloxide::{payloads::SendMessageSetters, prelude::*, types::ChatId};
+
+use crate::{
+    bot::DI,
+    keyboards,
+    scheduler::{JobContext, JobHandler},
+    services::practice_reminder::is_practice_logged_today,
+};
+
+/// Nudges the user to log their practice unless they already did today.
+pub fn handler(bot: Bot, di: Arc<DI>) -> impl JobHandler {
+    move |ctx: JobContext| {
+        let bot = bot.clone();
+        let di = di.clone();
+        async move {
+            let Some(telegram_id) = ctx.telegram_id else {
+                anyhow::bail!("practice reminder has no user");
+            };
+            if is_practice_logged_today(di.db_pool.clone(), telegram_id, Utc::now()).await? {
+                return Ok(());
+            }
+            bot.send_message(
+                ChatId(telegram_id),
+                "🧘 Сегодня в дневнике практик ещё нет записей. Самое время позаниматься!",
+            )
+            .reply_markup(keyboards::make_practice_nudge_inline_keyboard())
+            .await?;
+            Ok(())
+        }
+    }
+}
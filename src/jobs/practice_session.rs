@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use teloxide::{prelude::*, types::ChatId};
+
+use crate::{
+    bot::DI,
+    errors::PracticeSessionNotStartedError,
+    handlers::practice_session::max_session_duration,
+    scheduler::{JobContext, JobHandler},
+    services::practice_session::stop_practice_session,
+};
+
+/// Stops a stopwatch left running past the maximum and tells the user what
+/// was logged.
+pub fn handler(bot: Bot, di: Arc<DI>) -> impl JobHandler {
+    move |ctx: JobContext| {
+        let bot = bot.clone();
+        let di = di.clone();
+        async move {
+            let Some(telegram_id) = ctx.telegram_id else {
+                anyhow::bail!("practice session timeout has no user");
+            };
+            let max_duration = max_session_duration(&di);
+            let stopped = match stop_practice_session(
+                di.db_pool.clone(),
+                telegram_id,
+                Utc::now(),
+                max_duration,
+            )
+            .await
+            {
+                Ok(stopped) => stopped,
+                // The user got to it first.
+                Err(err) if err.is::<PracticeSessionNotStartedError>() => return Ok(()),
+                Err(err) => return Err(err),
+            };
+            bot.send_message(
+                ChatId(telegram_id),
+                format!(
+                    "⏹ Секундомер остановлен автоматически: он шёл слишком долго. В дневник записано {} мин — если это неверно, исправьте запись в разделе «Записи».",
+                    stopped.minutes
+                ),
+            )
+            .await?;
+            Ok(())
+        }
+    }
+}
@@ -1,6 +1,18 @@
+use chrono::{Datelike, Days, FixedOffset, Months, NaiveDate, Weekday};
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, KeyboardButton, KeyboardMarkup};
 
-use crate::repositories::class::Class;
+use crate::{
+    repositories::{
+        class::Class, class_deduction_history::ClassDeductionHistory,
+        class_schedule::ClassSchedule, daily_practice_log::DailyPracticeLog,
+        practice_category::PracticeCategory, practice_session::PracticeSession,
+    },
+    utils,
+};
+
+/// Callback data of calendar cells that do nothing when tapped (headers,
+/// padding, disabled days).
+pub const CALENDAR_NOOP_CALLBACK: &str = "calendar_noop:";
 
 pub struct MainMenuButton {
     pub text: String,
@@ -60,3 +72,732 @@ pub fn make_class_list_inline_keyboard(
         .collect();
     make_inline_keyboard(buttons, row_size)
 }
+
+pub fn make_deduction_done_inline_keyboard(
+    class_deduction_history_id: i64,
+) -> InlineKeyboardMarkup {
+    let buttons = vec![
+        InlineButton {
+            text: "Отменить".to_string(),
+            callback_data: format!("undo_deduction:{}", class_deduction_history_id),
+        },
+        InlineButton {
+            text: "📝 Заметка".to_string(),
+            callback_data: format!("deduction_note:{}", class_deduction_history_id),
+        },
+    ];
+    make_inline_keyboard(buttons, 2)
+}
+
+/// One button per deduction of the shown history page, labelled with its
+/// date, to add or edit its note.
+pub fn make_deduction_notes_inline_keyboard(
+    histories: &[ClassDeductionHistory],
+    utc_offset: FixedOffset,
+    back_callback_data: String,
+) -> InlineKeyboardMarkup {
+    let mut buttons: Vec<InlineButton> = histories
+        .iter()
+        .map(|h| InlineButton {
+            text: match h.local_created_at(utc_offset) {
+                Some(dt) => dt.format("%d.%m %H:%M").to_string(),
+                None => h.created_at.clone(),
+            },
+            callback_data: format!("deduction_note:{}", h.class_deduction_history_id),
+        })
+        .collect();
+    buttons.push(InlineButton {
+        text: "« Назад".to_string(),
+        callback_data: back_callback_data,
+    });
+    make_inline_keyboard(buttons, 3)
+}
+
+pub fn make_delete_class_confirmation_inline_keyboard(
+    class_id: i64,
+    offer_archive: bool,
+) -> InlineKeyboardMarkup {
+    let mut buttons = vec![InlineButton {
+        text: "🗑 Удалить".to_string(),
+        callback_data: format!("delete_class_confirm:{}", class_id),
+    }];
+    if offer_archive {
+        buttons.push(InlineButton {
+            text: "📦 В архив".to_string(),
+            callback_data: format!("archive_class:{}", class_id),
+        });
+    }
+    buttons.push(InlineButton {
+        text: "Отмена".to_string(),
+        callback_data: format!("class_action_cancel:{}", class_id),
+    });
+    make_inline_keyboard(buttons, 2)
+}
+
+pub fn make_archived_class_inline_keyboard(class_id: i64) -> InlineKeyboardMarkup {
+    let buttons = vec![
+        InlineButton {
+            text: "История списаний".to_string(),
+            callback_data: format!("class_deduction_history:{}", class_id),
+        },
+        InlineButton {
+            text: "♻️ Восстановить".to_string(),
+            callback_data: format!("restore_class:{}", class_id),
+        },
+        InlineButton {
+            text: "🗑 Удалить".to_string(),
+            callback_data: format!("delete_class:{}", class_id),
+        },
+    ];
+    make_inline_keyboard(buttons, 2)
+}
+
+pub fn make_edit_class_inline_keyboard(class_id: i64) -> InlineKeyboardMarkup {
+    let buttons = vec![
+        InlineButton {
+            text: "➕ Пополнить".to_string(),
+            callback_data: format!("top_up:{}", class_id),
+        },
+        InlineButton {
+            text: "✏️ Название".to_string(),
+            callback_data: format!("rename_class:{}", class_id),
+        },
+        InlineButton {
+            text: "🔢 Количество".to_string(),
+            callback_data: format!("update_quantity:{}", class_id),
+        },
+        InlineButton {
+            text: "📅 Срок действия".to_string(),
+            callback_data: format!("set_valid_until:{}", class_id),
+        },
+        InlineButton {
+            text: "❄️ Заморозить".to_string(),
+            callback_data: format!("freeze_class:{}", class_id),
+        },
+        InlineButton {
+            text: "🔔 Порог остатка".to_string(),
+            callback_data: format!("set_low_balance:{}", class_id),
+        },
+        InlineButton {
+            text: "🗓 Расписание".to_string(),
+            callback_data: format!("class_schedule:{}", class_id),
+        },
+    ];
+    make_inline_keyboard(buttons, 2)
+}
+
+pub fn make_low_balance_inline_keyboard(class_id: i64) -> InlineKeyboardMarkup {
+    let buttons = vec![InlineButton {
+        text: "Пополнить".to_string(),
+        callback_data: format!("top_up:{}", class_id),
+    }];
+    make_inline_keyboard(buttons, 1)
+}
+
+pub fn make_class_schedule_inline_keyboard(
+    class_id: i64,
+    schedules: &[ClassSchedule],
+) -> InlineKeyboardMarkup {
+    let mut buttons: Vec<InlineButton> = schedules
+        .iter()
+        .map(|schedule| InlineButton {
+            text: format!("✖ {}", schedule),
+            callback_data: format!("schedule_remove:{}", schedule.class_schedule_id),
+        })
+        .collect();
+    buttons.push(InlineButton {
+        text: "➕ Добавить время".to_string(),
+        callback_data: format!("schedule_add:{}", class_id),
+    });
+    make_inline_keyboard(buttons, 2)
+}
+
+pub fn make_attendance_inline_keyboard(class_attendance_id: i64) -> InlineKeyboardMarkup {
+    let buttons = vec![
+        InlineButton {
+            text: "Был(а)".to_string(),
+            callback_data: format!("attended:{}", class_attendance_id),
+        },
+        InlineButton {
+            text: "Пропустил(а)".to_string(),
+            callback_data: format!("skipped:{}", class_attendance_id),
+        },
+    ];
+    make_inline_keyboard(buttons, 2)
+}
+
+/// Category step of a new practice entry. Buttons send
+/// `practice_category:{id|none}`.
+pub fn make_practice_category_pick_inline_keyboard(
+    categories: &[PracticeCategory],
+) -> InlineKeyboardMarkup {
+    let mut buttons: Vec<InlineButton> = categories
+        .iter()
+        .map(|category| InlineButton {
+            text: category.name.clone(),
+            callback_data: format!("practice_category:{}", category.practice_category_id),
+        })
+        .collect();
+    buttons.push(InlineButton {
+        text: "Без категории".to_string(),
+        callback_data: "practice_category:none".to_string(),
+    });
+    make_inline_keyboard(buttons, 2)
+}
+
+pub fn make_practice_categories_inline_keyboard(
+    categories: &[PracticeCategory],
+) -> InlineKeyboardMarkup {
+    let mut buttons: Vec<InlineButton> = categories
+        .iter()
+        .map(|category| InlineButton {
+            text: format!("✖ {}", category.name),
+            callback_data: format!("practice_category_delete:{}", category.practice_category_id),
+        })
+        .collect();
+    buttons.push(InlineButton {
+        text: "➕ Добавить категорию".to_string(),
+        callback_data: "practice_category_add:".to_string(),
+    });
+    make_inline_keyboard(buttons, 2)
+}
+
+/// One button per recent practice entry, labelled with its day and minutes.
+pub fn make_practice_entries_inline_keyboard(
+    entries: &[DailyPracticeLog],
+    utc_offset: FixedOffset,
+) -> InlineKeyboardMarkup {
+    let buttons = entries
+        .iter()
+        .map(|entry| InlineButton {
+            text: match entry.local_date(utc_offset) {
+                Some(date) => format!("{} — {} мин", date.format("%d.%m"), entry.minutes),
+                None => format!("{} мин", entry.minutes),
+            },
+            callback_data: format!("practice_entry:{}", entry.daily_practice_log_id),
+        })
+        .collect();
+    make_inline_keyboard(buttons, 2)
+}
+
+pub fn make_practice_entry_inline_keyboard(daily_practice_log_id: i64) -> InlineKeyboardMarkup {
+    let buttons = vec![
+        InlineButton {
+            text: "✏️ Изменить минуты".to_string(),
+            callback_data: format!("practice_entry_edit:{}", daily_practice_log_id),
+        },
+        InlineButton {
+            text: "🗑 Удалить".to_string(),
+            callback_data: format!("practice_entry_delete:{}", daily_practice_log_id),
+        },
+        InlineButton {
+            text: "📝 Заметка".to_string(),
+            callback_data: format!("practice_note:{}", daily_practice_log_id),
+        },
+        InlineButton {
+            text: "« Назад".to_string(),
+            callback_data: "practice_entries:".to_string(),
+        },
+    ];
+    make_inline_keyboard(buttons, 2)
+}
+
+pub fn make_delete_practice_entry_confirmation_inline_keyboard(
+    daily_practice_log_id: i64,
+) -> InlineKeyboardMarkup {
+    let buttons = vec![
+        InlineButton {
+            text: "🗑 Удалить".to_string(),
+            callback_data: format!("practice_entry_delete_confirm:{}", daily_practice_log_id),
+        },
+        InlineButton {
+            text: "Отмена".to_string(),
+            callback_data: format!("practice_entry:{}", daily_practice_log_id),
+        },
+    ];
+    make_inline_keyboard(buttons, 2)
+}
+
+/// Day of a new practice entry: buttons send `practice_on:YYYY-MM-DD`, the
+/// calendar button `practice_calendar:YYYY-MM`.
+pub fn make_practice_date_inline_keyboard(today: NaiveDate) -> InlineKeyboardMarkup {
+    let yesterday = today - Days::new(1);
+    let buttons = vec![
+        InlineButton {
+            text: "Сегодня".to_string(),
+            callback_data: format!("practice_on:{}", today),
+        },
+        InlineButton {
+            text: "Вчера".to_string(),
+            callback_data: format!("practice_on:{}", yesterday),
+        },
+        InlineButton {
+            text: "📅 Выбрать дату".to_string(),
+            callback_data: format!("practice_calendar:{}", today.format("%Y-%m")),
+        },
+    ];
+    make_inline_keyboard(buttons, 2)
+}
+
+/// Month navigation of the practice history plus a way to open the separate
+/// entries of a day.
+pub fn make_practice_month_inline_keyboard(
+    month: NaiveDate,
+    current_month: NaiveDate,
+) -> InlineKeyboardMarkup {
+    make_month_navigation_inline_keyboard(month, "practice_month:", current_month).append_row(vec![
+        InlineKeyboardButton::callback(
+            "🔍 Записи за день",
+            format!("practice_day_calendar:{}", month.format("%Y-%m")),
+        ),
+    ])
+}
+
+/// One button per entry of a day, labelled with its time, and a way back to
+/// the month.
+pub fn make_practice_day_inline_keyboard(
+    entries: &[DailyPracticeLog],
+    utc_offset: FixedOffset,
+) -> InlineKeyboardMarkup {
+    let mut buttons: Vec<InlineButton> = entries
+        .iter()
+        .map(|entry| InlineButton {
+            text: match entry.local_created_at(utc_offset) {
+                Some(dt) => format!("{} — {} мин", dt.format("%H:%M"), entry.minutes),
+                None => format!("{} мин", entry.minutes),
+            },
+            callback_data: format!("practice_entry:{}", entry.daily_practice_log_id),
+        })
+        .collect();
+    if let Some(month) = entries
+        .first()
+        .and_then(|entry| entry.local_date(utc_offset))
+    {
+        buttons.push(InlineButton {
+            text: "« К месяцу".to_string(),
+            callback_data: format!("practice_month:{}", month.format("%Y-%m")),
+        });
+    }
+    make_inline_keyboard(buttons, 2)
+}
+
+/// Stopwatch controls: `stopwatch_start:` when it is not running, otherwise
+/// pause or resume, stop and refresh of the shown time.
+pub fn make_stopwatch_inline_keyboard(session: Option<&PracticeSession>) -> InlineKeyboardMarkup {
+    let Some(session) = session else {
+        return make_inline_keyboard(
+            vec![InlineButton {
+                text: "▶ Начать практику".to_string(),
+                callback_data: "stopwatch_start:".to_string(),
+            }],
+            1,
+        );
+    };
+    let pause_button = if session.is_paused() {
+        InlineButton {
+            text: "▶ Продолжить".to_string(),
+            callback_data: "stopwatch_resume:".to_string(),
+        }
+    } else {
+        InlineButton {
+            text: "⏸ Пауза".to_string(),
+            callback_data: "stopwatch_pause:".to_string(),
+        }
+    };
+    let buttons = vec![
+        pause_button,
+        InlineButton {
+            text: "⏹ Закончить".to_string(),
+            callback_data: "stopwatch_stop:".to_string(),
+        },
+        InlineButton {
+            text: "🔄 Обновить".to_string(),
+            callback_data: "stopwatch_refresh:".to_string(),
+        },
+    ];
+    make_inline_keyboard(buttons, 2)
+}
+
+pub fn make_practice_note_skip_inline_keyboard() -> InlineKeyboardMarkup {
+    make_inline_keyboard(
+        vec![InlineButton {
+            text: "Пропустить".to_string(),
+            callback_data: "practice_note_skip:".to_string(),
+        }],
+        1,
+    )
+}
+
+pub fn make_practice_note_inline_keyboard(daily_practice_log_id: i64) -> InlineKeyboardMarkup {
+    make_inline_keyboard(
+        vec![InlineButton {
+            text: "📝 Заметка".to_string(),
+            callback_data: format!("practice_note:{}", daily_practice_log_id),
+        }],
+        1,
+    )
+}
+
+/// Under the daily practice nudge: log right away or be reminded later.
+pub fn make_practice_nudge_inline_keyboard() -> InlineKeyboardMarkup {
+    let mut keyboard = vec![vec![InlineKeyboardButton::callback(
+        "✍️ Записать практику",
+        "practice_add:",
+    )]];
+    keyboard.push(
+        [(30, "Через 30 мин"), (60, "Через 1 ч"), (180, "Через 3 ч")]
+            .into_iter()
+            .map(|(minutes, text)| {
+                InlineKeyboardButton::callback(text, format!("practice_snooze:{}", minutes))
+            })
+            .collect(),
+    );
+    InlineKeyboardMarkup::new(keyboard)
+}
+
+pub fn make_practice_goals_inline_keyboard() -> InlineKeyboardMarkup {
+    let buttons = vec![
+        InlineButton {
+            text: "Цель на день".to_string(),
+            callback_data: "practice_goal:daily".to_string(),
+        },
+        InlineButton {
+            text: "Цель на неделю".to_string(),
+            callback_data: "practice_goal:weekly".to_string(),
+        },
+    ];
+    make_inline_keyboard(buttons, 2)
+}
+
+/// Buttons send `export:{csv|xlsx}`.
+pub fn make_export_inline_keyboard() -> InlineKeyboardMarkup {
+    let buttons = vec![
+        InlineButton {
+            text: "CSV".to_string(),
+            callback_data: "export:csv".to_string(),
+        },
+        InlineButton {
+            text: "Excel (XLSX)".to_string(),
+            callback_data: "export:xlsx".to_string(),
+        },
+    ];
+    make_inline_keyboard(buttons, 2)
+}
+
+/// Buttons send `practice_chart:{days:N|weeks:N|year}`.
+pub fn make_practice_charts_inline_keyboard() -> InlineKeyboardMarkup {
+    let buttons = vec![
+        InlineButton {
+            text: "По дням: 7 дней".to_string(),
+            callback_data: "practice_chart:days:7".to_string(),
+        },
+        InlineButton {
+            text: "По дням: 30 дней".to_string(),
+            callback_data: "practice_chart:days:30".to_string(),
+        },
+        InlineButton {
+            text: "По неделям: 12 недель".to_string(),
+            callback_data: "practice_chart:weeks:12".to_string(),
+        },
+        InlineButton {
+            text: "По неделям: полгода".to_string(),
+            callback_data: "practice_chart:weeks:26".to_string(),
+        },
+        InlineButton {
+            text: "Календарь за год".to_string(),
+            callback_data: "practice_chart:year".to_string(),
+        },
+    ];
+    make_inline_keyboard(buttons, 2)
+}
+
+/// Buttons send `digest_toggle:{week|month}:{on|off}`.
+pub fn make_digest_settings_inline_keyboard(weekly: bool, monthly: bool) -> InlineKeyboardMarkup {
+    let button = |label: &str, period: &str, enabled: bool| InlineButton {
+        text: format!(
+            "{}: {}",
+            label,
+            if enabled {
+                "выключить"
+            } else {
+                "включить"
+            }
+        ),
+        callback_data: format!(
+            "digest_toggle:{}:{}",
+            period,
+            if enabled { "off" } else { "on" }
+        ),
+    };
+    let buttons = vec![
+        button("Неделя", "week", weekly),
+        button("Месяц", "month", monthly),
+    ];
+    make_inline_keyboard(buttons, 1)
+}
+
+pub fn make_practice_reminder_inline_keyboard(enabled: bool) -> InlineKeyboardMarkup {
+    let mut buttons = vec![InlineButton {
+        text: if enabled {
+            "Изменить время".to_string()
+        } else {
+            "Включить".to_string()
+        },
+        callback_data: "practice_reminder_set:".to_string(),
+    }];
+    if enabled {
+        buttons.push(InlineButton {
+            text: "Выключить".to_string(),
+            callback_data: "practice_reminder_off:".to_string(),
+        });
+    }
+    make_inline_keyboard(buttons, 2)
+}
+
+pub fn make_deduction_date_inline_keyboard(
+    class_id: i64,
+    today: NaiveDate,
+) -> InlineKeyboardMarkup {
+    let yesterday = today - Days::new(1);
+    let buttons = vec![
+        InlineButton {
+            text: "Сегодня".to_string(),
+            callback_data: format!("deduct_on:{}:{}", class_id, today),
+        },
+        InlineButton {
+            text: "Вчера".to_string(),
+            callback_data: format!("deduct_on:{}:{}", class_id, yesterday),
+        },
+        InlineButton {
+            text: "📅 Выбрать дату".to_string(),
+            callback_data: format!("deduct_calendar:{}:{}", class_id, today.format("%Y-%m")),
+        },
+    ];
+    make_inline_keyboard(buttons, 2)
+}
+
+/// "◀ / ▶" buttons sending `{callback_prefix}YYYY-MM` for the neighbouring
+/// months; there is no way forward past `current_month`.
+pub fn make_month_navigation_inline_keyboard(
+    month: NaiveDate,
+    callback_prefix: &str,
+    current_month: NaiveDate,
+) -> InlineKeyboardMarkup {
+    let previous_month = month - Months::new(1);
+    let next_month = month + Months::new(1);
+    let next_button = if next_month > current_month {
+        InlineKeyboardButton::callback(" ", CALENDAR_NOOP_CALLBACK)
+    } else {
+        InlineKeyboardButton::callback(
+            "▶",
+            format!("{}{}", callback_prefix, next_month.format("%Y-%m")),
+        )
+    };
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback(
+            "◀",
+            format!("{}{}", callback_prefix, previous_month.format("%Y-%m")),
+        ),
+        next_button,
+    ]])
+}
+
+/// Navigation under a page of the deduction history. Buttons send
+/// `history_page:{class_id}:{YYYY-MM|all}:{page}`, the notes button the same
+/// arguments under `history_notes:`.
+pub fn make_deduction_history_inline_keyboard(
+    class_id: i64,
+    month: Option<NaiveDate>,
+    page: u32,
+    has_next: bool,
+    has_entries: bool,
+    current_month: NaiveDate,
+) -> InlineKeyboardMarkup {
+    let filter = month.map_or("all".to_string(), |m| m.format("%Y-%m").to_string());
+    let page_button = |text: &str, month: &str, page: u32| {
+        InlineKeyboardButton::callback(
+            text,
+            format!("history_page:{}:{}:{}", class_id, month, page),
+        )
+    };
+
+    let mut keyboard: Vec<Vec<InlineKeyboardButton>> = vec![];
+
+    let mut paging = vec![];
+    if page > 0 {
+        paging.push(page_button("◀ Новее", &filter, page - 1));
+    }
+    if has_next {
+        paging.push(page_button("Старее ▶", &filter, page + 1));
+    }
+    if !paging.is_empty() {
+        keyboard.push(paging);
+    }
+
+    if has_entries {
+        keyboard.push(vec![InlineKeyboardButton::callback(
+            "📝 Заметки",
+            format!("history_notes:{}:{}:{}", class_id, filter, page),
+        )]);
+    }
+
+    match month {
+        None => keyboard.push(vec![page_button(
+            "🗓 По месяцам",
+            &current_month.format("%Y-%m").to_string(),
+            0,
+        )]),
+        Some(month) => {
+            let previous_month = month - Months::new(1);
+            let next_month = month + Months::new(1);
+            let mut row = vec![page_button(
+                &format!(
+                    "◀ {}",
+                    utils::get_russian_month_name(previous_month.month())
+                ),
+                &previous_month.format("%Y-%m").to_string(),
+                0,
+            )];
+            row.push(page_button("Всё время", "all", 0));
+            if next_month <= current_month {
+                row.push(page_button(
+                    &format!("{} ▶", utils::get_russian_month_name(next_month.month())),
+                    &next_month.format("%Y-%m").to_string(),
+                    0,
+                ));
+            }
+            keyboard.push(row);
+        }
+    }
+
+    InlineKeyboardMarkup::new(keyboard)
+}
+
+/// Builds a month calendar. Tapping a day sends `{day_callback_prefix}YYYY-MM-DD`,
+/// the arrows send `{month_callback_prefix}YYYY-MM` for the previous/next month.
+/// Days after `max_date` are shown but cannot be picked.
+pub fn make_calendar_inline_keyboard(
+    month: NaiveDate,
+    day_callback_prefix: &str,
+    month_callback_prefix: &str,
+    max_date: Option<NaiveDate>,
+) -> InlineKeyboardMarkup {
+    let first_day = month.with_day(1).unwrap_or(month);
+    let noop = |text: &str| InlineKeyboardButton::callback(text, CALENDAR_NOOP_CALLBACK);
+
+    let mut keyboard: Vec<Vec<InlineKeyboardButton>> = vec![vec![noop(&format!(
+        "{} {}",
+        utils::get_russian_month_name(first_day.month()),
+        first_day.year()
+    ))]];
+
+    keyboard.push(
+        [
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+            Weekday::Sat,
+            Weekday::Sun,
+        ]
+        .into_iter()
+        .map(|weekday| noop(utils::get_russian_weekday_name(weekday, true)))
+        .collect(),
+    );
+
+    let mut row: Vec<InlineKeyboardButton> = (0..first_day.weekday().num_days_from_monday())
+        .map(|_| noop(" "))
+        .collect();
+    let mut day = first_day;
+    while day.month() == first_day.month() {
+        let button = if max_date.is_some_and(|max| day > max) {
+            noop("·")
+        } else {
+            InlineKeyboardButton::callback(
+                day.day().to_string(),
+                format!("{}{}", day_callback_prefix, day),
+            )
+        };
+        row.push(button);
+        if row.len() == 7 {
+            keyboard.push(std::mem::take(&mut row));
+        }
+        day = day + Days::new(1);
+    }
+    if !row.is_empty() {
+        while row.len() < 7 {
+            row.push(noop(" "));
+        }
+        keyboard.push(row);
+    }
+
+    let previous_month = first_day - Months::new(1);
+    let next_month = first_day + Months::new(1);
+    let next_button = if max_date.is_some_and(|max| next_month > max) {
+        noop(" ")
+    } else {
+        InlineKeyboardButton::callback(
+            "▶",
+            format!("{}{}", month_callback_prefix, next_month.format("%Y-%m")),
+        )
+    };
+    keyboard.push(vec![
+        InlineKeyboardButton::callback(
+            "◀",
+            format!(
+                "{}{}",
+                month_callback_prefix,
+                previous_month.format("%Y-%m")
+            ),
+        ),
+        next_button,
+    ]);
+
+    InlineKeyboardMarkup::new(keyboard)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use teloxide::types::InlineKeyboardButtonKind;
+
+    use super::{CALENDAR_NOOP_CALLBACK, make_calendar_inline_keyboard};
+
+    fn callback_data(button: &teloxide::types::InlineKeyboardButton) -> &str {
+        match &button.kind {
+            InlineKeyboardButtonKind::CallbackData(data) => data,
+            _ => "",
+        }
+    }
+
+    #[test]
+    fn test_calendar_layout() {
+        // October 2026 starts on a Thursday and has 31 days.
+        let month = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+        let max_date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let keyboard = make_calendar_inline_keyboard(month, "day:", "month:", Some(max_date));
+        let rows = &keyboard.inline_keyboard;
+
+        // Title, weekdays, 5 weeks, navigation.
+        assert_eq!(rows.len(), 8);
+        assert!(rows[2..7].iter().all(|row| row.len() == 7));
+
+        let first_week = &rows[2];
+        assert_eq!(callback_data(&first_week[2]), CALENDAR_NOOP_CALLBACK);
+        assert_eq!(callback_data(&first_week[3]), "day:2026-10-01");
+
+        let days: Vec<&str> = rows[2..7]
+            .iter()
+            .flatten()
+            .map(callback_data)
+            .filter(|data| data.starts_with("day:"))
+            .collect();
+        assert_eq!(days.len(), 18);
+        assert_eq!(days.last(), Some(&"day:2026-10-18"));
+
+        let navigation = &rows[7];
+        assert_eq!(callback_data(&navigation[0]), "month:2026-09");
+        assert_eq!(callback_data(&navigation[1]), CALENDAR_NOOP_CALLBACK);
+    }
+}
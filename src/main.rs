@@ -1,13 +1,18 @@
 mod bot;
+mod charts;
 mod commands;
 mod config;
+mod dialogue_storage;
 mod errors;
 mod handlers;
+mod jobs;
 mod keyboards;
 mod middlewares;
 mod rate_limiter;
 mod repositories;
+mod scheduler;
 mod services;
+mod spreadsheet;
 mod state;
 mod uow;
 mod utils;
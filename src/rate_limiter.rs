@@ -27,20 +27,13 @@ pub struct UserRateLimit {
 }
 
 impl RedisRateLimiter {
-    pub async fn new(
-        redis_url: &str,
-        limit: u16,
-        interval: Duration,
-        namespace: &str,
-    ) -> anyhow::Result<Self> {
-        let client = redis::Client::open(redis_url)?;
-        let conn = ConnectionManager::new(client).await?;
-        Ok(Self {
+    pub fn new(conn: ConnectionManager, limit: u16, interval: Duration, namespace: &str) -> Self {
+        Self {
             conn,
             limit,
             interval,
             namespace: namespace.into(),
-        })
+        }
     }
 
     fn build_key(&self, user_id: u64) -> String {
@@ -1,4 +1,11 @@
 pub mod class;
+pub mod class_attendance;
 pub mod class_deduction_history;
+pub mod class_freeze;
+pub mod class_pack;
+pub mod class_schedule;
 pub mod daily_practice_log;
+pub mod job;
+pub mod practice_category;
+pub mod practice_session;
 pub mod user;
@@ -1,13 +1,34 @@
 use std::{fmt, ops::DerefMut};
 
+use chrono::NaiveDate;
 use sqlx::{SqliteConnection, prelude::FromRow};
 
-#[derive(FromRow)]
+#[derive(Debug, FromRow)]
 pub struct Class {
     pub name: String,
     pub class_id: i64,
     pub user_id: i64,
+    /// Classes left in the packs still valid on the day the class was read.
     pub quantity: u8,
+    pub updated_at: String,
+    pub archived_at: Option<String>,
+    pub valid_until: Option<String>,
+    pub low_balance_threshold: Option<u8>,
+}
+
+impl Class {
+    pub fn valid_until_date(&self) -> Option<NaiveDate> {
+        self.valid_until.as_deref().and_then(|d| d.parse().ok())
+    }
+
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.valid_until_date().is_some_and(|d| d < today)
+    }
+
+    pub fn is_low_balance(&self) -> bool {
+        self.low_balance_threshold
+            .is_some_and(|threshold| self.quantity <= threshold)
+    }
 }
 
 impl fmt::Display for Class {
@@ -16,6 +37,16 @@ impl fmt::Display for Class {
     }
 }
 
+/// The columns of [`Class`]. The balance is summed over the packs still
+/// valid on the day bound to the placeholder, so that it drops as soon as a
+/// pack expires rather than on the next change to the class.
+const CLASS_COLUMNS: &str = "class_id, name, user_id, updated_at, archived_at, valid_until,
+    low_balance_threshold,
+    min(255, (select coalesce(sum(p.remaining), 0)
+              from class_pack p
+              where p.class_id = class.class_id
+              and (p.valid_until is null or p.valid_until >= ?))) as quantity";
+
 pub struct ClassRepository<'a> {
     conn: &'a mut SqliteConnection,
 }
@@ -29,14 +60,16 @@ impl<'a> ClassRepository<'a> {
         &mut self,
         name: String,
         quantity: i64,
+        valid_until: Option<String>,
         user_id: i64,
     ) -> anyhow::Result<i64, sqlx::Error> {
         let result = sqlx::query(
-            "insert into class (name, quantity, user_id)
-             values (?, ?, ?)",
+            "insert into class (name, quantity, valid_until, user_id)
+             values (?, ?, ?, ?)",
         )
         .bind(name)
         .bind(quantity)
+        .bind(valid_until)
         .bind(user_id)
         .execute(self.conn.deref_mut())
         .await?;
@@ -45,15 +78,84 @@ impl<'a> ClassRepository<'a> {
         Ok(class_id)
     }
 
-    pub async fn update_quantity(&mut self, class_id: i64, quantity: u8) -> anyhow::Result<Class> {
-        let updated_class = sqlx::query_as::<_, Class>(
+    pub async fn update_quantity(
+        &mut self,
+        class_id: i64,
+        quantity: u8,
+        today: NaiveDate,
+    ) -> anyhow::Result<Class> {
+        let updated_class = sqlx::query_as::<_, Class>(&format!(
             "update class
             set quantity = ?
             where class_id = ?
-            returning class_id, name, quantity, user_id",
-        )
+            returning {CLASS_COLUMNS}"
+        ))
         .bind(quantity)
         .bind(class_id)
+        .bind(today.to_string())
+        .fetch_one(self.conn.deref_mut())
+        .await?;
+
+        Ok(updated_class)
+    }
+
+    pub async fn rename(
+        &mut self,
+        class_id: i64,
+        name: String,
+        today: NaiveDate,
+    ) -> anyhow::Result<Class, sqlx::Error> {
+        let updated_class = sqlx::query_as::<_, Class>(&format!(
+            "update class
+            set name = ?
+            where class_id = ?
+            returning {CLASS_COLUMNS}"
+        ))
+        .bind(name)
+        .bind(class_id)
+        .bind(today.to_string())
+        .fetch_one(self.conn.deref_mut())
+        .await?;
+
+        Ok(updated_class)
+    }
+
+    pub async fn set_valid_until(
+        &mut self,
+        class_id: i64,
+        valid_until: Option<String>,
+        today: NaiveDate,
+    ) -> anyhow::Result<Class> {
+        let updated_class = sqlx::query_as::<_, Class>(&format!(
+            "update class
+            set valid_until = ?
+            where class_id = ?
+            returning {CLASS_COLUMNS}"
+        ))
+        .bind(valid_until)
+        .bind(class_id)
+        .bind(today.to_string())
+        .fetch_one(self.conn.deref_mut())
+        .await?;
+
+        Ok(updated_class)
+    }
+
+    pub async fn set_low_balance_threshold(
+        &mut self,
+        class_id: i64,
+        low_balance_threshold: Option<u8>,
+        today: NaiveDate,
+    ) -> anyhow::Result<Class> {
+        let updated_class = sqlx::query_as::<_, Class>(&format!(
+            "update class
+            set low_balance_threshold = ?
+            where class_id = ?
+            returning {CLASS_COLUMNS}"
+        ))
+        .bind(low_balance_threshold)
+        .bind(class_id)
+        .bind(today.to_string())
         .fetch_one(self.conn.deref_mut())
         .await?;
 
@@ -64,12 +166,14 @@ impl<'a> ClassRepository<'a> {
         &mut self,
         class_id: i64,
         user_id: i64,
+        today: NaiveDate,
     ) -> anyhow::Result<Option<Class>> {
-        let class: Option<Class> = sqlx::query_as::<_, Class>(
-            "select class_id, name, quantity, user_id
+        let class: Option<Class> = sqlx::query_as::<_, Class>(&format!(
+            "select {CLASS_COLUMNS}
                  from class
-                 where class_id = ? and user_id = ?",
-        )
+                 where class_id = ? and user_id = ?"
+        ))
+        .bind(today.to_string())
         .bind(class_id)
         .bind(user_id)
         .fetch_optional(self.conn.deref_mut())
@@ -78,15 +182,88 @@ impl<'a> ClassRepository<'a> {
         Ok(class)
     }
 
-    pub async fn get_user_classes(&mut self, user_id: i64) -> anyhow::Result<Vec<Class>> {
-        let classes: Vec<Class> = sqlx::query_as::<_, Class>(
-            "select class_id, name, quantity, user_id
+    pub async fn get_user_classes(
+        &mut self,
+        user_id: i64,
+        today: NaiveDate,
+    ) -> anyhow::Result<Vec<Class>> {
+        let classes: Vec<Class> = sqlx::query_as::<_, Class>(&format!(
+            "select {CLASS_COLUMNS}
                  from class
-                 where user_id = ?",
-        )
+                 where user_id = ?
+                 and archived_at is null"
+        ))
+        .bind(today.to_string())
         .bind(user_id)
         .fetch_all(self.conn.deref_mut())
         .await?;
         Ok(classes)
     }
+
+    pub async fn get_user_archived_classes(
+        &mut self,
+        user_id: i64,
+        today: NaiveDate,
+    ) -> anyhow::Result<Vec<Class>> {
+        let classes: Vec<Class> = sqlx::query_as::<_, Class>(&format!(
+            "select {CLASS_COLUMNS}
+                 from class
+                 where user_id = ?
+                 and archived_at is not null"
+        ))
+        .bind(today.to_string())
+        .bind(user_id)
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+        Ok(classes)
+    }
+
+    /// Active and archived classes alike, by name.
+    pub async fn get_all_user_classes(
+        &mut self,
+        user_id: i64,
+        today: NaiveDate,
+    ) -> anyhow::Result<Vec<Class>> {
+        let classes: Vec<Class> = sqlx::query_as::<_, Class>(&format!(
+            "select {CLASS_COLUMNS}
+                 from class
+                 where user_id = ?
+                 order by name"
+        ))
+        .bind(today.to_string())
+        .bind(user_id)
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+        Ok(classes)
+    }
+
+    pub async fn set_archived(
+        &mut self,
+        class_id: i64,
+        archived: bool,
+        today: NaiveDate,
+    ) -> anyhow::Result<Class> {
+        let updated_class = sqlx::query_as::<_, Class>(&format!(
+            "update class
+            set archived_at = case when ? then current_timestamp else null end
+            where class_id = ?
+            returning {CLASS_COLUMNS}"
+        ))
+        .bind(archived)
+        .bind(class_id)
+        .bind(today.to_string())
+        .fetch_one(self.conn.deref_mut())
+        .await?;
+
+        Ok(updated_class)
+    }
+
+    pub async fn delete(&mut self, class_id: i64) -> anyhow::Result<()> {
+        sqlx::query("delete from class where class_id = ?")
+            .bind(class_id)
+            .execute(self.conn.deref_mut())
+            .await?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,118 @@
+use std::ops::DerefMut;
+
+use chrono::NaiveDateTime;
+use sqlx::{SqliteConnection, prelude::FromRow};
+
+/// Format of `scheduled_for`: a local date and time without seconds.
+pub const SCHEDULED_FOR_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AttendanceStatus {
+    Pending,
+    Attended,
+    Skipped,
+}
+
+impl AttendanceStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AttendanceStatus::Pending => "pending",
+            AttendanceStatus::Attended => "attended",
+            AttendanceStatus::Skipped => "skipped",
+        }
+    }
+}
+
+#[derive(FromRow)]
+pub struct ClassAttendance {
+    pub class_attendance_id: i64,
+    pub class_id: i64,
+    pub scheduled_for: String,
+    pub status: String,
+}
+
+impl ClassAttendance {
+    pub fn scheduled_for_datetime(&self) -> Option<NaiveDateTime> {
+        NaiveDateTime::parse_from_str(&self.scheduled_for, SCHEDULED_FOR_FORMAT).ok()
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.status == AttendanceStatus::Pending.as_str()
+    }
+}
+
+pub struct ClassAttendanceRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl<'a> ClassAttendanceRepository<'a> {
+    pub fn new(conn: &'a mut SqliteConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Records a pending prompt for the slot. Returns `None` if the slot was
+    /// already prompted, so every slot is asked about once.
+    pub async fn create_pending(
+        &mut self,
+        class_id: i64,
+        user_id: i64,
+        class_schedule_id: i64,
+        scheduled_for: NaiveDateTime,
+    ) -> anyhow::Result<Option<i64>> {
+        let result = sqlx::query(
+            "insert or ignore into class_attendance
+             (class_id, user_id, class_schedule_id, scheduled_for)
+             values (?, ?, ?, ?)",
+        )
+        .bind(class_id)
+        .bind(user_id)
+        .bind(class_schedule_id)
+        .bind(scheduled_for.format(SCHEDULED_FOR_FORMAT).to_string())
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        Ok(Some(result.last_insert_rowid()))
+    }
+
+    pub async fn get_user_attendance_by_id(
+        &mut self,
+        class_attendance_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Option<ClassAttendance>> {
+        let attendance: Option<ClassAttendance> = sqlx::query_as::<_, ClassAttendance>(
+            "select class_attendance_id, class_id, scheduled_for, status
+             from class_attendance
+             where class_attendance_id = ?
+             and user_id = ?",
+        )
+        .bind(class_attendance_id)
+        .bind(user_id)
+        .fetch_optional(self.conn.deref_mut())
+        .await?;
+
+        Ok(attendance)
+    }
+
+    pub async fn set_status(
+        &mut self,
+        class_attendance_id: i64,
+        status: AttendanceStatus,
+        class_deduction_history_id: Option<i64>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "update class_attendance
+             set status = ?, class_deduction_history_id = ?, answered_at = current_timestamp
+             where class_attendance_id = ?",
+        )
+        .bind(status.as_str())
+        .bind(class_deduction_history_id)
+        .bind(class_attendance_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(())
+    }
+}
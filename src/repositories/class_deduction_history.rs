@@ -1,31 +1,39 @@
-use std::{fmt, ops::DerefMut};
+use std::ops::DerefMut;
 
-use chrono::{Datelike, NaiveDateTime};
+use chrono::{Datelike, FixedOffset, NaiveDate, NaiveDateTime, TimeZone};
 use sqlx::{SqliteConnection, prelude::FromRow};
 
 use crate::utils;
 
-#[derive(FromRow)]
+#[derive(Debug, FromRow)]
 pub struct ClassDeductionHistory {
+    pub class_deduction_history_id: i64,
+    pub class_id: i64,
     pub created_at: String,
+    pub recorded_at: String,
+    pub class_pack_id: Option<i64>,
+    pub note: Option<String>,
 }
 
-impl fmt::Display for ClassDeductionHistory {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let dt = match NaiveDateTime::parse_from_str(&self.created_at, "%Y-%m-%d %H:%M:%S") {
-            Ok(dt) => dt,
-            Err(_) => {
-                // Fallback: if the datetime cannot be parsed, print the raw value without panicking.
-                return write!(f, "{}", self.created_at);
-            }
-        };
-        let formatted_date = dt.format("%d.%m.%Y %H:%M").to_string();
-        write!(
-            f,
-            "{} ({})",
-            formatted_date,
-            utils::get_russian_weekday_name(dt.weekday(), true)
-        )
+impl ClassDeductionHistory {
+    /// `created_at` at the given offset, usually the user's; it is stored as
+    /// UTC.
+    pub fn local_created_at(&self, utc_offset: FixedOffset) -> Option<NaiveDateTime> {
+        let dt = NaiveDateTime::parse_from_str(&self.created_at, "%Y-%m-%d %H:%M:%S").ok()?;
+        Some(utc_offset.from_utc_datetime(&dt).naive_local())
+    }
+
+    /// `ДД.ММ.ГГГГ ЧЧ:ММ (Дн)`, taken at the given offset.
+    pub fn describe(&self, utc_offset: FixedOffset) -> String {
+        match self.local_created_at(utc_offset) {
+            Some(dt) => format!(
+                "{} ({})",
+                dt.format("%d.%m.%Y %H:%M"),
+                utils::get_russian_weekday_name(dt.weekday(), true)
+            ),
+            // Fallback: if the datetime cannot be parsed, print the raw value without panicking.
+            None => self.created_at.clone(),
+        }
     }
 }
 
@@ -38,13 +46,23 @@ impl<'a> ClassDeductionHistoryRepository<'a> {
         Self { conn }
     }
 
-    pub async fn create(&mut self, class_id: i64, user_id: i64) -> anyhow::Result<i64> {
+    /// `created_at` defaults to the current timestamp when not given.
+    pub async fn create(
+        &mut self,
+        class_id: i64,
+        user_id: i64,
+        class_pack_id: Option<i64>,
+        created_at: Option<String>,
+    ) -> anyhow::Result<i64> {
         let result = sqlx::query(
-            "insert into class_deduction_history (class_id, user_id)
-                 values (?, ?)",
+            "insert into class_deduction_history
+                 (class_id, user_id, class_pack_id, created_at, recorded_at)
+                 values (?, ?, ?, coalesce(?, current_timestamp), current_timestamp)",
         )
         .bind(class_id)
         .bind(user_id)
+        .bind(class_pack_id)
+        .bind(created_at)
         .execute(self.conn.deref_mut())
         .await?;
 
@@ -52,22 +70,183 @@ impl<'a> ClassDeductionHistoryRepository<'a> {
         Ok(id)
     }
 
+    /// Newest first. `days` limits the day of the class, both ends inclusive;
+    /// days are taken at `utc_offset`, as the history shows them.
     pub async fn get_histories(
         &mut self,
         class_id: i64,
         user_id: i64,
+        days: Option<(NaiveDate, NaiveDate)>,
+        utc_offset: FixedOffset,
+        limit: u32,
+        offset: u32,
     ) -> anyhow::Result<Vec<ClassDeductionHistory>> {
         let histories: Vec<ClassDeductionHistory> = sqlx::query_as::<_, ClassDeductionHistory>(
-            "select created_at
+            "select class_deduction_history_id, class_id, created_at,
+                 coalesce(recorded_at, created_at) as recorded_at, class_pack_id, note
              from class_deduction_history
              where user_id = ?
-             and class_id = ?",
+             and class_id = ?
+             and date(created_at, ?)
+                 between coalesce(?, '0000-01-01') and coalesce(?, '9999-12-31')
+             order by created_at desc, class_deduction_history_id desc
+             limit ? offset ?",
         )
         .bind(user_id)
         .bind(class_id)
+        .bind(utils::sqlite_offset_modifier(utc_offset))
+        .bind(days.map(|(from, _)| from.to_string()))
+        .bind(days.map(|(_, until)| until.to_string()))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(histories)
+    }
+
+    /// Every deduction of the user across all classes, oldest first.
+    pub async fn get_user_histories(
+        &mut self,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<ClassDeductionHistory>> {
+        let histories: Vec<ClassDeductionHistory> = sqlx::query_as::<_, ClassDeductionHistory>(
+            "select class_deduction_history_id, class_id, created_at,
+                 coalesce(recorded_at, created_at) as recorded_at, class_pack_id, note
+             from class_deduction_history
+             where user_id = ?
+             order by created_at, class_deduction_history_id",
+        )
+        .bind(user_id)
         .fetch_all(self.conn.deref_mut())
         .await?;
 
         Ok(histories)
     }
+
+    pub async fn count_histories(
+        &mut self,
+        class_id: i64,
+        user_id: i64,
+        days: Option<(NaiveDate, NaiveDate)>,
+        utc_offset: FixedOffset,
+    ) -> anyhow::Result<u32> {
+        let (count,): (u32,) = sqlx::query_as(
+            "select count(*)
+             from class_deduction_history
+             where user_id = ?
+             and class_id = ?
+             and date(created_at, ?)
+                 between coalesce(?, '0000-01-01') and coalesce(?, '9999-12-31')",
+        )
+        .bind(user_id)
+        .bind(class_id)
+        .bind(utils::sqlite_offset_modifier(utc_offset))
+        .bind(days.map(|(from, _)| from.to_string()))
+        .bind(days.map(|(_, until)| until.to_string()))
+        .fetch_one(self.conn.deref_mut())
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Deductions per class on the days `from..=until` at `utc_offset`, most
+    /// attended first.
+    pub async fn count_by_class(
+        &mut self,
+        user_id: i64,
+        from: NaiveDate,
+        until: NaiveDate,
+        utc_offset: FixedOffset,
+    ) -> anyhow::Result<Vec<(String, u32)>> {
+        let counts: Vec<(String, u32)> = sqlx::query_as(
+            "select c.name, count(*) as attended
+             from class_deduction_history h
+             join class c on c.class_id = h.class_id
+             where h.user_id = ?
+             and date(h.created_at, ?) between ? and ?
+             group by c.class_id
+             order by attended desc, c.name",
+        )
+        .bind(user_id)
+        .bind(utils::sqlite_offset_modifier(utc_offset))
+        .bind(from.to_string())
+        .bind(until.to_string())
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(counts)
+    }
+
+    pub async fn get_user_history_by_id(
+        &mut self,
+        class_deduction_history_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Option<ClassDeductionHistory>> {
+        let history: Option<ClassDeductionHistory> = sqlx::query_as::<_, ClassDeductionHistory>(
+            "select class_deduction_history_id, class_id, created_at,
+                 coalesce(recorded_at, created_at) as recorded_at, class_pack_id, note
+             from class_deduction_history
+             where class_deduction_history_id = ?
+             and user_id = ?",
+        )
+        .bind(class_deduction_history_id)
+        .bind(user_id)
+        .fetch_optional(self.conn.deref_mut())
+        .await?;
+
+        Ok(history)
+    }
+
+    pub async fn get_last_history(
+        &mut self,
+        class_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Option<ClassDeductionHistory>> {
+        let history: Option<ClassDeductionHistory> = sqlx::query_as::<_, ClassDeductionHistory>(
+            "select class_deduction_history_id, class_id, created_at,
+                 coalesce(recorded_at, created_at) as recorded_at, class_pack_id, note
+             from class_deduction_history
+             where user_id = ?
+             and class_id = ?
+             order by class_deduction_history_id desc
+             limit 1",
+        )
+        .bind(user_id)
+        .bind(class_id)
+        .fetch_optional(self.conn.deref_mut())
+        .await?;
+
+        Ok(history)
+    }
+
+    pub async fn set_note(
+        &mut self,
+        class_deduction_history_id: i64,
+        note: Option<String>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "update class_deduction_history
+             set note = ?
+             where class_deduction_history_id = ?",
+        )
+        .bind(note)
+        .bind(class_deduction_history_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete(&mut self, class_deduction_history_id: i64) -> anyhow::Result<()> {
+        sqlx::query(
+            "delete from class_deduction_history
+             where class_deduction_history_id = ?",
+        )
+        .bind(class_deduction_history_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,112 @@
+use std::{fmt, ops::DerefMut};
+
+use chrono::NaiveDate;
+use sqlx::{SqliteConnection, prelude::FromRow};
+
+use crate::utils;
+
+#[derive(FromRow)]
+pub struct ClassFreeze {
+    pub frozen_from: String,
+    pub frozen_until: String,
+    pub days: i64,
+}
+
+impl fmt::Display for ClassFreeze {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (
+            self.frozen_from.parse::<NaiveDate>(),
+            self.frozen_until.parse::<NaiveDate>(),
+        ) {
+            (Ok(from), Ok(until)) => write!(
+                f,
+                "❄️ {} – {} ({} дн.)",
+                utils::format_date(from),
+                utils::format_date(until),
+                self.days
+            ),
+            // Fallback: if the dates cannot be parsed, print the raw values without panicking.
+            _ => write!(
+                f,
+                "❄️ {} – {} ({} дн.)",
+                self.frozen_from, self.frozen_until, self.days
+            ),
+        }
+    }
+}
+
+pub struct ClassFreezeRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl<'a> ClassFreezeRepository<'a> {
+    pub fn new(conn: &'a mut SqliteConnection) -> Self {
+        Self { conn }
+    }
+
+    pub async fn create(
+        &mut self,
+        class_id: i64,
+        user_id: i64,
+        frozen_from: NaiveDate,
+        frozen_until: NaiveDate,
+        days: i64,
+    ) -> anyhow::Result<i64> {
+        let result = sqlx::query(
+            "insert into class_freeze (class_id, user_id, frozen_from, frozen_until, days)
+             values (?, ?, ?, ?, ?)",
+        )
+        .bind(class_id)
+        .bind(user_id)
+        .bind(frozen_from.to_string())
+        .bind(frozen_until.to_string())
+        .bind(days)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        let id = result.last_insert_rowid();
+        Ok(id)
+    }
+
+    /// The freeze that is still running on `on`, if any.
+    pub async fn get_active_freeze(
+        &mut self,
+        class_id: i64,
+        on: NaiveDate,
+    ) -> anyhow::Result<Option<ClassFreeze>> {
+        let freeze: Option<ClassFreeze> = sqlx::query_as::<_, ClassFreeze>(
+            "select frozen_from, frozen_until, days
+             from class_freeze
+             where class_id = ?
+             and frozen_until >= ?
+             order by frozen_until desc
+             limit 1",
+        )
+        .bind(class_id)
+        .bind(on.to_string())
+        .fetch_optional(self.conn.deref_mut())
+        .await?;
+
+        Ok(freeze)
+    }
+
+    pub async fn get_freezes(
+        &mut self,
+        class_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<ClassFreeze>> {
+        let freezes: Vec<ClassFreeze> = sqlx::query_as::<_, ClassFreeze>(
+            "select frozen_from, frozen_until, days
+             from class_freeze
+             where user_id = ?
+             and class_id = ?
+             order by frozen_from",
+        )
+        .bind(user_id)
+        .bind(class_id)
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(freezes)
+    }
+}
@@ -0,0 +1,291 @@
+use std::{fmt, ops::DerefMut};
+
+use chrono::NaiveDate;
+use sqlx::{SqliteConnection, prelude::FromRow};
+
+use crate::utils;
+
+#[derive(FromRow)]
+pub struct ClassPack {
+    pub class_pack_id: i64,
+    pub quantity: i64,
+    pub remaining: i64,
+    pub purchased_on: String,
+    pub valid_until: Option<String>,
+    pub price: Option<i64>,
+    pub currency: Option<String>,
+}
+
+/// What a pack cost, `amount` being in minor units (kopecks, cents).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PackPrice {
+    pub amount: i64,
+    pub currency: String,
+}
+
+impl fmt::Display for PackPrice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", utils::format_money(self.amount), self.currency)
+    }
+}
+
+/// A priced pack together with the number of deductions taken from it.
+#[derive(FromRow)]
+pub struct PricedPackUsage {
+    pub class_id: i64,
+    pub name: String,
+    pub quantity: i64,
+    pub remaining: i64,
+    pub price: i64,
+    pub currency: String,
+    pub attended: i64,
+}
+
+#[derive(FromRow)]
+pub struct MonthlySpend {
+    pub month: String,
+    pub currency: String,
+    pub total: i64,
+}
+
+impl ClassPack {
+    pub fn valid_until_date(&self) -> Option<NaiveDate> {
+        self.valid_until.as_deref().and_then(|d| d.parse().ok())
+    }
+
+    pub fn is_expired(&self, on: NaiveDate) -> bool {
+        self.valid_until_date().is_some_and(|d| d < on)
+    }
+
+    pub fn pack_price(&self) -> Option<PackPrice> {
+        match (self.price, &self.currency) {
+            (Some(amount), Some(currency)) => Some(PackPrice {
+                amount,
+                currency: currency.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ClassPack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let purchased_on = match self.purchased_on.parse::<NaiveDate>() {
+            Ok(d) => utils::format_date(d),
+            // Fallback: if the date cannot be parsed, print the raw value without panicking.
+            Err(_) => self.purchased_on.clone(),
+        };
+        write!(
+            f,
+            "{} шт. от {} — осталось {}",
+            self.quantity, purchased_on, self.remaining
+        )?;
+        if let Some(valid_until) = self.valid_until_date() {
+            write!(f, ", до {}", utils::format_date(valid_until))?;
+        }
+        if let Some(price) = self.pack_price() {
+            write!(f, ", {}", price)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct ClassPackRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl<'a> ClassPackRepository<'a> {
+    pub fn new(conn: &'a mut SqliteConnection) -> Self {
+        Self { conn }
+    }
+
+    pub async fn create(
+        &mut self,
+        class_id: i64,
+        user_id: i64,
+        quantity: i64,
+        purchased_on: NaiveDate,
+        valid_until: Option<NaiveDate>,
+        price: Option<&PackPrice>,
+    ) -> anyhow::Result<i64> {
+        let result = sqlx::query(
+            "insert into class_pack
+             (class_id, user_id, quantity, remaining, purchased_on, valid_until, price, currency)
+             values (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(class_id)
+        .bind(user_id)
+        .bind(quantity)
+        .bind(quantity)
+        .bind(purchased_on.to_string())
+        .bind(valid_until.map(|d| d.to_string()))
+        .bind(price.map(|p| p.amount))
+        .bind(price.map(|p| p.currency.as_str()))
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        let class_pack_id = result.last_insert_rowid();
+        Ok(class_pack_id)
+    }
+
+    /// Packs with classes left, oldest purchase first.
+    pub async fn get_open_packs(&mut self, class_id: i64) -> anyhow::Result<Vec<ClassPack>> {
+        let packs: Vec<ClassPack> = sqlx::query_as::<_, ClassPack>(
+            "select class_pack_id, quantity, remaining, purchased_on, valid_until, price, currency
+             from class_pack
+             where class_id = ?
+             and remaining > 0
+             order by purchased_on, class_pack_id",
+        )
+        .bind(class_id)
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(packs)
+    }
+
+    pub async fn get_user_packs(
+        &mut self,
+        class_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<ClassPack>> {
+        let packs: Vec<ClassPack> = sqlx::query_as::<_, ClassPack>(
+            "select class_pack_id, quantity, remaining, purchased_on, valid_until, price, currency
+             from class_pack
+             where class_id = ?
+             and user_id = ?
+             order by purchased_on, class_pack_id",
+        )
+        .bind(class_id)
+        .bind(user_id)
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(packs)
+    }
+
+    /// Classes left in the packs still valid `on` the given day.
+    pub async fn get_balance(&mut self, class_id: i64, on: NaiveDate) -> anyhow::Result<i64> {
+        let (balance,): (i64,) = sqlx::query_as(
+            "select coalesce(sum(remaining), 0)
+             from class_pack
+             where class_id = ?
+             and (valid_until is null or valid_until >= ?)",
+        )
+        .bind(class_id)
+        .bind(on.to_string())
+        .fetch_one(self.conn.deref_mut())
+        .await?;
+
+        Ok(balance)
+    }
+
+    pub async fn consume(&mut self, class_pack_id: i64) -> anyhow::Result<()> {
+        sqlx::query(
+            "update class_pack
+             set remaining = remaining - 1
+             where class_pack_id = ?",
+        )
+        .bind(class_pack_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn restore(&mut self, class_pack_id: i64) -> anyhow::Result<()> {
+        sqlx::query(
+            "update class_pack
+             set remaining = remaining + 1
+             where class_pack_id = ?",
+        )
+        .bind(class_pack_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_remaining(
+        &mut self,
+        class_pack_id: i64,
+        remaining: i64,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "update class_pack
+             set remaining = ?
+             where class_pack_id = ?",
+        )
+        .bind(remaining)
+        .bind(class_pack_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Moves the expiry date of every pack that still has classes left.
+    pub async fn extend_validity(&mut self, class_id: i64, days: u16) -> anyhow::Result<()> {
+        sqlx::query(
+            "update class_pack
+             set valid_until = date(valid_until, '+' || ? || ' days')
+             where class_id = ?
+             and remaining > 0
+             and valid_until is not null",
+        )
+        .bind(days)
+        .bind(class_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Every priced pack of the user, archived classes included.
+    pub async fn get_priced_pack_usage(
+        &mut self,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<PricedPackUsage>> {
+        let usage: Vec<PricedPackUsage> = sqlx::query_as::<_, PricedPackUsage>(
+            "select p.class_id, c.name, p.quantity, p.remaining, p.price, p.currency,
+             (select count(*)
+              from class_deduction_history h
+              where h.class_pack_id = p.class_pack_id) as attended
+             from class_pack p
+             join class c on c.class_id = p.class_id
+             where p.user_id = ?
+             and p.price is not null
+             and p.currency is not null
+             order by c.name, p.purchased_on, p.class_pack_id",
+        )
+        .bind(user_id)
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(usage)
+    }
+
+    /// Money spent on packs per purchase month, newest month first.
+    pub async fn get_monthly_spend(
+        &mut self,
+        user_id: i64,
+        months: u32,
+    ) -> anyhow::Result<Vec<MonthlySpend>> {
+        let spend: Vec<MonthlySpend> = sqlx::query_as::<_, MonthlySpend>(
+            "select substr(purchased_on, 1, 7) as month, currency, sum(price) as total
+             from class_pack
+             where user_id = ?
+             and price is not null
+             and currency is not null
+             and purchased_on >= date('now', 'localtime', 'start of month', '-' || ? || ' months')
+             group by month, currency
+             order by month desc, currency",
+        )
+        .bind(user_id)
+        .bind(months.saturating_sub(1))
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(spend)
+    }
+}
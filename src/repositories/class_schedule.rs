@@ -0,0 +1,157 @@
+use std::{fmt, ops::DerefMut};
+
+use chrono::{FixedOffset, NaiveTime, Weekday};
+use sqlx::{SqliteConnection, prelude::FromRow};
+
+use crate::{repositories::user::utc_offset_or_local, utils};
+
+#[derive(FromRow)]
+pub struct ClassSchedule {
+    pub class_schedule_id: i64,
+    pub class_id: i64,
+    pub weekday: u8,
+    pub starts_at: String,
+}
+
+impl ClassSchedule {
+    pub fn weekday(&self) -> Option<Weekday> {
+        Weekday::try_from(self.weekday).ok()
+    }
+}
+
+impl fmt::Display for ClassSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.weekday() {
+            Some(weekday) => write!(
+                f,
+                "{} {}",
+                utils::get_russian_weekday_name(weekday, true),
+                self.starts_at
+            ),
+            // Fallback: if the weekday is out of range, print the raw value without panicking.
+            None => write!(f, "{} {}", self.weekday, self.starts_at),
+        }
+    }
+}
+
+/// A slot of an active class together with whom to ask about it.
+#[derive(FromRow)]
+pub struct ScheduledSlot {
+    pub class_schedule_id: i64,
+    pub class_id: i64,
+    pub user_id: i64,
+    pub telegram_id: i64,
+    pub name: String,
+    pub weekday: u8,
+    pub starts_at: String,
+    pub utc_offset_minutes: Option<i32>,
+}
+
+impl ScheduledSlot {
+    /// Slot times are wall-clock times in the offset of the class owner.
+    pub fn utc_offset(&self) -> FixedOffset {
+        utc_offset_or_local(self.utc_offset_minutes)
+    }
+}
+
+pub struct ClassScheduleRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl<'a> ClassScheduleRepository<'a> {
+    pub fn new(conn: &'a mut SqliteConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Returns `None` when the class already has this slot.
+    pub async fn create(
+        &mut self,
+        class_id: i64,
+        user_id: i64,
+        weekday: Weekday,
+        starts_at: NaiveTime,
+    ) -> anyhow::Result<Option<i64>> {
+        let result = sqlx::query(
+            "insert or ignore into class_schedule (class_id, user_id, weekday, starts_at)
+             values (?, ?, ?, ?)",
+        )
+        .bind(class_id)
+        .bind(user_id)
+        .bind(weekday.num_days_from_monday())
+        .bind(starts_at.format("%H:%M").to_string())
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        Ok(Some(result.last_insert_rowid()))
+    }
+
+    pub async fn get_class_schedules(
+        &mut self,
+        class_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<ClassSchedule>> {
+        let schedules: Vec<ClassSchedule> = sqlx::query_as::<_, ClassSchedule>(
+            "select class_schedule_id, class_id, weekday, starts_at
+             from class_schedule
+             where class_id = ?
+             and user_id = ?
+             order by weekday, starts_at",
+        )
+        .bind(class_id)
+        .bind(user_id)
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(schedules)
+    }
+
+    pub async fn get_user_schedule_by_id(
+        &mut self,
+        class_schedule_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Option<ClassSchedule>> {
+        let schedule: Option<ClassSchedule> = sqlx::query_as::<_, ClassSchedule>(
+            "select class_schedule_id, class_id, weekday, starts_at
+             from class_schedule
+             where class_schedule_id = ?
+             and user_id = ?",
+        )
+        .bind(class_schedule_id)
+        .bind(user_id)
+        .fetch_optional(self.conn.deref_mut())
+        .await?;
+
+        Ok(schedule)
+    }
+
+    /// Slots of all classes that are not archived, across users.
+    pub async fn get_active_slots(&mut self) -> anyhow::Result<Vec<ScheduledSlot>> {
+        let slots: Vec<ScheduledSlot> = sqlx::query_as::<_, ScheduledSlot>(
+            "select s.class_schedule_id, s.class_id, s.user_id, u.telegram_id, c.name,
+                    s.weekday, s.starts_at, u.utc_offset_minutes
+             from class_schedule s
+             join class c on c.class_id = s.class_id
+             join user u on u.user_id = s.user_id
+             where c.archived_at is null",
+        )
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(slots)
+    }
+
+    pub async fn delete(&mut self, class_schedule_id: i64) -> anyhow::Result<()> {
+        sqlx::query(
+            "delete from class_schedule
+             where class_schedule_id = ?",
+        )
+        .bind(class_schedule_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,36 +1,50 @@
-use std::{fmt, ops::DerefMut};
+use std::ops::DerefMut;
 
-use chrono::{Datelike, NaiveDateTime};
+use chrono::{Datelike, FixedOffset, NaiveDate, NaiveDateTime, TimeZone};
 use sqlx::{SqliteConnection, prelude::FromRow};
 
 use crate::utils;
 
-#[derive(FromRow)]
+#[derive(Debug, FromRow)]
 pub struct DailyPracticeLog {
+    pub daily_practice_log_id: i64,
     pub created_at: String,
     pub user_id: i64,
     pub minutes: u16,
+    pub note: Option<String>,
+    /// Only selected by `get_all`.
+    #[sqlx(default)]
+    pub category_name: Option<String>,
 }
 
-impl fmt::Display for DailyPracticeLog {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let dt = match NaiveDateTime::parse_from_str(&self.created_at, "%Y-%m-%d %H:%M:%S") {
-            Ok(dt) => dt,
-            Err(_) => {
-                // Fallback: if the datetime cannot be parsed, print the raw value without panicking.
-                return write!(f, "{} - {} мин", self.created_at, self.minutes);
-            }
-        };
-        let formatted_date = dt.format("%d.%m.%Y").to_string();
-        write!(
-            f,
-            "{} ({}) - {} мин",
-            formatted_date,
-            utils::get_russian_weekday_name(dt.weekday(), true),
-            self.minutes
-        )
+impl DailyPracticeLog {
+    /// `created_at` at the given offset, usually the user's; it is stored as
+    /// UTC.
+    pub fn local_created_at(&self, utc_offset: FixedOffset) -> Option<NaiveDateTime> {
+        let dt = NaiveDateTime::parse_from_str(&self.created_at, "%Y-%m-%d %H:%M:%S").ok()?;
+        Some(utc_offset.from_utc_datetime(&dt).naive_local())
+    }
+
+    /// The day of the entry at the given offset.
+    pub fn local_date(&self, utc_offset: FixedOffset) -> Option<NaiveDate> {
+        self.local_created_at(utc_offset).map(|dt| dt.date())
+    }
+
+    /// `ДД.ММ.ГГГГ (Дн) - N мин`, the day taken at the given offset.
+    pub fn describe(&self, utc_offset: FixedOffset) -> String {
+        match self.local_date(utc_offset) {
+            Some(date) => format!(
+                "{} ({}) - {} мин",
+                date.format("%d.%m.%Y"),
+                utils::get_russian_weekday_name(date.weekday(), true),
+                self.minutes
+            ),
+            // Fallback: if the datetime cannot be parsed, print the raw value without panicking.
+            None => format!("{} - {} мин", self.created_at, self.minutes),
+        }
     }
 }
+
 pub struct DailyPracticeLogRepository<'a> {
     conn: &'a mut SqliteConnection,
 }
@@ -40,13 +54,25 @@ impl<'a> DailyPracticeLogRepository<'a> {
         Self { conn }
     }
 
-    pub async fn create(&mut self, minutes: u16, user_id: i64) -> anyhow::Result<i64> {
+    /// `created_at` (UTC) defaults to the current timestamp when not given.
+    pub async fn create(
+        &mut self,
+        minutes: u16,
+        user_id: i64,
+        practice_category_id: Option<i64>,
+        created_at: Option<String>,
+        note: Option<String>,
+    ) -> anyhow::Result<i64> {
         let result = sqlx::query(
-            "insert into daily_practice_log (minutes, user_id)
-             values (?, ?)",
+            "insert into daily_practice_log
+                 (minutes, user_id, practice_category_id, created_at, note)
+                 values (?, ?, ?, coalesce(?, current_timestamp), ?)",
         )
         .bind(minutes)
         .bind(user_id)
+        .bind(practice_category_id)
+        .bind(created_at)
+        .bind(note)
         .execute(self.conn.deref_mut())
         .await?;
 
@@ -54,16 +80,242 @@ impl<'a> DailyPracticeLogRepository<'a> {
         Ok(daily_practice_log_id)
     }
 
+    /// Entries whose day at `utc_offset` falls within `from..=until`, oldest
+    /// first.
+    pub async fn get_by_period(
+        &mut self,
+        user_id: i64,
+        from: NaiveDate,
+        until: NaiveDate,
+        utc_offset: FixedOffset,
+    ) -> anyhow::Result<Vec<DailyPracticeLog>> {
+        let records: Vec<DailyPracticeLog> = sqlx::query_as::<_, DailyPracticeLog>(
+            "select daily_practice_log_id, minutes, user_id, created_at, note
+             from daily_practice_log
+             where user_id = ?
+             and date(created_at, ?) between ? and ?
+             order by created_at",
+        )
+        .bind(user_id)
+        .bind(utils::sqlite_offset_modifier(utc_offset))
+        .bind(from.to_string())
+        .bind(until.to_string())
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Every entry of the user with its category name, oldest first.
     pub async fn get_all(&mut self, user_id: i64) -> anyhow::Result<Vec<DailyPracticeLog>> {
         let records: Vec<DailyPracticeLog> = sqlx::query_as::<_, DailyPracticeLog>(
-            "select minutes, user_id, created_at
+            "select l.daily_practice_log_id, l.minutes, l.user_id, l.created_at, l.note,
+                 c.name as category_name
+             from daily_practice_log l
+             left join practice_category c on c.practice_category_id = l.practice_category_id
+             where l.user_id = ?
+             order by l.created_at, l.daily_practice_log_id",
+        )
+        .bind(user_id)
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(records)
+    }
+
+    /// The latest `limit` entries, newest first.
+    pub async fn get_recent(
+        &mut self,
+        user_id: i64,
+        limit: u32,
+    ) -> anyhow::Result<Vec<DailyPracticeLog>> {
+        let records: Vec<DailyPracticeLog> = sqlx::query_as::<_, DailyPracticeLog>(
+            "select daily_practice_log_id, minutes, user_id, created_at, note
              from daily_practice_log
-             where user_id = ?",
+             where user_id = ?
+             order by created_at desc, daily_practice_log_id desc
+             limit ?",
         )
         .bind(user_id)
+        .bind(limit)
         .fetch_all(self.conn.deref_mut())
         .await?;
 
         Ok(records)
     }
+
+    pub async fn get_user_entry_by_id(
+        &mut self,
+        daily_practice_log_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Option<DailyPracticeLog>> {
+        let record: Option<DailyPracticeLog> = sqlx::query_as::<_, DailyPracticeLog>(
+            "select daily_practice_log_id, minutes, user_id, created_at, note
+             from daily_practice_log
+             where daily_practice_log_id = ?
+             and user_id = ?",
+        )
+        .bind(daily_practice_log_id)
+        .bind(user_id)
+        .fetch_optional(self.conn.deref_mut())
+        .await?;
+
+        Ok(record)
+    }
+
+    /// Returns `false` if the entry doesn't exist or belongs to someone else.
+    pub async fn update_minutes(
+        &mut self,
+        daily_practice_log_id: i64,
+        user_id: i64,
+        minutes: u16,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            "update daily_practice_log
+             set minutes = ?
+             where daily_practice_log_id = ?
+             and user_id = ?",
+        )
+        .bind(minutes)
+        .bind(daily_practice_log_id)
+        .bind(user_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Returns `false` if the entry doesn't exist or belongs to someone else.
+    pub async fn set_note(
+        &mut self,
+        daily_practice_log_id: i64,
+        user_id: i64,
+        note: Option<String>,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            "update daily_practice_log
+             set note = ?
+             where daily_practice_log_id = ?
+             and user_id = ?",
+        )
+        .bind(note)
+        .bind(daily_practice_log_id)
+        .bind(user_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// All entries that have a note, newest first.
+    pub async fn get_with_notes(&mut self, user_id: i64) -> anyhow::Result<Vec<DailyPracticeLog>> {
+        let records: Vec<DailyPracticeLog> = sqlx::query_as::<_, DailyPracticeLog>(
+            "select daily_practice_log_id, minutes, user_id, created_at, note
+             from daily_practice_log
+             where user_id = ?
+             and note is not null
+             order by created_at desc, daily_practice_log_id desc",
+        )
+        .bind(user_id)
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Returns `false` if the entry doesn't exist or belongs to someone else.
+    pub async fn delete(
+        &mut self,
+        daily_practice_log_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            "delete from daily_practice_log
+             where daily_practice_log_id = ?
+             and user_id = ?",
+        )
+        .bind(daily_practice_log_id)
+        .bind(user_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Minutes logged at or after `since` (UTC).
+    pub async fn get_minutes_since(
+        &mut self,
+        user_id: i64,
+        since: NaiveDateTime,
+    ) -> anyhow::Result<i64> {
+        let (minutes,): (i64,) = sqlx::query_as(
+            "select coalesce(sum(minutes), 0)
+             from daily_practice_log
+             where user_id = ?
+             and created_at >= ?",
+        )
+        .bind(user_id)
+        .bind(since.format("%Y-%m-%d %H:%M:%S").to_string())
+        .fetch_one(self.conn.deref_mut())
+        .await?;
+
+        Ok(minutes)
+    }
+
+    /// Minutes per day at `utc_offset`, oldest first; several entries on one
+    /// day are summed up and days without practice are left out. `from` and
+    /// `until` limit the days, both inclusive.
+    pub async fn get_daily_totals(
+        &mut self,
+        user_id: i64,
+        from: Option<NaiveDate>,
+        until: Option<NaiveDate>,
+        utc_offset: FixedOffset,
+    ) -> anyhow::Result<Vec<(String, i64)>> {
+        let totals: Vec<(String, i64)> = sqlx::query_as(
+            "select date(created_at, ?) as day, sum(minutes)
+             from daily_practice_log
+             where user_id = ?
+             and day >= coalesce(?, day)
+             and day <= coalesce(?, day)
+             group by day
+             order by day",
+        )
+        .bind(utils::sqlite_offset_modifier(utc_offset))
+        .bind(user_id)
+        .bind(from.map(|d| d.to_string()))
+        .bind(until.map(|d| d.to_string()))
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(totals)
+    }
+
+    /// Minutes per category over the days `from..=until` at `utc_offset`, the
+    /// biggest first; entries without a category come as `None`.
+    pub async fn get_category_totals(
+        &mut self,
+        user_id: i64,
+        from: NaiveDate,
+        until: NaiveDate,
+        utc_offset: FixedOffset,
+    ) -> anyhow::Result<Vec<(Option<String>, i64)>> {
+        let totals: Vec<(Option<String>, i64)> = sqlx::query_as(
+            "select c.name, sum(l.minutes) as total
+             from daily_practice_log l
+             left join practice_category c on c.practice_category_id = l.practice_category_id
+             where l.user_id = ?
+             and date(l.created_at, ?) between ? and ?
+             group by l.practice_category_id
+             order by total desc, c.name",
+        )
+        .bind(user_id)
+        .bind(utils::sqlite_offset_modifier(utc_offset))
+        .bind(from.to_string())
+        .bind(until.to_string())
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(totals)
+    }
 }
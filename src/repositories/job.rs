@@ -0,0 +1,245 @@
+use std::ops::DerefMut;
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Utc};
+use sqlx::{SqliteConnection, prelude::FromRow};
+
+/// Format of `run_at` and `last_run_at`, the same as `current_timestamp`.
+pub const JOB_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum JobStatus {
+    Done,
+    Failed,
+    Missed,
+}
+
+impl JobStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Done => "done",
+            JobStatus::Failed => "failed",
+            JobStatus::Missed => "missed",
+        }
+    }
+}
+
+#[derive(FromRow)]
+pub struct Job {
+    pub job_id: i64,
+    pub kind: String,
+    pub payload: String,
+    pub run_at: String,
+    pub cron: Option<String>,
+    pub utc_offset_minutes: i32,
+    pub telegram_id: Option<i64>,
+}
+
+impl Job {
+    pub fn run_at_utc(&self) -> Option<DateTime<Utc>> {
+        NaiveDateTime::parse_from_str(&self.run_at, JOB_TIME_FORMAT)
+            .ok()
+            .map(|dt| dt.and_utc())
+    }
+
+    pub fn utc_offset(&self) -> Option<FixedOffset> {
+        FixedOffset::east_opt(self.utc_offset_minutes.checked_mul(60)?)
+    }
+}
+
+pub struct NewJob<'a> {
+    pub kind: &'a str,
+    pub payload: &'a str,
+    pub run_at: DateTime<Utc>,
+    pub cron: Option<&'a str>,
+    pub utc_offset_minutes: i32,
+    pub unique_key: Option<&'a str>,
+    pub user_id: Option<i64>,
+}
+
+pub struct JobRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+const JOB_COLUMNS: &str =
+    "j.job_id, j.kind, j.payload, j.run_at, j.cron, j.utc_offset_minutes, u.telegram_id";
+
+impl<'a> JobRepository<'a> {
+    pub fn new(conn: &'a mut SqliteConnection) -> Self {
+        Self { conn }
+    }
+
+    /// A job with the same `unique_key` is replaced.
+    pub async fn create(&mut self, job: &NewJob<'_>) -> anyhow::Result<i64> {
+        if let Some(unique_key) = job.unique_key {
+            sqlx::query("delete from job where unique_key = ?")
+                .bind(unique_key)
+                .execute(self.conn.deref_mut())
+                .await?;
+        }
+
+        let result = sqlx::query(
+            "insert into job (kind, payload, run_at, cron, utc_offset_minutes, unique_key, user_id)
+             values (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(job.kind)
+        .bind(job.payload)
+        .bind(job.run_at.format(JOB_TIME_FORMAT).to_string())
+        .bind(job.cron)
+        .bind(job.utc_offset_minutes)
+        .bind(job.unique_key)
+        .bind(job.user_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Scheduled jobs due by `now`, the most overdue first.
+    pub async fn get_due(&mut self, now: DateTime<Utc>, limit: u32) -> anyhow::Result<Vec<Job>> {
+        let jobs: Vec<Job> = sqlx::query_as::<_, Job>(&format!(
+            "select {JOB_COLUMNS}
+             from job j
+             left join user u on u.user_id = j.user_id
+             where j.status = 'scheduled'
+             and j.run_at <= ?
+             order by j.run_at, j.job_id
+             limit ?"
+        ))
+        .bind(now.format(JOB_TIME_FORMAT).to_string())
+        .bind(limit)
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(jobs)
+    }
+
+    /// Marks a scheduled job as running. Returns `false` if it was cancelled
+    /// or taken in the meantime.
+    pub async fn claim(&mut self, job_id: i64) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            "update job
+             set status = 'running', attempts = attempts + 1
+             where job_id = ?
+             and status = 'scheduled'",
+        )
+        .bind(job_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Puts a recurring job back in the queue for its next occurrence.
+    pub async fn reschedule(
+        &mut self,
+        job_id: i64,
+        run_at: DateTime<Utc>,
+        last_run_at: Option<DateTime<Utc>>,
+        last_error: Option<&str>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "update job
+             set status = 'scheduled', run_at = ?, last_run_at = coalesce(?, last_run_at),
+                 last_error = ?
+             where job_id = ?
+             and status = 'running'",
+        )
+        .bind(run_at.format(JOB_TIME_FORMAT).to_string())
+        .bind(last_run_at.map(|dt| dt.format(JOB_TIME_FORMAT).to_string()))
+        .bind(last_error)
+        .bind(job_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn finish(
+        &mut self,
+        job_id: i64,
+        status: JobStatus,
+        last_error: Option<&str>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "update job
+             set status = ?, last_error = ?,
+                 last_run_at = case when ? then current_timestamp else last_run_at end
+             where job_id = ?
+             and status = 'running'",
+        )
+        .bind(status.as_str())
+        .bind(last_error)
+        .bind(status != JobStatus::Missed)
+        .bind(job_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Cancels the job with the key if it has not run yet.
+    pub async fn cancel_by_key(&mut self, unique_key: &str) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            "update job
+             set status = 'cancelled'
+             where unique_key = ?
+             and status = 'scheduled'",
+        )
+        .bind(unique_key)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Recurring jobs of the user waiting for their next occurrence.
+    pub async fn get_user_recurring_jobs(&mut self, user_id: i64) -> anyhow::Result<Vec<Job>> {
+        let jobs: Vec<Job> = sqlx::query_as::<_, Job>(&format!(
+            "select {JOB_COLUMNS}
+             from job j
+             left join user u on u.user_id = j.user_id
+             where j.user_id = ?
+             and j.cron is not null
+             and j.status = 'scheduled'"
+        ))
+        .bind(user_id)
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(jobs)
+    }
+
+    pub async fn set_timing(
+        &mut self,
+        job_id: i64,
+        run_at: DateTime<Utc>,
+        utc_offset_minutes: i32,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "update job
+             set run_at = ?, utc_offset_minutes = ?
+             where job_id = ?
+             and status = 'scheduled'",
+        )
+        .bind(run_at.format(JOB_TIME_FORMAT).to_string())
+        .bind(utc_offset_minutes)
+        .bind(job_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Jobs left running by a crash or restart go back to the queue.
+    pub async fn reset_running(&mut self) -> anyhow::Result<u64> {
+        let result = sqlx::query(
+            "update job
+             set status = 'scheduled'
+             where status = 'running'",
+        )
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
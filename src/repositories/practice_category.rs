@@ -0,0 +1,78 @@
+use std::ops::DerefMut;
+
+use sqlx::{SqliteConnection, prelude::FromRow};
+
+#[derive(FromRow)]
+pub struct PracticeCategory {
+    pub practice_category_id: i64,
+    pub name: String,
+}
+
+pub struct PracticeCategoryRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl<'a> PracticeCategoryRepository<'a> {
+    pub fn new(conn: &'a mut SqliteConnection) -> Self {
+        Self { conn }
+    }
+
+    pub async fn create(&mut self, name: &str, user_id: i64) -> anyhow::Result<i64, sqlx::Error> {
+        let result = sqlx::query(
+            "insert into practice_category (name, user_id)
+             values (?, ?)",
+        )
+        .bind(name)
+        .bind(user_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// In alphabetical order.
+    pub async fn get_user_categories(
+        &mut self,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<PracticeCategory>> {
+        let categories: Vec<PracticeCategory> = sqlx::query_as::<_, PracticeCategory>(
+            "select practice_category_id, name
+             from practice_category
+             where user_id = ?
+             order by name",
+        )
+        .bind(user_id)
+        .fetch_all(self.conn.deref_mut())
+        .await?;
+
+        Ok(categories)
+    }
+
+    pub async fn get_user_category_by_id(
+        &mut self,
+        practice_category_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Option<PracticeCategory>> {
+        let category: Option<PracticeCategory> = sqlx::query_as::<_, PracticeCategory>(
+            "select practice_category_id, name
+             from practice_category
+             where practice_category_id = ?
+             and user_id = ?",
+        )
+        .bind(practice_category_id)
+        .bind(user_id)
+        .fetch_optional(self.conn.deref_mut())
+        .await?;
+
+        Ok(category)
+    }
+
+    pub async fn delete(&mut self, practice_category_id: i64) -> anyhow::Result<()> {
+        sqlx::query("delete from practice_category where practice_category_id = ?")
+            .bind(practice_category_id)
+            .execute(self.conn.deref_mut())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use std::ops::DerefMut;
+
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use sqlx::{SqliteConnection, prelude::FromRow};
+
+pub const SESSION_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug, FromRow)]
+pub struct PracticeSession {
+    pub practice_session_id: i64,
+    pub started_at: String,
+    pub paused_at: Option<String>,
+    pub paused_secs: i64,
+}
+
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(text, SESSION_TIME_FORMAT)
+        .ok()
+        .map(|dt| dt.and_utc())
+}
+
+impl PracticeSession {
+    pub fn started_at_utc(&self) -> Option<DateTime<Utc>> {
+        parse_time(&self.started_at)
+    }
+
+    pub fn paused_at_utc(&self) -> Option<DateTime<Utc>> {
+        self.paused_at.as_deref().and_then(parse_time)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Practice time by `now`, not counting pauses.
+    pub fn elapsed(&self, now: DateTime<Utc>) -> TimeDelta {
+        let Some(started_at) = self.started_at_utc() else {
+            return TimeDelta::zero();
+        };
+        let until = self.paused_at_utc().unwrap_or(now);
+        (until - started_at - TimeDelta::seconds(self.paused_secs)).max(TimeDelta::zero())
+    }
+}
+
+pub struct PracticeSessionRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl<'a> PracticeSessionRepository<'a> {
+    pub fn new(conn: &'a mut SqliteConnection) -> Self {
+        Self { conn }
+    }
+
+    pub async fn create(&mut self, user_id: i64, started_at: DateTime<Utc>) -> anyhow::Result<i64> {
+        let result = sqlx::query(
+            "insert into practice_session (user_id, started_at)
+             values (?, ?)",
+        )
+        .bind(user_id)
+        .bind(started_at.format(SESSION_TIME_FORMAT).to_string())
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn get_by_user_id(
+        &mut self,
+        user_id: i64,
+    ) -> anyhow::Result<Option<PracticeSession>> {
+        let session: Option<PracticeSession> = sqlx::query_as::<_, PracticeSession>(
+            "select practice_session_id, started_at, paused_at, paused_secs
+             from practice_session
+             where user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(self.conn.deref_mut())
+        .await?;
+
+        Ok(session)
+    }
+
+    pub async fn pause(
+        &mut self,
+        practice_session_id: i64,
+        paused_at: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "update practice_session
+             set paused_at = ?
+             where practice_session_id = ?",
+        )
+        .bind(paused_at.format(SESSION_TIME_FORMAT).to_string())
+        .bind(practice_session_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Clears `paused_at`, storing the new pause total.
+    pub async fn resume(
+        &mut self,
+        practice_session_id: i64,
+        paused_secs: i64,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "update practice_session
+             set paused_at = null, paused_secs = ?
+             where practice_session_id = ?",
+        )
+        .bind(paused_secs)
+        .bind(practice_session_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete(&mut self, practice_session_id: i64) -> anyhow::Result<()> {
+        sqlx::query("delete from practice_session where practice_session_id = ?")
+            .bind(practice_session_id)
+            .execute(self.conn.deref_mut())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use std::ops::DerefMut;
 
+use chrono::{FixedOffset, Local, Offset};
 use sqlx::{SqliteConnection, prelude::FromRow};
 
 #[derive(FromRow)]
@@ -7,6 +8,22 @@ pub struct User {
     pub username: Option<String>,
     pub user_id: i64,
     pub telegram_id: i64,
+    pub utc_offset_minutes: Option<i32>,
+}
+
+impl User {
+    /// The user's own offset, or the bot's current one if not set.
+    pub fn utc_offset(&self) -> FixedOffset {
+        utc_offset_or_local(self.utc_offset_minutes)
+    }
+}
+
+/// A stored `utc_offset_minutes` as an offset, or the bot's current one if
+/// not set.
+pub fn utc_offset_or_local(utc_offset_minutes: Option<i32>) -> FixedOffset {
+    utc_offset_minutes
+        .and_then(|minutes| FixedOffset::east_opt(minutes.checked_mul(60)?))
+        .unwrap_or_else(|| Local::now().offset().fix())
 }
 
 pub struct UserRepository<'a> {
@@ -42,7 +59,9 @@ impl<'a> UserRepository<'a> {
         telegram_id: i64,
     ) -> anyhow::Result<Option<User>> {
         let user: Option<User> = sqlx::query_as::<_, User>(
-            "select user_id, telegram_id, username from user where telegram_id = ?",
+            "select user_id, telegram_id, username, utc_offset_minutes
+             from user
+             where telegram_id = ?",
         )
         .bind(telegram_id)
         .fetch_optional(self.conn.deref_mut())
@@ -50,6 +69,109 @@ impl<'a> UserRepository<'a> {
 
         Ok(user)
     }
+
+    /// Daily practice reminder time, `HH:MM` in the user's offset.
+    pub async fn get_practice_reminder_at(
+        &mut self,
+        user_id: i64,
+    ) -> anyhow::Result<Option<String>> {
+        let row: Option<(Option<String>,)> =
+            sqlx::query_as("select practice_reminder_at from user where user_id = ?")
+                .bind(user_id)
+                .fetch_optional(self.conn.deref_mut())
+                .await?;
+        Ok(row.and_then(|(remind_at,)| remind_at))
+    }
+
+    pub async fn set_practice_reminder_at(
+        &mut self,
+        user_id: i64,
+        remind_at: Option<&str>,
+    ) -> anyhow::Result<()> {
+        sqlx::query("update user set practice_reminder_at = ? where user_id = ?")
+            .bind(remind_at)
+            .bind(user_id)
+            .execute(self.conn.deref_mut())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Daily and weekly practice goals in minutes.
+    pub async fn get_practice_goals(
+        &mut self,
+        user_id: i64,
+    ) -> anyhow::Result<(Option<u16>, Option<u16>)> {
+        let row: Option<(Option<u16>, Option<u16>)> = sqlx::query_as(
+            "select daily_practice_goal_mins, weekly_practice_goal_mins
+             from user
+             where user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(self.conn.deref_mut())
+        .await?;
+        Ok(row.unwrap_or_default())
+    }
+
+    pub async fn set_practice_goals(
+        &mut self,
+        user_id: i64,
+        daily: Option<u16>,
+        weekly: Option<u16>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "update user
+             set daily_practice_goal_mins = ?, weekly_practice_goal_mins = ?
+             where user_id = ?",
+        )
+        .bind(daily)
+        .bind(weekly)
+        .bind(user_id)
+        .execute(self.conn.deref_mut())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Whether the weekly and monthly digests are on.
+    pub async fn get_digest_subscriptions(&mut self, user_id: i64) -> anyhow::Result<(bool, bool)> {
+        let row: Option<(bool, bool)> =
+            sqlx::query_as("select weekly_digest, monthly_digest from user where user_id = ?")
+                .bind(user_id)
+                .fetch_optional(self.conn.deref_mut())
+                .await?;
+        Ok(row.unwrap_or_default())
+    }
+
+    pub async fn set_digest_subscriptions(
+        &mut self,
+        user_id: i64,
+        weekly: bool,
+        monthly: bool,
+    ) -> anyhow::Result<()> {
+        sqlx::query("update user set weekly_digest = ?, monthly_digest = ? where user_id = ?")
+            .bind(weekly)
+            .bind(monthly)
+            .bind(user_id)
+            .execute(self.conn.deref_mut())
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_utc_offset(
+        &mut self,
+        user_id: i64,
+        utc_offset_minutes: Option<i32>,
+    ) -> anyhow::Result<()> {
+        sqlx::query("update user set utc_offset_minutes = ? where user_id = ?")
+            .bind(utc_offset_minutes)
+            .bind(user_id)
+            .execute(self.conn.deref_mut())
+            .await?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,464 @@
+pub mod cron;
+
+use std::{collections::HashMap, future::Future, sync::Arc, time::Duration};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::future::BoxFuture;
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+    config::{MissedJobPolicy, SchedulerConfig},
+    repositories::job::{Job, JobStatus},
+    scheduler::cron::CronSchedule,
+    services::job::next_cron_run,
+    uow::UnitOfWork,
+};
+
+/// Jobs taken from the queue per poll; the rest wait for the next one.
+const BATCH_SIZE: u32 = 50;
+/// Upper bound of missed occurrences replayed under
+/// [`MissedJobPolicy::RunAll`].
+const MAX_CATCH_UP_RUNS: usize = 100;
+
+pub struct JobContext {
+    /// Chat of the user the job belongs to; `None` for system jobs.
+    pub telegram_id: Option<i64>,
+    pub payload: serde_json::Value,
+    /// When this run was due, which may be well before now after a restart.
+    pub scheduled_for: DateTime<Utc>,
+}
+
+pub trait JobHandler: Send + Sync {
+    fn run(&self, ctx: JobContext) -> BoxFuture<'static, anyhow::Result<()>>;
+}
+
+impl<F, Fut> JobHandler for F
+where
+    F: Fn(JobContext) -> Fut + Send + Sync,
+    Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+{
+    fn run(&self, ctx: JobContext) -> BoxFuture<'static, anyhow::Result<()>> {
+        Box::pin(self(ctx))
+    }
+}
+
+/// Runs the jobs stored in the `job` table by their `kind`. Jobs are
+/// scheduled through [`crate::services::job`]; since the queue lives in the
+/// database, they survive restarts.
+pub struct Scheduler {
+    db_pool: Arc<Pool<Sqlite>>,
+    config: SchedulerConfig,
+    handlers: HashMap<String, Arc<dyn JobHandler>>,
+}
+
+impl Scheduler {
+    pub fn new(db_pool: Arc<Pool<Sqlite>>, config: SchedulerConfig) -> Self {
+        Self {
+            db_pool,
+            config,
+            handlers: HashMap::new(),
+        }
+    }
+
+    pub fn handler(mut self, kind: &str, handler: impl JobHandler + 'static) -> Self {
+        self.handlers.insert(kind.to_string(), Arc::new(handler));
+        self
+    }
+
+    pub async fn run(self) {
+        match self.reset_running().await {
+            Ok(0) => {}
+            Ok(count) => log::warn!("Requeued {} jobs interrupted by a restart", count),
+            Err(err) => log::error!("Failed to requeue interrupted jobs: {}", err),
+        }
+
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(self.config.poll_interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.run_due_jobs(Utc::now()).await {
+                log::error!("Failed to run scheduled jobs: {}", err);
+            }
+        }
+    }
+
+    async fn reset_running(&self) -> anyhow::Result<u64> {
+        let mut uow = UnitOfWork::new_transactional(self.db_pool.as_ref()).await?;
+        let count = uow.job_repo().await?.reset_running().await?;
+        uow.commit().await?;
+        Ok(count)
+    }
+
+    /// Runs every job due by `now`. Returns how many were taken.
+    pub async fn run_due_jobs(&self, now: DateTime<Utc>) -> anyhow::Result<usize> {
+        let mut taken = 0;
+        loop {
+            let jobs = {
+                let mut uow = UnitOfWork::new_readonly(self.db_pool.as_ref());
+                uow.job_repo().await?.get_due(now, BATCH_SIZE).await?
+            };
+            let batch_len = jobs.len();
+
+            for job in jobs {
+                if !self.claim(job.job_id).await? {
+                    continue;
+                }
+                taken += 1;
+                let job_id = job.job_id;
+                if let Err(err) = self.process(job, now).await {
+                    log::error!("Failed to process job {}: {}", job_id, err);
+                }
+            }
+
+            if batch_len < BATCH_SIZE as usize {
+                return Ok(taken);
+            }
+        }
+    }
+
+    async fn claim(&self, job_id: i64) -> anyhow::Result<bool> {
+        let mut uow = UnitOfWork::new_transactional(self.db_pool.as_ref()).await?;
+        let claimed = uow.job_repo().await?.claim(job_id).await?;
+        uow.commit().await?;
+        Ok(claimed)
+    }
+
+    async fn process(&self, job: Job, now: DateTime<Utc>) -> anyhow::Result<()> {
+        let Some(run_at) = job.run_at_utc() else {
+            return self
+                .finish(job.job_id, JobStatus::Failed, Some("invalid run_at"))
+                .await;
+        };
+        let Some(handler) = self.handlers.get(&job.kind).cloned() else {
+            let error = format!("no handler for job kind `{}`", job.kind);
+            return self
+                .finish(job.job_id, JobStatus::Failed, Some(&error))
+                .await;
+        };
+        let payload = match serde_json::from_str(&job.payload) {
+            Ok(payload) => payload,
+            Err(err) => {
+                let error = format!("invalid payload: {err}");
+                return self
+                    .finish(job.job_id, JobStatus::Failed, Some(&error))
+                    .await;
+            }
+        };
+
+        let grace = TimeDelta::seconds(self.config.missed_job_grace_secs as i64);
+        let missed = now - run_at > grace;
+        let policy = self.config.missed_job_policy;
+
+        let Some(expression) = job.cron.as_deref() else {
+            if missed && policy == MissedJobPolicy::Skip {
+                return self.finish(job.job_id, JobStatus::Missed, None).await;
+            }
+            return match self.execute(handler.as_ref(), &job, payload, run_at).await {
+                Ok(()) => self.finish(job.job_id, JobStatus::Done, None).await,
+                Err(err) => {
+                    let error = err.to_string();
+                    self.finish(job.job_id, JobStatus::Failed, Some(&error))
+                        .await
+                }
+            };
+        };
+
+        let (Ok(schedule), Some(utc_offset)) =
+            (expression.parse::<CronSchedule>(), job.utc_offset())
+        else {
+            return self
+                .finish(job.job_id, JobStatus::Failed, Some("invalid cron schedule"))
+                .await;
+        };
+
+        let runs = match (missed, policy) {
+            (false, _) | (true, MissedJobPolicy::RunOnce) => vec![run_at],
+            (true, MissedJobPolicy::Skip) => vec![],
+            (true, MissedJobPolicy::RunAll) => {
+                let mut runs = vec![run_at];
+                while runs.len() < MAX_CATCH_UP_RUNS {
+                    match next_cron_run(&schedule, utc_offset, runs[runs.len() - 1]) {
+                        Some(next) if next <= now => runs.push(next),
+                        _ => break,
+                    }
+                }
+                runs
+            }
+        };
+        if runs.is_empty() {
+            log::info!("Skipped missed runs of job {} ({})", job.job_id, job.kind);
+        }
+
+        let mut last_run_at = None;
+        let mut last_error = None;
+        for scheduled_for in runs {
+            if let Err(err) = self
+                .execute(handler.as_ref(), &job, payload.clone(), scheduled_for)
+                .await
+            {
+                last_error = Some(err.to_string());
+            }
+            last_run_at = Some(Utc::now());
+        }
+
+        match next_cron_run(&schedule, utc_offset, now) {
+            Some(next) => {
+                let mut uow = UnitOfWork::new_transactional(self.db_pool.as_ref()).await?;
+                uow.job_repo()
+                    .await?
+                    .reschedule(job.job_id, next, last_run_at, last_error.as_deref())
+                    .await?;
+                uow.commit().await?;
+                Ok(())
+            }
+            None => {
+                let status = match last_error {
+                    Some(_) => JobStatus::Failed,
+                    None => JobStatus::Done,
+                };
+                self.finish(job.job_id, status, last_error.as_deref()).await
+            }
+        }
+    }
+
+    async fn execute(
+        &self,
+        handler: &dyn JobHandler,
+        job: &Job,
+        payload: serde_json::Value,
+        scheduled_for: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        let ctx = JobContext {
+            telegram_id: job.telegram_id,
+            payload,
+            scheduled_for,
+        };
+        log::debug!(
+            "Running job {} ({}) due at {} for {:?}: {}",
+            job.job_id,
+            job.kind,
+            ctx.scheduled_for,
+            ctx.telegram_id,
+            ctx.payload
+        );
+        let result = handler.run(ctx).await;
+        if let Err(err) = &result {
+            log::warn!("Job {} ({}) failed: {}", job.job_id, job.kind, err);
+        }
+        result
+    }
+
+    async fn finish(
+        &self,
+        job_id: i64,
+        status: JobStatus,
+        last_error: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let mut uow = UnitOfWork::new_transactional(self.db_pool.as_ref()).await?;
+        uow.job_repo()
+            .await?
+            .finish(job_id, status, last_error)
+            .await?;
+        uow.commit().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::{DateTime, Days, FixedOffset, TimeDelta, Utc};
+    use sqlx::{Pool, Sqlite};
+
+    use crate::{
+        config::{MissedJobPolicy, SchedulerConfig},
+        repositories::job::NewJob,
+        services::job::{JobTiming, schedule_job},
+        test_utils,
+        uow::UnitOfWork,
+    };
+
+    use super::{JobContext, Scheduler};
+
+    type Runs = Arc<Mutex<Vec<DateTime<Utc>>>>;
+
+    fn setup_scheduler(pool: &Arc<Pool<Sqlite>>, policy: MissedJobPolicy) -> (Scheduler, Runs) {
+        let runs: Runs = Arc::default();
+        let recorded = runs.clone();
+        let scheduler = Scheduler::new(
+            pool.clone(),
+            SchedulerConfig {
+                missed_job_policy: policy,
+                ..SchedulerConfig::default()
+            },
+        )
+        .handler("record", move |ctx: JobContext| {
+            let recorded = recorded.clone();
+            async move {
+                recorded.lock().unwrap().push(ctx.scheduled_for);
+                Ok(())
+            }
+        })
+        .handler("fail", |_: JobContext| async { anyhow::bail!("boom") });
+        (scheduler, runs)
+    }
+
+    async fn create_once(pool: &Pool<Sqlite>, kind: &str, run_at: DateTime<Utc>) -> i64 {
+        let mut uow = UnitOfWork::new_transactional(pool).await.unwrap();
+        let job_id = uow
+            .job_repo()
+            .await
+            .unwrap()
+            .create(&NewJob {
+                kind,
+                payload: "{}",
+                run_at,
+                cron: None,
+                utc_offset_minutes: 0,
+                unique_key: None,
+                user_id: None,
+            })
+            .await
+            .unwrap();
+        uow.commit().await.unwrap();
+        job_id
+    }
+
+    async fn schedule_daily(pool: &Arc<Pool<Sqlite>>) -> anyhow::Result<i64> {
+        schedule_job(
+            pool.clone(),
+            "record",
+            serde_json::json!({}),
+            None,
+            JobTiming::Cron {
+                schedule: "0 9 * * *".parse()?,
+                utc_offset: FixedOffset::east_opt(3 * 3600).unwrap(),
+            },
+            Some("daily"),
+        )
+        .await
+    }
+
+    async fn job_state(pool: &Pool<Sqlite>, job_id: i64) -> (String, String, Option<String>) {
+        sqlx::query_as("select status, run_at, last_error from job where job_id = ?")
+            .bind(job_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    fn parse(run_at: &str) -> DateTime<Utc> {
+        chrono::NaiveDateTime::parse_from_str(run_at, "%Y-%m-%d %H:%M:%S")
+            .unwrap()
+            .and_utc()
+    }
+
+    #[tokio::test]
+    async fn test_run_one_off_jobs() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        let (scheduler, runs) = setup_scheduler(&pool, MissedJobPolicy::RunOnce);
+        let now = Utc::now();
+
+        let done = create_once(&pool, "record", now - TimeDelta::minutes(1)).await;
+        let failed = create_once(&pool, "fail", now - TimeDelta::minutes(1)).await;
+        let unknown = create_once(&pool, "unknown", now - TimeDelta::minutes(1)).await;
+        let later = create_once(&pool, "record", now + TimeDelta::minutes(5)).await;
+
+        assert_eq!(scheduler.run_due_jobs(now).await?, 3);
+        assert_eq!(runs.lock().unwrap().len(), 1);
+        assert_eq!(job_state(&pool, done).await.0, "done");
+        let (status, _, last_error) = job_state(&pool, failed).await;
+        assert_eq!(
+            (status.as_str(), last_error.as_deref()),
+            ("failed", Some("boom"))
+        );
+        assert_eq!(job_state(&pool, unknown).await.0, "failed");
+        assert_eq!(job_state(&pool, later).await.0, "scheduled");
+
+        // Finished jobs are not picked up again.
+        assert_eq!(scheduler.run_due_jobs(now).await?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missed_one_off_job() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        let now = Utc::now();
+
+        let (scheduler, runs) = setup_scheduler(&pool, MissedJobPolicy::Skip);
+        let job_id = create_once(&pool, "record", now - TimeDelta::hours(2)).await;
+        scheduler.run_due_jobs(now).await?;
+        assert!(runs.lock().unwrap().is_empty());
+        assert_eq!(job_state(&pool, job_id).await.0, "missed");
+
+        let (scheduler, runs) = setup_scheduler(&pool, MissedJobPolicy::RunOnce);
+        let job_id = create_once(&pool, "record", now - TimeDelta::hours(2)).await;
+        scheduler.run_due_jobs(now).await?;
+        assert_eq!(runs.lock().unwrap().len(), 1);
+        assert_eq!(job_state(&pool, job_id).await.0, "done");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recurring_job_is_rescheduled() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        let (scheduler, runs) = setup_scheduler(&pool, MissedJobPolicy::RunOnce);
+
+        let job_id = schedule_daily(&pool).await?;
+        let (_, run_at, _) = job_state(&pool, job_id).await;
+        let run_at = parse(&run_at);
+        // 09:00 at UTC+3.
+        assert_eq!(run_at.format("%H:%M").to_string(), "06:00");
+
+        scheduler
+            .run_due_jobs(run_at + TimeDelta::minutes(1))
+            .await?;
+        assert_eq!(*runs.lock().unwrap(), vec![run_at]);
+        let (status, next_run_at, _) = job_state(&pool, job_id).await;
+        assert_eq!(status, "scheduled");
+        assert_eq!(parse(&next_run_at), run_at + Days::new(1));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missed_recurring_job_policies() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        for (policy, expected_runs) in [
+            (MissedJobPolicy::Skip, 0),
+            (MissedJobPolicy::RunOnce, 1),
+            (MissedJobPolicy::RunAll, 4),
+        ] {
+            let (scheduler, runs) = setup_scheduler(&pool, policy);
+            let job_id = schedule_daily(&pool).await?;
+            let run_at = parse(&job_state(&pool, job_id).await.1);
+
+            // Down for three days and a bit.
+            let now = run_at + Days::new(3) + TimeDelta::hours(1);
+            scheduler.run_due_jobs(now).await?;
+
+            assert_eq!(runs.lock().unwrap().len(), expected_runs, "{policy:?}");
+            let (status, next_run_at, _) = job_state(&pool, job_id).await;
+            assert_eq!(status, "scheduled");
+            assert_eq!(parse(&next_run_at), run_at + Days::new(4));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reset_running_jobs() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        let (scheduler, runs) = setup_scheduler(&pool, MissedJobPolicy::RunOnce);
+        let now = Utc::now();
+
+        let job_id = create_once(&pool, "record", now - TimeDelta::minutes(1)).await;
+        assert!(scheduler.claim(job_id).await?);
+        // A claimed job is not run twice, even if still due.
+        assert_eq!(scheduler.run_due_jobs(now).await?, 0);
+
+        assert_eq!(scheduler.reset_running().await?, 1);
+        assert_eq!(scheduler.run_due_jobs(now).await?, 1);
+        assert_eq!(runs.lock().unwrap().len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{Datelike, Days, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+/// A five-field cron expression (`minute hour day-of-month month
+/// day-of-week`) supporting `*`, lists, ranges and steps. Day of week is
+/// 0–7 with both 0 and 7 meaning Sunday. As in classic cron, when both the
+/// day of month and the day of week are restricted a day matching either
+/// one fires.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days_of_month: Vec<u32>,
+    months: Vec<u32>,
+    days_of_week: Vec<u32>,
+    days_of_month_restricted: bool,
+    days_of_week_restricted: bool,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[error("invalid cron expression `{0}`")]
+pub struct CronParseError(String);
+
+/// How far ahead to look for the next matching day: enough to reach the
+/// next 29 February.
+const MAX_SEARCH_DAYS: u64 = 366 * 8;
+
+fn parse_field(field: &str, min: u32, max: u32) -> Option<Vec<u32>> {
+    let mut values = vec![];
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0)?),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+                // `5/15` means from 5 to the end of the range.
+                None if part.contains('/') => (range.parse().ok()?, max),
+                None => {
+                    let value = range.parse().ok()?;
+                    (value, value)
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            return None;
+        }
+        values.extend((start..=end).step_by(step as usize));
+    }
+    values.sort_unstable();
+    values.dedup();
+    Some(values)
+}
+
+impl FromStr for CronSchedule {
+    type Err = CronParseError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let error = || CronParseError(expression.to_string());
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(error());
+        };
+
+        let mut days_of_week = parse_field(day_of_week, 0, 7).ok_or_else(error)?;
+        for day in days_of_week.iter_mut() {
+            *day %= 7;
+        }
+        days_of_week.sort_unstable();
+        days_of_week.dedup();
+
+        Ok(Self {
+            expression: fields.join(" "),
+            minutes: parse_field(minute, 0, 59).ok_or_else(error)?,
+            hours: parse_field(hour, 0, 23).ok_or_else(error)?,
+            days_of_month: parse_field(day_of_month, 1, 31).ok_or_else(error)?,
+            months: parse_field(month, 1, 12).ok_or_else(error)?,
+            days_of_week,
+            days_of_month_restricted: day_of_month != "*",
+            days_of_week_restricted: day_of_week != "*",
+        })
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+impl CronSchedule {
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if !self.months.contains(&date.month()) {
+            return false;
+        }
+        let day_of_month = self.days_of_month.contains(&date.day());
+        let day_of_week = self
+            .days_of_week
+            .contains(&date.weekday().num_days_from_sunday());
+        match (self.days_of_month_restricted, self.days_of_week_restricted) {
+            (true, true) => day_of_month || day_of_week,
+            (true, false) => day_of_month,
+            (false, true) => day_of_week,
+            (false, false) => true,
+        }
+    }
+
+    /// The first matching minute strictly after `after`, in the same
+    /// (local) time as `after`.
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_second(0)?.with_nanosecond(0)?;
+        for offset in 0..MAX_SEARCH_DAYS {
+            let date = start.date().checked_add_days(Days::new(offset))?;
+            if !self.matches_day(date) {
+                continue;
+            }
+            for &hour in &self.hours {
+                for &minute in &self.minutes {
+                    let candidate = date.and_time(NaiveTime::from_hms_opt(hour, minute, 0)?);
+                    if candidate > after {
+                        return Some(candidate);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    use super::CronSchedule;
+
+    fn at(month: u32, day: u32, hour: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, month, day)
+            .unwrap()
+            .and_hms_opt(hour, min, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!("* * * * *".parse::<CronSchedule>().is_ok());
+        assert!("*/15 9-18 * * 1-5".parse::<CronSchedule>().is_ok());
+        assert!("0 0 1,15 * 7".parse::<CronSchedule>().is_ok());
+        assert!("* * * *".parse::<CronSchedule>().is_err());
+        assert!("60 * * * *".parse::<CronSchedule>().is_err());
+        assert!("*/0 * * * *".parse::<CronSchedule>().is_err());
+        assert!("5-1 * * * *".parse::<CronSchedule>().is_err());
+    }
+
+    #[test]
+    fn test_next_after() {
+        // 2026-10-18 is a Sunday.
+        let daily: CronSchedule = "30 20 * * *".parse().unwrap();
+        assert_eq!(
+            daily.next_after(at(10, 18, 20, 0)),
+            Some(at(10, 18, 20, 30))
+        );
+        assert_eq!(
+            daily.next_after(at(10, 18, 20, 30)),
+            Some(at(10, 19, 20, 30))
+        );
+
+        let weekdays: CronSchedule = "0 9 * * 1-5".parse().unwrap();
+        assert_eq!(
+            weekdays.next_after(at(10, 17, 12, 0)),
+            Some(at(10, 19, 9, 0))
+        );
+
+        let sunday: CronSchedule = "0 10 * * 7".parse().unwrap();
+        assert_eq!(sunday.next_after(at(10, 18, 9, 0)), Some(at(10, 18, 10, 0)));
+
+        // Day of month or day of week.
+        let either: CronSchedule = "0 8 1 * 1".parse().unwrap();
+        assert_eq!(either.next_after(at(10, 18, 9, 0)), Some(at(10, 19, 8, 0)));
+        assert_eq!(either.next_after(at(10, 26, 9, 0)), Some(at(11, 1, 8, 0)));
+
+        let leap: CronSchedule = "0 0 29 2 *".parse().unwrap();
+        assert_eq!(
+            leap.next_after(at(10, 18, 0, 0)),
+            NaiveDate::from_ymd_opt(2028, 2, 29)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+        );
+    }
+}
@@ -1,3 +1,11 @@
 pub mod class;
+pub mod class_schedule;
 pub mod daily_practice_log;
+pub mod export;
+pub mod job;
+pub mod practice_category;
+pub mod practice_digest;
+pub mod practice_reminder;
+pub mod practice_session;
+pub mod spending;
 pub mod user;
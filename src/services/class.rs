@@ -1,18 +1,61 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use anyhow::bail;
+use chrono::{Days, FixedOffset, Months, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use sqlx::{Pool, Sqlite};
 
 use crate::{
+    config::ExpiredDeductionPolicy,
     errors::*,
-    repositories::{class::Class, class_deduction_history::ClassDeductionHistory},
+    repositories::{
+        class::Class,
+        class_deduction_history::ClassDeductionHistory,
+        class_freeze::ClassFreeze,
+        class_pack::{ClassPack, PackPrice},
+    },
     uow::UnitOfWork,
+    utils,
 };
 
+/// Maps a failed insert/update of `class.name` to a user-facing error,
+/// recognising SQLite unique constraint violations.
+fn map_class_name_error(err: sqlx::Error) -> anyhow::Error {
+    if let Some(db_err) = err.as_database_error()
+        && let Some("2067") | Some("1555") = db_err.code().as_deref()
+    {
+        return DuplicateClassNameError.into();
+    }
+    SomethingWentWrongError.into()
+}
+
+/// `class.quantity` is a `u8`, so a larger balance is capped at 255 instead
+/// of wrapping around.
+fn saturating_quantity(balance: i64) -> u8 {
+    balance.clamp(0, i64::from(u8::MAX)) as u8
+}
+
+/// Stores the classes left in the unexpired packs in `class.quantity`. Reads
+/// sum the packs themselves; the column keeps the last known balance for
+/// anything that looks at the table directly.
+async fn sync_class_quantity(uow: &mut UnitOfWork<'_>, class_id: i64) -> anyhow::Result<Class> {
+    let balance = uow
+        .class_pack_repo()
+        .await?
+        .get_balance(class_id, utils::today())
+        .await?;
+    let quantity = saturating_quantity(balance);
+    uow.class_repo()
+        .await?
+        .update_quantity(class_id, quantity, utils::today())
+        .await
+}
+
 pub async fn add_class(
     db_pool: Arc<Pool<Sqlite>>,
     name: String,
     quantity: u8,
+    valid_until: Option<NaiveDate>,
+    price: Option<PackPrice>,
     telegram_user_id: i64,
 ) -> anyhow::Result<i64> {
     let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
@@ -31,25 +74,32 @@ pub async fn add_class(
     let class_id = match uow
         .class_repo()
         .await?
-        .create(name, quantity as i64, user_id)
+        .create(
+            name,
+            quantity as i64,
+            valid_until.map(|d| d.to_string()),
+            user_id,
+        )
         .await
     {
         Ok(class_id) => class_id,
-        Err(err) => {
-            if let Some(db_err) = err.as_database_error() {
-                match db_err.code().as_deref() {
-                    Some("2067") | Some("1555") => {
-                        bail!(DuplicateClassNameError);
-                    }
-                    _ => {
-                        bail!(SomethingWentWrongError);
-                    }
-                }
-            }
-            bail!(SomethingWentWrongError);
-        }
+        Err(err) => return Err(map_class_name_error(err)),
     };
 
+    if quantity > 0 {
+        uow.class_pack_repo()
+            .await?
+            .create(
+                class_id,
+                user_id,
+                quantity.into(),
+                utils::today(),
+                valid_until,
+                price.as_ref(),
+            )
+            .await?;
+    }
+
     uow.commit().await?;
     Ok(class_id)
 }
@@ -71,15 +121,19 @@ pub async fn get_classes_by_user_id(
         }
     };
 
-    let classes = uow.class_repo().await?.get_user_classes(user_id).await?;
+    let classes = uow
+        .class_repo()
+        .await?
+        .get_user_classes(user_id, utils::today())
+        .await?;
     Ok(classes)
 }
 
-pub async fn get_class_deduction_histories(
+pub async fn get_class_by_id(
     db_pool: Arc<Pool<Sqlite>>,
     class_id: i64,
     telegram_user_id: i64,
-) -> anyhow::Result<Vec<ClassDeductionHistory>> {
+) -> anyhow::Result<Class> {
     let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
     let user_id = match uow
         .user_repo()
@@ -93,19 +147,92 @@ pub async fn get_class_deduction_histories(
         }
     };
 
+    match uow
+        .class_repo()
+        .await?
+        .get_user_class_by_id(class_id, user_id, utils::today())
+        .await?
+    {
+        Some(c) => Ok(c),
+        None => bail!(ClassNotFoundError),
+    }
+}
+
+/// Number of deductions shown per page of the history.
+pub const HISTORY_PAGE_SIZE: u32 = 15;
+
+pub struct DeductionHistoryPage {
+    pub histories: Vec<ClassDeductionHistory>,
+    pub page: u32,
+    pub total: u32,
+}
+
+impl DeductionHistoryPage {
+    pub fn page_count(&self) -> u32 {
+        self.total.div_ceil(HISTORY_PAGE_SIZE).max(1)
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.page + 1 < self.page_count()
+    }
+}
+
+/// Returns one page of deductions, newest first, optionally only those of
+/// the month starting at `month` in the user's offset.
+pub async fn get_class_deduction_history_page(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_id: i64,
+    telegram_user_id: i64,
+    month: Option<NaiveDate>,
+    page: u32,
+) -> anyhow::Result<DeductionHistoryPage> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let (user_id, utc_offset) = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => (u.user_id, u.utc_offset()),
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let days = month.map(|m| (m, m + Months::new(1) - Days::new(1)));
+
+    let total = uow
+        .class_deduction_history_repo()
+        .await?
+        .count_histories(class_id, user_id, days, utc_offset)
+        .await?;
     let histories = uow
         .class_deduction_history_repo()
         .await?
-        .get_histories(class_id, user_id)
+        .get_histories(
+            class_id,
+            user_id,
+            days,
+            utc_offset,
+            HISTORY_PAGE_SIZE,
+            page.saturating_mul(HISTORY_PAGE_SIZE),
+        )
         .await?;
-    Ok(histories)
+
+    Ok(DeductionHistoryPage {
+        histories,
+        page,
+        total,
+    })
 }
 
-pub async fn deduct_class(
+/// Sets or, with `None`, clears the note of a deduction.
+pub async fn set_deduction_note(
     db_pool: Arc<Pool<Sqlite>>,
-    class_id: i64,
+    class_deduction_history_id: i64,
     telegram_user_id: i64,
-) -> anyhow::Result<Class> {
+    note: Option<String>,
+) -> anyhow::Result<ClassDeductionHistory> {
     let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
     let user_id = match uow
         .user_repo()
@@ -119,10 +246,94 @@ pub async fn deduct_class(
         }
     };
 
+    let mut history = match uow
+        .class_deduction_history_repo()
+        .await?
+        .get_user_history_by_id(class_deduction_history_id, user_id)
+        .await?
+    {
+        Some(h) => h,
+        None => {
+            bail!(DeductionNotFoundError);
+        }
+    };
+
+    uow.class_deduction_history_repo()
+        .await?
+        .set_note(history.class_deduction_history_id, note.clone())
+        .await?;
+    history.note = note;
+
+    uow.commit().await?;
+    Ok(history)
+}
+
+/// The outcome of a deduction.
+#[derive(Debug)]
+pub struct ClassDeduction {
+    pub class: Class,
+    /// The created `class_deduction_history` row.
+    pub class_deduction_history_id: i64,
+    /// This deduction took the balance from above the low balance threshold
+    /// to or below it, so the user is warned once rather than on every class.
+    pub low_balance_reached: bool,
+}
+
+/// Deducts one class attended on `deducted_on`, a day in the user's offset.
+/// Deducting from a subscription that had expired by that day is refused only
+/// under [`ExpiredDeductionPolicy::Block`].
+pub async fn deduct_class(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_id: i64,
+    telegram_user_id: i64,
+    deducted_on: NaiveDate,
+    expired_policy: ExpiredDeductionPolicy,
+) -> anyhow::Result<ClassDeduction> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let (user_id, utc_offset) = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => (u.user_id, u.utc_offset()),
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    if deducted_on > utils::today_in(utc_offset) {
+        bail!(FutureDateError);
+    }
+
+    let deduction = deduct_class_in(
+        &mut uow,
+        class_id,
+        user_id,
+        deducted_on,
+        utc_offset,
+        expired_policy,
+    )
+    .await?;
+
+    uow.commit().await?;
+    Ok(deduction)
+}
+
+/// The body of [`deduct_class`], for services that deduct as part of a
+/// larger transaction.
+pub(crate) async fn deduct_class_in(
+    uow: &mut UnitOfWork<'_>,
+    class_id: i64,
+    user_id: i64,
+    deducted_on: NaiveDate,
+    utc_offset: FixedOffset,
+    expired_policy: ExpiredDeductionPolicy,
+) -> anyhow::Result<ClassDeduction> {
     let class = match uow
         .class_repo()
         .await?
-        .get_user_class_by_id(class_id, user_id)
+        .get_user_class_by_id(class_id, user_id, utils::today())
         .await?
     {
         Some(c) => c,
@@ -131,26 +342,149 @@ pub async fn deduct_class(
         }
     };
 
-    if class.quantity == 0 {
+    if class.archived_at.is_some() {
+        bail!(ClassArchivedError);
+    }
+
+    if expired_policy == ExpiredDeductionPolicy::Block
+        && class.is_expired(deducted_on)
+        && let Some(valid_until) = class.valid_until_date()
+    {
+        bail!(ClassExpiredError(utils::format_date(valid_until)));
+    }
+
+    // Packs are consumed oldest first, skipping the ones that had expired by
+    // the day of the class: classes left in those are lost, whatever the
+    // policy for the subscription as a whole.
+    let packs = uow
+        .class_pack_repo()
+        .await?
+        .get_open_packs(class.class_id)
+        .await?;
+    let Some(pack) = packs.iter().find(|p| !p.is_expired(deducted_on)) else {
         bail!(NotEnoughClassQuantityToDeductError(class.quantity));
+    };
+    let class_pack_id = pack.class_pack_id;
+
+    uow.class_pack_repo().await?.consume(class_pack_id).await?;
+    let updated_class = sync_class_quantity(uow, class.class_id).await?;
+
+    // Backdated deductions keep the current time of day so that ordering
+    // within a day stays stable. The history stores UTC, the day is the
+    // user's.
+    let now = Utc::now().with_timezone(&utc_offset);
+    let created_at = (deducted_on != now.date_naive())
+        .then(|| {
+            utc_offset
+                .from_local_datetime(&deducted_on.and_time(now.time()))
+                .earliest()
+        })
+        .flatten()
+        .map(|dt| dt.naive_utc().format("%Y-%m-%d %H:%M:%S").to_string());
+    let class_deduction_history_id = uow
+        .class_deduction_history_repo()
+        .await?
+        .create(class_id, user_id, Some(class_pack_id), created_at)
+        .await?;
+
+    let low_balance_reached = updated_class.is_low_balance() && !class.is_low_balance();
+    Ok(ClassDeduction {
+        class: updated_class,
+        class_deduction_history_id,
+        low_balance_reached,
+    })
+}
+
+/// Reverts a deduction made less than `window` ago: restores one class and
+/// removes the history row. Refuses if the class was touched after the
+/// deduction (another deduction or a quantity update).
+pub async fn undo_class_deduction(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_deduction_history_id: i64,
+    telegram_user_id: i64,
+    window: Duration,
+) -> anyhow::Result<Class> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let history = match uow
+        .class_deduction_history_repo()
+        .await?
+        .get_user_history_by_id(class_deduction_history_id, user_id)
+        .await?
+    {
+        Some(h) => h,
+        None => {
+            bail!(DeductionNotFoundError);
+        }
+    };
+
+    let recorded_at = NaiveDateTime::parse_from_str(&history.recorded_at, "%Y-%m-%d %H:%M:%S")?;
+    let elapsed = Utc::now().naive_utc() - recorded_at;
+    if elapsed.to_std().unwrap_or_default() > window {
+        bail!(DeductionUndoExpiredError);
     }
 
-    let new_quantity = class.quantity - 1;
-    let updated_class = uow
+    let class = match uow
         .class_repo()
         .await?
-        .update_quantity(class.class_id, new_quantity)
-        .await?;
+        .get_user_class_by_id(history.class_id, user_id, utils::today())
+        .await?
+    {
+        Some(c) => c,
+        None => {
+            bail!(ClassNotFoundError);
+        }
+    };
+
+    let last_history_id = uow
+        .class_deduction_history_repo()
+        .await?
+        .get_last_history(history.class_id, user_id)
+        .await?
+        .map(|h| h.class_deduction_history_id);
+    if last_history_id != Some(history.class_deduction_history_id)
+        || class.updated_at > history.recorded_at
+    {
+        bail!(ClassChangedSinceDeductionError);
+    }
+
+    match history.class_pack_id {
+        Some(class_pack_id) => {
+            uow.class_pack_repo().await?.restore(class_pack_id).await?;
+        }
+        // Deductions made before packs existed (or whose pack was removed)
+        // give the class back as a pack of one.
+        None => {
+            uow.class_pack_repo()
+                .await?
+                .create(class.class_id, user_id, 1, utils::today(), None, None)
+                .await?;
+        }
+    }
 
     uow.class_deduction_history_repo()
         .await?
-        .create(class_id, user_id)
+        .delete(history.class_deduction_history_id)
         .await?;
 
+    let updated_class = sync_class_quantity(&mut uow, class.class_id).await?;
+
     uow.commit().await?;
     Ok(updated_class)
 }
 
+/// Sets the balance to `quantity`, adjusting the packs to match.
 pub async fn update_class_quantity(
     db_pool: Arc<Pool<Sqlite>>,
     class_id: i64,
@@ -173,7 +507,149 @@ pub async fn update_class_quantity(
     let class = match uow
         .class_repo()
         .await?
-        .get_user_class_by_id(class_id, user_id)
+        .get_user_class_by_id(class_id, user_id, utils::today())
+        .await?
+    {
+        Some(c) => c,
+        None => {
+            bail!(ClassNotFoundError);
+        }
+    };
+
+    if class.archived_at.is_some() {
+        bail!(ClassArchivedError);
+    }
+
+    // A manual correction: extra classes become a new pack, missing ones are
+    // taken from the oldest unexpired packs without a deduction record.
+    let current = i64::from(class.quantity);
+    let target = i64::from(quantity);
+    if target > current {
+        uow.class_pack_repo()
+            .await?
+            .create(
+                class.class_id,
+                user_id,
+                target - current,
+                utils::today(),
+                None,
+                None,
+            )
+            .await?;
+    } else {
+        let mut surplus = current - target;
+        let packs = uow
+            .class_pack_repo()
+            .await?
+            .get_open_packs(class.class_id)
+            .await?;
+        let today = utils::today();
+        for pack in packs.into_iter().filter(|p| !p.is_expired(today)) {
+            if surplus == 0 {
+                break;
+            }
+            let taken = pack.remaining.min(surplus);
+            uow.class_pack_repo()
+                .await?
+                .set_remaining(pack.class_pack_id, pack.remaining - taken)
+                .await?;
+            surplus -= taken;
+        }
+    }
+
+    let updated_class = sync_class_quantity(&mut uow, class.class_id).await?;
+
+    uow.commit().await?;
+    Ok(updated_class)
+}
+
+pub async fn get_archived_classes_by_user_id(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+) -> anyhow::Result<Vec<Class>> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let classes = uow
+        .class_repo()
+        .await?
+        .get_user_archived_classes(user_id, utils::today())
+        .await?;
+    Ok(classes)
+}
+
+pub async fn archive_class(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_id: i64,
+    telegram_user_id: i64,
+) -> anyhow::Result<Class> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let class = match uow
+        .class_repo()
+        .await?
+        .get_user_class_by_id(class_id, user_id, utils::today())
+        .await?
+    {
+        Some(c) => c,
+        None => {
+            bail!(ClassNotFoundError);
+        }
+    };
+
+    let updated_class = uow
+        .class_repo()
+        .await?
+        .set_archived(class.class_id, true, utils::today())
+        .await?;
+
+    uow.commit().await?;
+    Ok(updated_class)
+}
+
+pub async fn restore_class(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_id: i64,
+    telegram_user_id: i64,
+) -> anyhow::Result<Class> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let class = match uow
+        .class_repo()
+        .await?
+        .get_user_class_by_id(class_id, user_id, utils::today())
         .await?
     {
         Some(c) => c,
@@ -185,9 +661,972 @@ pub async fn update_class_quantity(
     let updated_class = uow
         .class_repo()
         .await?
-        .update_quantity(class.class_id, quantity)
+        .set_archived(class.class_id, false, utils::today())
         .await?;
 
     uow.commit().await?;
     Ok(updated_class)
 }
+
+/// Permanently removes the class and its deduction history.
+pub async fn delete_class(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_id: i64,
+    telegram_user_id: i64,
+) -> anyhow::Result<Class> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let class = match uow
+        .class_repo()
+        .await?
+        .get_user_class_by_id(class_id, user_id, utils::today())
+        .await?
+    {
+        Some(c) => c,
+        None => {
+            bail!(ClassNotFoundError);
+        }
+    };
+
+    // Deduction history goes away with the class via `on delete cascade`.
+    uow.class_repo().await?.delete(class.class_id).await?;
+
+    uow.commit().await?;
+    Ok(class)
+}
+
+pub async fn rename_class(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_id: i64,
+    telegram_user_id: i64,
+    name: String,
+) -> anyhow::Result<Class> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let class = match uow
+        .class_repo()
+        .await?
+        .get_user_class_by_id(class_id, user_id, utils::today())
+        .await?
+    {
+        Some(c) => c,
+        None => {
+            bail!(ClassNotFoundError);
+        }
+    };
+
+    let updated_class = match uow
+        .class_repo()
+        .await?
+        .rename(class.class_id, name, utils::today())
+        .await
+    {
+        Ok(class) => class,
+        Err(err) => return Err(map_class_name_error(err)),
+    };
+
+    uow.commit().await?;
+    Ok(updated_class)
+}
+
+pub async fn set_class_valid_until(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_id: i64,
+    telegram_user_id: i64,
+    valid_until: Option<NaiveDate>,
+) -> anyhow::Result<Class> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let class = match uow
+        .class_repo()
+        .await?
+        .get_user_class_by_id(class_id, user_id, utils::today())
+        .await?
+    {
+        Some(c) => c,
+        None => {
+            bail!(ClassNotFoundError);
+        }
+    };
+
+    let updated_class = uow
+        .class_repo()
+        .await?
+        .set_valid_until(
+            class.class_id,
+            valid_until.map(|d| d.to_string()),
+            utils::today(),
+        )
+        .await?;
+
+    uow.commit().await?;
+    Ok(updated_class)
+}
+
+pub async fn set_class_low_balance_threshold(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_id: i64,
+    telegram_user_id: i64,
+    low_balance_threshold: Option<u8>,
+) -> anyhow::Result<Class> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let class = match uow
+        .class_repo()
+        .await?
+        .get_user_class_by_id(class_id, user_id, utils::today())
+        .await?
+    {
+        Some(c) => c,
+        None => {
+            bail!(ClassNotFoundError);
+        }
+    };
+
+    let updated_class = uow
+        .class_repo()
+        .await?
+        .set_low_balance_threshold(class.class_id, low_balance_threshold, utils::today())
+        .await?;
+
+    uow.commit().await?;
+    Ok(updated_class)
+}
+
+/// Pauses the subscription for `days` days starting today: the expiry dates
+/// of the class and its open packs move forward by the same amount and the
+/// freeze is recorded.
+pub async fn freeze_class(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_id: i64,
+    telegram_user_id: i64,
+    days: u16,
+) -> anyhow::Result<Class> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let class = match uow
+        .class_repo()
+        .await?
+        .get_user_class_by_id(class_id, user_id, utils::today())
+        .await?
+    {
+        Some(c) => c,
+        None => {
+            bail!(ClassNotFoundError);
+        }
+    };
+
+    let today = utils::today();
+    let Some(valid_until) = class.valid_until_date() else {
+        bail!(ClassHasNoExpiryError);
+    };
+    if class.is_expired(today) {
+        bail!(ClassExpiredError(utils::format_date(valid_until)));
+    }
+    // A second freeze over the same days would push the expiry date twice.
+    if let Some(freeze) = uow
+        .class_freeze_repo()
+        .await?
+        .get_active_freeze(class.class_id, today)
+        .await?
+    {
+        let frozen_until = freeze
+            .frozen_until
+            .parse()
+            .map(utils::format_date)
+            .unwrap_or(freeze.frozen_until);
+        bail!(ClassAlreadyFrozenError(frozen_until));
+    }
+
+    let new_valid_until = valid_until + Days::new(days.into());
+    let frozen_until = today + Days::new(u64::from(days).saturating_sub(1));
+
+    let updated_class = uow
+        .class_repo()
+        .await?
+        .set_valid_until(class.class_id, Some(new_valid_until.to_string()), today)
+        .await?;
+
+    uow.class_pack_repo()
+        .await?
+        .extend_validity(class.class_id, days)
+        .await?;
+
+    uow.class_freeze_repo()
+        .await?
+        .create(class.class_id, user_id, today, frozen_until, days.into())
+        .await?;
+
+    uow.commit().await?;
+    Ok(updated_class)
+}
+
+pub async fn get_class_freezes(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_id: i64,
+    telegram_user_id: i64,
+) -> anyhow::Result<Vec<ClassFreeze>> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let freezes = uow
+        .class_freeze_repo()
+        .await?
+        .get_freezes(class_id, user_id)
+        .await?;
+    Ok(freezes)
+}
+
+/// Records the purchase of a new pack of `quantity` classes. A later expiry
+/// date of the pack also extends the subscription of the class.
+pub async fn top_up_class(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_id: i64,
+    telegram_user_id: i64,
+    quantity: u8,
+    valid_until: Option<NaiveDate>,
+    price: Option<PackPrice>,
+) -> anyhow::Result<Class> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let class = match uow
+        .class_repo()
+        .await?
+        .get_user_class_by_id(class_id, user_id, utils::today())
+        .await?
+    {
+        Some(c) => c,
+        None => {
+            bail!(ClassNotFoundError);
+        }
+    };
+
+    if class.archived_at.is_some() {
+        bail!(ClassArchivedError);
+    }
+
+    if u16::from(class.quantity) + u16::from(quantity) > u16::from(u8::MAX) {
+        bail!(ClassQuantityLimitError(u8::MAX));
+    }
+
+    uow.class_pack_repo()
+        .await?
+        .create(
+            class.class_id,
+            user_id,
+            quantity.into(),
+            utils::today(),
+            valid_until,
+            price.as_ref(),
+        )
+        .await?;
+
+    if let Some(valid_until) = valid_until
+        && class.valid_until_date().is_none_or(|d| d < valid_until)
+    {
+        uow.class_repo()
+            .await?
+            .set_valid_until(
+                class.class_id,
+                Some(valid_until.to_string()),
+                utils::today(),
+            )
+            .await?;
+    }
+
+    let updated_class = sync_class_quantity(&mut uow, class.class_id).await?;
+
+    uow.commit().await?;
+    Ok(updated_class)
+}
+
+pub async fn get_class_packs(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_id: i64,
+    telegram_user_id: i64,
+) -> anyhow::Result<Vec<ClassPack>> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let packs = uow
+        .class_pack_repo()
+        .await?
+        .get_user_packs(class_id, user_id)
+        .await?;
+    Ok(packs)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use sqlx::{Pool, Row, Sqlite};
+
+    use chrono::{Datelike, Days, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone};
+
+    use crate::{
+        config::ExpiredDeductionPolicy,
+        errors::*,
+        services::{job::set_user_utc_offset, user::add_user},
+        test_utils, utils,
+    };
+
+    use super::{
+        ClassDeduction, HISTORY_PAGE_SIZE, add_class, archive_class, deduct_class, delete_class,
+        freeze_class, get_archived_classes_by_user_id, get_class_by_id,
+        get_class_deduction_history_page, get_class_freezes, get_class_packs,
+        get_classes_by_user_id, rename_class, restore_class, saturating_quantity,
+        set_class_low_balance_threshold, set_class_valid_until, set_deduction_note, top_up_class,
+        undo_class_deduction, update_class_quantity,
+    };
+
+    const WINDOW: Duration = Duration::from_secs(300);
+
+    async fn setup_class(quantity: u8) -> anyhow::Result<(Pool<Sqlite>, Arc<Pool<Sqlite>>, i64)> {
+        let pool = test_utils::setup_db().await;
+        let arc_pool: Arc<Pool<Sqlite>> = Arc::new(pool.clone());
+        add_user(arc_pool.clone(), 1, "alice").await?;
+        let class_id = add_class(arc_pool.clone(), "Йога".into(), quantity, None, None, 1).await?;
+        Ok((pool, arc_pool, class_id))
+    }
+
+    async fn deduct(arc_pool: &Arc<Pool<Sqlite>>, class_id: i64) -> anyhow::Result<ClassDeduction> {
+        deduct_class(
+            arc_pool.clone(),
+            class_id,
+            1,
+            utils::today(),
+            ExpiredDeductionPolicy::Warn,
+        )
+        .await
+    }
+
+    async fn history_count(pool: &Pool<Sqlite>, class_id: i64) -> anyhow::Result<i64> {
+        let row =
+            sqlx::query("SELECT COUNT(*) as cnt FROM class_deduction_history WHERE class_id = ?")
+                .bind(class_id)
+                .fetch_one(pool)
+                .await?;
+        Ok(row.get::<i64, _>("cnt"))
+    }
+
+    #[tokio::test]
+    async fn test_undo_deduction_restores_quantity() -> anyhow::Result<()> {
+        let (pool, arc_pool, class_id) = setup_class(5).await?;
+
+        let ClassDeduction {
+            class,
+            class_deduction_history_id: history_id,
+            ..
+        } = deduct(&arc_pool, class_id).await?;
+        assert_eq!(class.quantity, 4);
+        assert_eq!(history_count(&pool, class_id).await?, 1);
+
+        let class = undo_class_deduction(arc_pool.clone(), history_id, 1, WINDOW).await?;
+        assert_eq!(class.quantity, 5);
+        assert_eq!(history_count(&pool, class_id).await?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_undo_deduction_refused_after_newer_deduction() -> anyhow::Result<()> {
+        let (pool, arc_pool, class_id) = setup_class(5).await?;
+
+        let first_history_id = deduct(&arc_pool, class_id)
+            .await?
+            .class_deduction_history_id;
+        deduct(&arc_pool, class_id).await?;
+
+        let err = undo_class_deduction(arc_pool.clone(), first_history_id, 1, WINDOW)
+            .await
+            .expect_err("undo should be refused");
+        assert!(
+            err.downcast_ref::<ClassChangedSinceDeductionError>()
+                .is_some()
+        );
+        assert_eq!(history_count(&pool, class_id).await?, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_undo_deduction_refused_after_window() -> anyhow::Result<()> {
+        let (pool, arc_pool, class_id) = setup_class(5).await?;
+
+        let history_id = deduct(&arc_pool, class_id)
+            .await?
+            .class_deduction_history_id;
+        sqlx::query(
+            "UPDATE class_deduction_history SET recorded_at = datetime('now', '-1 hour')
+             WHERE class_deduction_history_id = ?",
+        )
+        .bind(history_id)
+        .execute(&pool)
+        .await?;
+
+        let err = undo_class_deduction(arc_pool.clone(), history_id, 1, WINDOW)
+            .await
+            .expect_err("undo should be refused");
+        assert!(err.downcast_ref::<DeductionUndoExpiredError>().is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_undo_deduction_of_other_user_not_found() -> anyhow::Result<()> {
+        let (_, arc_pool, class_id) = setup_class(5).await?;
+        add_user(arc_pool.clone(), 2, "bob").await?;
+
+        let history_id = deduct(&arc_pool, class_id)
+            .await?
+            .class_deduction_history_id;
+
+        let err = undo_class_deduction(arc_pool.clone(), history_id, 2, WINDOW)
+            .await
+            .expect_err("undo should be refused");
+        assert!(err.downcast_ref::<DeductionNotFoundError>().is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_archive_hides_class_and_keeps_history() -> anyhow::Result<()> {
+        let (pool, arc_pool, class_id) = setup_class(5).await?;
+        deduct(&arc_pool, class_id).await?;
+
+        archive_class(arc_pool.clone(), class_id, 1).await?;
+        assert!(
+            get_classes_by_user_id(arc_pool.clone(), 1)
+                .await?
+                .is_empty()
+        );
+        let archived = get_archived_classes_by_user_id(arc_pool.clone(), 1).await?;
+        assert_eq!(archived.len(), 1);
+        assert_eq!(history_count(&pool, class_id).await?, 1);
+
+        let err = deduct(&arc_pool, class_id)
+            .await
+            .expect_err("deduction should be refused");
+        assert!(err.downcast_ref::<ClassArchivedError>().is_some());
+
+        restore_class(arc_pool.clone(), class_id, 1).await?;
+        assert_eq!(get_classes_by_user_id(arc_pool.clone(), 1).await?.len(), 1);
+        assert!(
+            get_archived_classes_by_user_id(arc_pool.clone(), 1)
+                .await?
+                .is_empty()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delete_class_removes_history() -> anyhow::Result<()> {
+        let (pool, arc_pool, class_id) = setup_class(5).await?;
+        deduct(&arc_pool, class_id).await?;
+
+        delete_class(arc_pool.clone(), class_id, 1).await?;
+        assert!(
+            get_classes_by_user_id(arc_pool.clone(), 1)
+                .await?
+                .is_empty()
+        );
+        assert_eq!(history_count(&pool, class_id).await?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rename_class() -> anyhow::Result<()> {
+        let (pool, arc_pool, class_id) = setup_class(5).await?;
+        deduct(&arc_pool, class_id).await?;
+        add_class(arc_pool.clone(), "Пилатес".into(), 3, None, None, 1).await?;
+
+        let err = rename_class(arc_pool.clone(), class_id, 1, "Пилатес".into())
+            .await
+            .expect_err("rename should be refused");
+        assert!(err.downcast_ref::<DuplicateClassNameError>().is_some());
+
+        let class = rename_class(arc_pool.clone(), class_id, 1, "Хатха-йога".into()).await?;
+        assert_eq!(class.name, "Хатха-йога");
+        assert_eq!(class.quantity, 4);
+        assert_eq!(history_count(&pool, class_id).await?, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_class_names_are_unique_per_user() -> anyhow::Result<()> {
+        let (_, arc_pool, _) = setup_class(5).await?;
+        add_user(arc_pool.clone(), 2, "bob").await?;
+
+        add_class(arc_pool.clone(), "Йога".into(), 3, None, None, 2).await?;
+
+        let err = add_class(arc_pool.clone(), "Йога".into(), 3, None, None, 1)
+            .await
+            .expect_err("duplicate name should be refused");
+        assert!(err.downcast_ref::<DuplicateClassNameError>().is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_balance_drops_when_pack_expires() -> anyhow::Result<()> {
+        let (pool, arc_pool, class_id) = setup_class(5).await?;
+        let today = utils::today();
+        top_up_class(arc_pool.clone(), class_id, 1, 3, Some(today), None).await?;
+        let classes = get_classes_by_user_id(arc_pool.clone(), 1).await?;
+        assert_eq!(classes[0].quantity, 8);
+
+        // The top-up runs out overnight, with nothing done to the class.
+        sqlx::query("UPDATE class_pack SET valid_until = ? WHERE valid_until IS NOT NULL")
+            .bind((today - Days::new(1)).to_string())
+            .execute(&pool)
+            .await?;
+
+        let classes = get_classes_by_user_id(arc_pool.clone(), 1).await?;
+        assert_eq!(classes[0].quantity, 5);
+        let class = get_class_by_id(arc_pool.clone(), class_id, 1).await?;
+        assert_eq!(class.quantity, 5);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deduct_expired_class_respects_policy() -> anyhow::Result<()> {
+        let (_, arc_pool, class_id) = setup_class(5).await?;
+        let yesterday = utils::today() - Days::new(1);
+        set_class_valid_until(arc_pool.clone(), class_id, 1, Some(yesterday)).await?;
+
+        let err = deduct_class(
+            arc_pool.clone(),
+            class_id,
+            1,
+            utils::today(),
+            ExpiredDeductionPolicy::Block,
+        )
+        .await
+        .expect_err("deduction should be refused");
+        assert!(err.downcast_ref::<ClassExpiredError>().is_some());
+
+        let class = deduct(&arc_pool, class_id).await?.class;
+        assert_eq!(class.quantity, 4);
+        assert!(class.is_expired(utils::today()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_freeze_class_extends_validity() -> anyhow::Result<()> {
+        let (_, arc_pool, class_id) = setup_class(5).await?;
+
+        let err = freeze_class(arc_pool.clone(), class_id, 1, 7)
+            .await
+            .expect_err("freeze should be refused");
+        assert!(err.downcast_ref::<ClassHasNoExpiryError>().is_some());
+
+        let valid_until = utils::today() + Days::new(10);
+        set_class_valid_until(arc_pool.clone(), class_id, 1, Some(valid_until)).await?;
+
+        let class = freeze_class(arc_pool.clone(), class_id, 1, 7).await?;
+        assert_eq!(class.valid_until_date(), Some(valid_until + Days::new(7)));
+
+        let freezes = get_class_freezes(arc_pool.clone(), class_id, 1).await?;
+        assert_eq!(freezes.len(), 1);
+        assert_eq!(freezes[0].days, 7);
+        assert_eq!(freezes[0].frozen_from, utils::today().to_string());
+
+        let err = freeze_class(arc_pool.clone(), class_id, 1, 3)
+            .await
+            .expect_err("the class is already frozen");
+        assert!(err.downcast_ref::<ClassAlreadyFrozenError>().is_some());
+        let class = get_class_by_id(arc_pool.clone(), class_id, 1).await?;
+        assert_eq!(class.valid_until_date(), Some(valid_until + Days::new(7)));
+        assert_eq!(
+            get_class_freezes(arc_pool.clone(), class_id, 1)
+                .await?
+                .len(),
+            1
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_low_balance_threshold() -> anyhow::Result<()> {
+        let (_, arc_pool, class_id) = setup_class(3).await?;
+
+        let deduction = deduct(&arc_pool, class_id).await?;
+        assert_eq!(deduction.class.low_balance_threshold, Some(1));
+        assert!(!deduction.class.is_low_balance());
+        assert!(!deduction.low_balance_reached);
+
+        let deduction = deduct(&arc_pool, class_id).await?;
+        assert!(deduction.class.is_low_balance());
+        assert!(deduction.low_balance_reached);
+
+        // Already below the threshold: no second warning.
+        let deduction = deduct(&arc_pool, class_id).await?;
+        assert!(deduction.class.is_low_balance());
+        assert!(!deduction.low_balance_reached);
+        assert_eq!(deduction.class.quantity, 0);
+
+        update_class_quantity(arc_pool.clone(), class_id, 1, 1).await?;
+        set_class_low_balance_threshold(arc_pool.clone(), class_id, 1, None).await?;
+        let deduction = deduct(&arc_pool, class_id).await?;
+        assert_eq!(deduction.class.quantity, 0);
+        assert!(!deduction.class.is_low_balance());
+        assert!(!deduction.low_balance_reached);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_backdated_deduction() -> anyhow::Result<()> {
+        let (pool, arc_pool, class_id) = setup_class(5).await?;
+        let yesterday = utils::today() - Days::new(1);
+
+        let history_id = deduct_class(
+            arc_pool.clone(),
+            class_id,
+            1,
+            yesterday,
+            ExpiredDeductionPolicy::Warn,
+        )
+        .await?
+        .class_deduction_history_id;
+
+        let row = sqlx::query(
+            "SELECT created_at FROM class_deduction_history WHERE class_deduction_history_id = ?",
+        )
+        .bind(history_id)
+        .fetch_one(&pool)
+        .await?;
+        let created_at: String = row.get("created_at");
+        let created_at = NaiveDateTime::parse_from_str(&created_at, "%Y-%m-%d %H:%M:%S")?;
+        assert_eq!(Local.from_utc_datetime(&created_at).date_naive(), yesterday);
+
+        // The undo window counts from when the deduction was made, not from the class date.
+        let class = undo_class_deduction(arc_pool.clone(), history_id, 1, WINDOW).await?;
+        assert_eq!(class.quantity, 5);
+
+        let err = deduct_class(
+            arc_pool.clone(),
+            class_id,
+            1,
+            utils::today() + Days::new(1),
+            ExpiredDeductionPolicy::Warn,
+        )
+        .await
+        .expect_err("deduction should be refused");
+        assert!(err.downcast_ref::<FutureDateError>().is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_top_up_packs_are_consumed_oldest_first() -> anyhow::Result<()> {
+        let (_, arc_pool, class_id) = setup_class(1).await?;
+
+        let class = top_up_class(arc_pool.clone(), class_id, 1, 2, None, None).await?;
+        assert_eq!(class.quantity, 3);
+
+        let ClassDeduction {
+            class,
+            class_deduction_history_id: history_id,
+            ..
+        } = deduct(&arc_pool, class_id).await?;
+        assert_eq!(class.quantity, 2);
+        let packs = get_class_packs(arc_pool.clone(), class_id, 1).await?;
+        let remaining: Vec<i64> = packs.iter().map(|p| p.remaining).collect();
+        assert_eq!(remaining, vec![0, 2]);
+
+        // Undo gives the class back to the pack it was taken from.
+        undo_class_deduction(arc_pool.clone(), history_id, 1, WINDOW).await?;
+        let packs = get_class_packs(arc_pool.clone(), class_id, 1).await?;
+        let remaining: Vec<i64> = packs.iter().map(|p| p.remaining).collect();
+        assert_eq!(remaining, vec![1, 2]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deduction_skips_expired_pack() -> anyhow::Result<()> {
+        let (pool, arc_pool, class_id) = setup_class(1).await?;
+        let today = utils::today();
+        sqlx::query("UPDATE class_pack SET valid_until = ? WHERE class_id = ?")
+            .bind((today - Days::new(1)).to_string())
+            .bind(class_id)
+            .execute(&pool)
+            .await?;
+
+        let class = top_up_class(
+            arc_pool.clone(),
+            class_id,
+            1,
+            1,
+            Some(today + Days::new(30)),
+            None,
+        )
+        .await?;
+        assert_eq!(class.valid_until_date(), Some(today + Days::new(30)));
+
+        let class = deduct_class(
+            arc_pool.clone(),
+            class_id,
+            1,
+            today,
+            ExpiredDeductionPolicy::Block,
+        )
+        .await?
+        .class;
+        // The class left in the expired pack no longer counts.
+        assert_eq!(class.quantity, 0);
+        let packs = get_class_packs(arc_pool.clone(), class_id, 1).await?;
+        let remaining: Vec<i64> = packs.iter().map(|p| p.remaining).collect();
+        assert_eq!(remaining, vec![1, 0]);
+
+        for policy in [ExpiredDeductionPolicy::Block, ExpiredDeductionPolicy::Warn] {
+            let err = deduct_class(arc_pool.clone(), class_id, 1, today, policy)
+                .await
+                .expect_err("only an expired pack is left");
+            assert_eq!(
+                err.downcast_ref::<NotEnoughClassQuantityToDeductError>(),
+                Some(&NotEnoughClassQuantityToDeductError(0))
+            );
+        }
+        let packs = get_class_packs(arc_pool.clone(), class_id, 1).await?;
+        let remaining: Vec<i64> = packs.iter().map(|p| p.remaining).collect();
+        assert_eq!(remaining, vec![1, 0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_saturating_quantity() {
+        assert_eq!(saturating_quantity(0), 0);
+        assert_eq!(saturating_quantity(42), 42);
+        assert_eq!(saturating_quantity(255), 255);
+        assert_eq!(saturating_quantity(300), 255);
+        assert_eq!(saturating_quantity(-1), 0);
+    }
+
+    #[tokio::test]
+    async fn test_update_quantity_adjusts_packs() -> anyhow::Result<()> {
+        let (_, arc_pool, class_id) = setup_class(3).await?;
+        top_up_class(arc_pool.clone(), class_id, 1, 2, None, None).await?;
+
+        let class = update_class_quantity(arc_pool.clone(), class_id, 1, 1).await?;
+        assert_eq!(class.quantity, 1);
+        let packs = get_class_packs(arc_pool.clone(), class_id, 1).await?;
+        let remaining: Vec<i64> = packs.iter().map(|p| p.remaining).collect();
+        assert_eq!(remaining, vec![0, 1]);
+
+        let class = update_class_quantity(arc_pool.clone(), class_id, 1, 4).await?;
+        assert_eq!(class.quantity, 4);
+        assert_eq!(
+            get_class_packs(arc_pool.clone(), class_id, 1).await?.len(),
+            3
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deduction_history_pages_newest_first() -> anyhow::Result<()> {
+        let (_, arc_pool, class_id) = setup_class(40).await?;
+        let today = utils::today();
+        let total = HISTORY_PAGE_SIZE + 5;
+        // Backdated only, so that every row carries its day explicitly.
+        for days_ago in (1..=total).rev() {
+            deduct_class(
+                arc_pool.clone(),
+                class_id,
+                1,
+                today - Days::new(days_ago.into()),
+                ExpiredDeductionPolicy::Warn,
+            )
+            .await?;
+        }
+
+        let first =
+            get_class_deduction_history_page(arc_pool.clone(), class_id, 1, None, 0).await?;
+        assert_eq!(first.total, total);
+        assert_eq!(first.histories.len(), HISTORY_PAGE_SIZE as usize);
+        assert!(first.has_next());
+        assert!(
+            first.histories[0]
+                .created_at
+                .starts_with(&(today - Days::new(1)).to_string())
+        );
+
+        let second =
+            get_class_deduction_history_page(arc_pool.clone(), class_id, 1, None, 1).await?;
+        assert_eq!(second.histories.len(), 5);
+        assert!(!second.has_next());
+        let oldest = today - Days::new(total.into());
+        assert!(
+            second.histories[4]
+                .created_at
+                .starts_with(&oldest.to_string())
+        );
+
+        let month = today.with_day(1).unwrap();
+        let in_month = (today - month).num_days();
+        let filtered =
+            get_class_deduction_history_page(arc_pool.clone(), class_id, 1, Some(month), 0).await?;
+        assert_eq!(i64::from(filtered.total), in_month.min(total.into()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deduction_history_month_in_user_offset() -> anyhow::Result<()> {
+        let (pool, arc_pool, class_id) = setup_class(5).await?;
+        set_user_utc_offset(
+            arc_pool.clone(),
+            1,
+            FixedOffset::east_opt(3 * 3600).unwrap(),
+        )
+        .await?;
+        let history_id = deduct(&arc_pool, class_id)
+            .await?
+            .class_deduction_history_id;
+        // 01:30 on October 1 for the user.
+        sqlx::query(
+            "UPDATE class_deduction_history SET created_at = '2026-09-30 22:30:00'
+             WHERE class_deduction_history_id = ?",
+        )
+        .bind(history_id)
+        .execute(&pool)
+        .await?;
+
+        let october = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+        let page =
+            get_class_deduction_history_page(arc_pool.clone(), class_id, 1, Some(october), 0)
+                .await?;
+        assert_eq!(page.total, 1);
+        assert_eq!(page.histories.len(), 1);
+
+        let september = NaiveDate::from_ymd_opt(2026, 9, 1).unwrap();
+        let page =
+            get_class_deduction_history_page(arc_pool.clone(), class_id, 1, Some(september), 0)
+                .await?;
+        assert_eq!(page.total, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deduction_note() -> anyhow::Result<()> {
+        let (_, arc_pool, class_id) = setup_class(5).await?;
+        add_user(arc_pool.clone(), 2, "bob").await?;
+        let history_id = deduct(&arc_pool, class_id)
+            .await?
+            .class_deduction_history_id;
+
+        let history =
+            set_deduction_note(arc_pool.clone(), history_id, 1, Some("Отработка".into())).await?;
+        assert_eq!(history.note.as_deref(), Some("Отработка"));
+
+        let page = get_class_deduction_history_page(arc_pool.clone(), class_id, 1, None, 0).await?;
+        assert_eq!(page.histories[0].note.as_deref(), Some("Отработка"));
+
+        let err = set_deduction_note(arc_pool.clone(), history_id, 2, None)
+            .await
+            .expect_err("other users cannot edit the note");
+        assert!(err.downcast_ref::<DeductionNotFoundError>().is_some());
+
+        let history = set_deduction_note(arc_pool.clone(), history_id, 1, None).await?;
+        assert!(history.note.is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,538 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::bail;
+use chrono::{DateTime, Datelike, Days, NaiveDateTime, NaiveTime, TimeDelta, Utc, Weekday};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+    config::ExpiredDeductionPolicy,
+    errors::*,
+    repositories::{
+        class::Class,
+        class_attendance::AttendanceStatus,
+        class_schedule::{ClassSchedule, ScheduledSlot},
+    },
+    services::class::{ClassDeduction, deduct_class_in},
+    uow::UnitOfWork,
+    utils,
+};
+
+/// Adds the slots to the weekly timetable of a class; slots it already has
+/// are skipped. Returns the whole timetable.
+pub async fn add_class_schedule(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_id: i64,
+    telegram_user_id: i64,
+    weekdays: Vec<Weekday>,
+    starts_at: NaiveTime,
+) -> anyhow::Result<Vec<ClassSchedule>> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let class = match uow
+        .class_repo()
+        .await?
+        .get_user_class_by_id(class_id, user_id, utils::today())
+        .await?
+    {
+        Some(c) => c,
+        None => {
+            bail!(ClassNotFoundError);
+        }
+    };
+
+    for weekday in weekdays {
+        uow.class_schedule_repo()
+            .await?
+            .create(class.class_id, user_id, weekday, starts_at)
+            .await?;
+    }
+
+    let schedules = uow
+        .class_schedule_repo()
+        .await?
+        .get_class_schedules(class.class_id, user_id)
+        .await?;
+
+    uow.commit().await?;
+    Ok(schedules)
+}
+
+pub async fn get_class_schedules(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_id: i64,
+    telegram_user_id: i64,
+) -> anyhow::Result<Vec<ClassSchedule>> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let schedules = uow
+        .class_schedule_repo()
+        .await?
+        .get_class_schedules(class_id, user_id)
+        .await?;
+    Ok(schedules)
+}
+
+/// Removes a slot and returns the id of its class.
+pub async fn remove_class_schedule(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_schedule_id: i64,
+    telegram_user_id: i64,
+) -> anyhow::Result<i64> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let schedule = match uow
+        .class_schedule_repo()
+        .await?
+        .get_user_schedule_by_id(class_schedule_id, user_id)
+        .await?
+    {
+        Some(s) => s,
+        None => {
+            bail!(ClassScheduleNotFoundError);
+        }
+    };
+
+    uow.class_schedule_repo()
+        .await?
+        .delete(schedule.class_schedule_id)
+        .await?;
+
+    uow.commit().await?;
+    Ok(schedule.class_id)
+}
+
+/// A slot that has just passed and should be asked about.
+pub struct DueAttendancePrompt {
+    pub class_attendance_id: i64,
+    pub telegram_id: i64,
+    pub class_name: String,
+    pub scheduled_for: NaiveDateTime,
+}
+
+/// The latest occurrence of `weekday` at `starts_at` not later than `cutoff`.
+fn last_slot_before(
+    weekday: Weekday,
+    starts_at: NaiveTime,
+    cutoff: NaiveDateTime,
+) -> NaiveDateTime {
+    let days_back =
+        (cutoff.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+    let slot = (cutoff.date() - Days::new(days_back.into())).and_time(starts_at);
+    if slot > cutoff {
+        slot - Days::new(7)
+    } else {
+        slot
+    }
+}
+
+fn due_slot(
+    slot: &ScheduledSlot,
+    cutoff: NaiveDateTime,
+    max_lateness: Duration,
+) -> Option<NaiveDateTime> {
+    let weekday = Weekday::try_from(slot.weekday).ok()?;
+    let starts_at = NaiveTime::parse_from_str(&slot.starts_at, "%H:%M").ok()?;
+    let scheduled_for = last_slot_before(weekday, starts_at, cutoff);
+    let lateness = TimeDelta::from_std(max_lateness).ok()?;
+    (cutoff - scheduled_for <= lateness).then_some(scheduled_for)
+}
+
+/// Finds slots that ended at least `delay` before `now` and records a
+/// pending prompt for each one not asked about yet. Slots are matched in the
+/// offset of their owner. Slots missed for longer than `max_lateness`, e.g.
+/// while the bot was down, are dropped.
+pub async fn claim_due_attendance_prompts(
+    db_pool: Arc<Pool<Sqlite>>,
+    now: DateTime<Utc>,
+    delay: Duration,
+    max_lateness: Duration,
+) -> anyhow::Result<Vec<DueAttendancePrompt>> {
+    let delay = TimeDelta::from_std(delay)?;
+
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let slots = uow.class_schedule_repo().await?.get_active_slots().await?;
+
+    let mut prompts = vec![];
+    for slot in slots {
+        let cutoff = now.with_timezone(&slot.utc_offset()).naive_local() - delay;
+        let Some(scheduled_for) = due_slot(&slot, cutoff, max_lateness) else {
+            continue;
+        };
+        let created = uow
+            .class_attendance_repo()
+            .await?
+            .create_pending(
+                slot.class_id,
+                slot.user_id,
+                slot.class_schedule_id,
+                scheduled_for,
+            )
+            .await?;
+        if let Some(class_attendance_id) = created {
+            prompts.push(DueAttendancePrompt {
+                class_attendance_id,
+                telegram_id: slot.telegram_id,
+                class_name: slot.name,
+                scheduled_for,
+            });
+        }
+    }
+
+    uow.commit().await?;
+    Ok(prompts)
+}
+
+#[derive(Debug)]
+pub enum AttendanceAnswer {
+    /// The class was deducted as on the day of the slot.
+    Attended {
+        deduction: ClassDeduction,
+        scheduled_for: NaiveDateTime,
+    },
+    Skipped {
+        class: Class,
+        scheduled_for: NaiveDateTime,
+    },
+}
+
+/// Answers a prompt once. Attending deducts the class exactly like
+/// [`deduct_class`](crate::services::class::deduct_class) for the day of the
+/// slot; a skip is only recorded.
+pub async fn answer_attendance(
+    db_pool: Arc<Pool<Sqlite>>,
+    class_attendance_id: i64,
+    telegram_user_id: i64,
+    attended: bool,
+    expired_policy: ExpiredDeductionPolicy,
+) -> anyhow::Result<AttendanceAnswer> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let (user_id, utc_offset) = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => (u.user_id, u.utc_offset()),
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let attendance = match uow
+        .class_attendance_repo()
+        .await?
+        .get_user_attendance_by_id(class_attendance_id, user_id)
+        .await?
+    {
+        Some(a) => a,
+        None => {
+            bail!(AttendanceNotFoundError);
+        }
+    };
+    if !attendance.is_pending() {
+        bail!(AttendanceAlreadyAnsweredError);
+    }
+    let Some(scheduled_for) = attendance.scheduled_for_datetime() else {
+        bail!(SomethingWentWrongError);
+    };
+
+    let answer = if attended {
+        let deduction = deduct_class_in(
+            &mut uow,
+            attendance.class_id,
+            user_id,
+            scheduled_for.date(),
+            utc_offset,
+            expired_policy,
+        )
+        .await?;
+        uow.class_attendance_repo()
+            .await?
+            .set_status(
+                attendance.class_attendance_id,
+                AttendanceStatus::Attended,
+                Some(deduction.class_deduction_history_id),
+            )
+            .await?;
+        AttendanceAnswer::Attended {
+            deduction,
+            scheduled_for,
+        }
+    } else {
+        let class = match uow
+            .class_repo()
+            .await?
+            .get_user_class_by_id(attendance.class_id, user_id, utils::today())
+            .await?
+        {
+            Some(c) => c,
+            None => {
+                bail!(ClassNotFoundError);
+            }
+        };
+        uow.class_attendance_repo()
+            .await?
+            .set_status(
+                attendance.class_attendance_id,
+                AttendanceStatus::Skipped,
+                None,
+            )
+            .await?;
+        AttendanceAnswer::Skipped {
+            class,
+            scheduled_for,
+        }
+    };
+
+    uow.commit().await?;
+    Ok(answer)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use chrono::{
+        DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Utc, Weekday,
+    };
+    use sqlx::{Pool, Sqlite};
+
+    use crate::{
+        config::ExpiredDeductionPolicy,
+        errors::*,
+        services::{class::add_class, job::set_user_utc_offset, user::add_user},
+        test_utils,
+    };
+
+    use super::{
+        AttendanceAnswer, add_class_schedule, answer_attendance, claim_due_attendance_prompts,
+        last_slot_before,
+    };
+
+    const DELAY: Duration = Duration::from_secs(15 * 60);
+    const MAX_LATENESS: Duration = Duration::from_secs(6 * 60 * 60);
+
+    fn at(day: u32, hour: u32, min: u32) -> NaiveDateTime {
+        // 2026-10-19 is a Monday.
+        NaiveDate::from_ymd_opt(2026, 10, day)
+            .unwrap()
+            .and_hms_opt(hour, min, 0)
+            .unwrap()
+    }
+
+    /// `at` on the clock of alice, who lives at UTC+3.
+    fn msk(day: u32, hour: u32, min: u32) -> DateTime<Utc> {
+        (at(day, hour, min) - TimeDelta::hours(3)).and_utc()
+    }
+
+    async fn setup() -> anyhow::Result<(Arc<Pool<Sqlite>>, i64)> {
+        let pool = test_utils::setup_db().await;
+        let arc_pool: Arc<Pool<Sqlite>> = Arc::new(pool);
+        add_user(arc_pool.clone(), 1, "alice").await?;
+        set_user_utc_offset(
+            arc_pool.clone(),
+            1,
+            FixedOffset::east_opt(3 * 3600).unwrap(),
+        )
+        .await?;
+        let class_id = add_class(arc_pool.clone(), "Йога".into(), 5, None, None, 1).await?;
+        add_class_schedule(
+            arc_pool.clone(),
+            class_id,
+            1,
+            vec![Weekday::Mon, Weekday::Wed],
+            NaiveTime::from_hms_opt(19, 0, 0).unwrap(),
+        )
+        .await?;
+        Ok((arc_pool, class_id))
+    }
+
+    #[test]
+    fn test_last_slot_before() {
+        let seven = NaiveTime::from_hms_opt(19, 0, 0).unwrap();
+        assert_eq!(
+            last_slot_before(Weekday::Mon, seven, at(19, 19, 15)),
+            at(19, 19, 0)
+        );
+        assert_eq!(
+            last_slot_before(Weekday::Mon, seven, at(19, 18, 0)),
+            at(12, 19, 0)
+        );
+        assert_eq!(
+            last_slot_before(Weekday::Wed, seven, at(19, 20, 0)),
+            at(14, 19, 0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_each_slot_is_prompted_once() -> anyhow::Result<()> {
+        let (arc_pool, _) = setup().await?;
+
+        // Too early: the Monday class has not been over for long enough.
+        let prompts =
+            claim_due_attendance_prompts(arc_pool.clone(), msk(19, 19, 10), DELAY, MAX_LATENESS)
+                .await?;
+        assert!(prompts.is_empty());
+
+        let prompts =
+            claim_due_attendance_prompts(arc_pool.clone(), msk(19, 19, 20), DELAY, MAX_LATENESS)
+                .await?;
+        assert_eq!(prompts.len(), 1);
+        assert_eq!(prompts[0].scheduled_for, at(19, 19, 0));
+        assert_eq!(prompts[0].telegram_id, 1);
+
+        let prompts =
+            claim_due_attendance_prompts(arc_pool.clone(), msk(19, 19, 30), DELAY, MAX_LATENESS)
+                .await?;
+        assert!(prompts.is_empty());
+
+        // Wednesday's slot was missed by more than the allowed lateness.
+        let prompts =
+            claim_due_attendance_prompts(arc_pool.clone(), msk(22, 9, 0), DELAY, MAX_LATENESS)
+                .await?;
+        assert!(prompts.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_slots_follow_owner_offset() -> anyhow::Result<()> {
+        let (arc_pool, _) = setup().await?;
+        add_user(arc_pool.clone(), 2, "bob").await?;
+        set_user_utc_offset(
+            arc_pool.clone(),
+            2,
+            FixedOffset::west_opt(5 * 3600).unwrap(),
+        )
+        .await?;
+        let class_id = add_class(arc_pool.clone(), "Танцы".into(), 5, None, None, 2).await?;
+        add_class_schedule(
+            arc_pool.clone(),
+            class_id,
+            2,
+            vec![Weekday::Mon],
+            NaiveTime::from_hms_opt(19, 0, 0).unwrap(),
+        )
+        .await?;
+
+        // 19:20 in Moscow is still 11:20 for bob.
+        let prompts =
+            claim_due_attendance_prompts(arc_pool.clone(), msk(19, 19, 20), DELAY, MAX_LATENESS)
+                .await?;
+        assert_eq!(prompts.len(), 1);
+        assert_eq!(prompts[0].telegram_id, 1);
+
+        // 00:20 UTC on Tuesday is 19:20 on Monday at UTC-5.
+        let prompts = claim_due_attendance_prompts(
+            arc_pool.clone(),
+            at(20, 0, 20).and_utc(),
+            DELAY,
+            MAX_LATENESS,
+        )
+        .await?;
+        assert_eq!(prompts.len(), 1);
+        assert_eq!(prompts[0].telegram_id, 2);
+        assert_eq!(prompts[0].scheduled_for, at(19, 19, 0));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_answer_attendance() -> anyhow::Result<()> {
+        let (arc_pool, _) = setup().await?;
+        let monday =
+            claim_due_attendance_prompts(arc_pool.clone(), msk(19, 19, 20), DELAY, MAX_LATENESS)
+                .await?;
+        let wednesday =
+            claim_due_attendance_prompts(arc_pool.clone(), msk(21, 19, 20), DELAY, MAX_LATENESS)
+                .await?;
+
+        let answer = answer_attendance(
+            arc_pool.clone(),
+            monday[0].class_attendance_id,
+            1,
+            true,
+            ExpiredDeductionPolicy::Warn,
+        )
+        .await?;
+        let deduction = match answer {
+            AttendanceAnswer::Attended { deduction, .. } => deduction,
+            AttendanceAnswer::Skipped { .. } => panic!("expected a deduction"),
+        };
+        assert_eq!(deduction.class.quantity, 4);
+        // Recorded on the day of the class on alice's clock, not the server's.
+        let created_at: String = sqlx::query_scalar(
+            "SELECT created_at FROM class_deduction_history WHERE class_deduction_history_id = ?",
+        )
+        .bind(deduction.class_deduction_history_id)
+        .fetch_one(arc_pool.as_ref())
+        .await?;
+        let created_at = NaiveDateTime::parse_from_str(&created_at, "%Y-%m-%d %H:%M:%S")?;
+        assert_eq!(
+            (created_at + TimeDelta::hours(3)).date(),
+            at(19, 0, 0).date()
+        );
+
+        let answer = answer_attendance(
+            arc_pool.clone(),
+            wednesday[0].class_attendance_id,
+            1,
+            false,
+            ExpiredDeductionPolicy::Warn,
+        )
+        .await?;
+        match answer {
+            AttendanceAnswer::Skipped { class, .. } => assert_eq!(class.quantity, 4),
+            AttendanceAnswer::Attended { .. } => panic!("expected a skip"),
+        }
+
+        let err = answer_attendance(
+            arc_pool.clone(),
+            monday[0].class_attendance_id,
+            1,
+            true,
+            ExpiredDeductionPolicy::Warn,
+        )
+        .await
+        .expect_err("a prompt is answered once");
+        assert!(
+            err.downcast_ref::<AttendanceAlreadyAnsweredError>()
+                .is_some()
+        );
+
+        Ok(())
+    }
+}
@@ -1,32 +1,64 @@
 use std::sync::Arc;
 
 use anyhow::bail;
+use chrono::{Datelike, Days, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Sqlite};
 
 use crate::{errors::*, repositories::daily_practice_log::DailyPracticeLog, uow::UnitOfWork};
 
+/// `practice_category_id` must be one of the user's categories. Several
+/// entries on one day are kept apart and summed up when shown.
 pub async fn add_daily_practice_entry(
     db_pool: Arc<Pool<Sqlite>>,
     minutes: u16,
     telegram_user_id: i64,
+    practice_category_id: Option<i64>,
+    practiced_on: NaiveDate,
+    note: Option<String>,
 ) -> anyhow::Result<i64> {
     let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
-    let user_id = match uow
+    let user = match uow
         .user_repo()
         .await?
         .get_user_by_telegram_id(telegram_user_id)
         .await?
     {
-        Some(u) => u.user_id,
+        Some(u) => u,
         None => {
             bail!(UserNotFoundError);
         }
     };
+    let user_id = user.user_id;
+    let utc_offset = user.utc_offset();
+    let now = Utc::now().with_timezone(&utc_offset);
+
+    if practiced_on > now.date_naive() {
+        bail!(FuturePracticeDateError);
+    }
+
+    if let Some(practice_category_id) = practice_category_id
+        && uow
+            .practice_category_repo()
+            .await?
+            .get_user_category_by_id(practice_category_id, user_id)
+            .await?
+            .is_none()
+    {
+        bail!(PracticeCategoryNotFoundError);
+    }
 
+    // Entries for past days keep the current time of day so that ordering
+    // within a day stays stable. The log stores UTC, the day is the user's.
+    let created_at = (practiced_on != now.date_naive()).then(|| {
+        (practiced_on.and_time(now.time()) - utc_offset)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    });
     let daily_practice_entry_id = match uow
         .daily_practice_log_repo()
         .await?
-        .create(minutes, user_id)
+        .create(minutes, user_id, practice_category_id, created_at, note)
         .await
     {
         Ok(daily_practice_entry_id) => daily_practice_entry_id,
@@ -37,9 +69,168 @@ pub async fn add_daily_practice_entry(
     Ok(daily_practice_entry_id)
 }
 
-pub async fn get_daily_practice_log_history(
+/// The latest `limit` entries, newest first.
+pub async fn get_recent_practice_entries(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    limit: u32,
+) -> anyhow::Result<Vec<DailyPracticeLog>> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let entries = uow
+        .daily_practice_log_repo()
+        .await?
+        .get_recent(user_id, limit)
+        .await?;
+    Ok(entries)
+}
+
+/// Separate entries of one day in the user's offset, oldest first.
+pub async fn get_practice_day_entries(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    day: NaiveDate,
+) -> anyhow::Result<Vec<DailyPracticeLog>> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+    let (user_id, utc_offset) = (user.user_id, user.utc_offset());
+
+    let entries = uow
+        .daily_practice_log_repo()
+        .await?
+        .get_by_period(user_id, day, day, utc_offset)
+        .await?;
+    Ok(entries)
+}
+
+pub async fn get_practice_entry(
+    db_pool: Arc<Pool<Sqlite>>,
+    daily_practice_log_id: i64,
+    telegram_user_id: i64,
+) -> anyhow::Result<DailyPracticeLog> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    match uow
+        .daily_practice_log_repo()
+        .await?
+        .get_user_entry_by_id(daily_practice_log_id, user_id)
+        .await?
+    {
+        Some(entry) => Ok(entry),
+        None => bail!(PracticeEntryNotFoundError),
+    }
+}
+
+pub async fn update_practice_entry_minutes(
+    db_pool: Arc<Pool<Sqlite>>,
+    daily_practice_log_id: i64,
+    telegram_user_id: i64,
+    minutes: u16,
+) -> anyhow::Result<()> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    if !uow
+        .daily_practice_log_repo()
+        .await?
+        .update_minutes(daily_practice_log_id, user_id, minutes)
+        .await?
+    {
+        bail!(PracticeEntryNotFoundError);
+    }
+
+    uow.commit().await?;
+    Ok(())
+}
+
+/// Sets or, with `None`, clears the note of an entry.
+pub async fn set_practice_entry_note(
     db_pool: Arc<Pool<Sqlite>>,
+    daily_practice_log_id: i64,
     telegram_user_id: i64,
+    note: Option<String>,
+) -> anyhow::Result<DailyPracticeLog> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let mut log_repo = uow.daily_practice_log_repo().await?;
+    if !log_repo
+        .set_note(daily_practice_log_id, user_id, note)
+        .await?
+    {
+        bail!(PracticeEntryNotFoundError);
+    }
+    let Some(entry) = log_repo
+        .get_user_entry_by_id(daily_practice_log_id, user_id)
+        .await?
+    else {
+        bail!(PracticeEntryNotFoundError);
+    };
+
+    uow.commit().await?;
+    Ok(entry)
+}
+
+/// Entries whose note contains every word of `query`, newest first, at most
+/// `limit` of them. Matching ignores case; it is done here rather than with
+/// `like`, which in SQLite folds the case of ASCII letters only.
+pub async fn search_practice_notes(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    query: &str,
+    limit: usize,
 ) -> anyhow::Result<Vec<DailyPracticeLog>> {
     let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
     let user_id = match uow
@@ -54,10 +245,632 @@ pub async fn get_daily_practice_log_history(
         }
     };
 
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if words.is_empty() {
+        return Ok(vec![]);
+    }
+    let entries = uow
+        .daily_practice_log_repo()
+        .await?
+        .get_with_notes(user_id)
+        .await?
+        .into_iter()
+        .filter(|entry| {
+            let note = entry.note.as_deref().unwrap_or_default().to_lowercase();
+            words.iter().all(|word| note.contains(word.as_str()))
+        })
+        .take(limit)
+        .collect();
+    Ok(entries)
+}
+
+pub async fn delete_practice_entry(
+    db_pool: Arc<Pool<Sqlite>>,
+    daily_practice_log_id: i64,
+    telegram_user_id: i64,
+) -> anyhow::Result<()> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    if !uow
+        .daily_practice_log_repo()
+        .await?
+        .delete(daily_practice_log_id, user_id)
+        .await?
+    {
+        bail!(PracticeEntryNotFoundError);
+    }
+
+    uow.commit().await?;
+    Ok(())
+}
+
+pub struct PracticeDay {
+    pub date: NaiveDate,
+    pub minutes: u32,
+    /// Number of entries logged for the day.
+    pub sessions: u32,
+    /// Notes of the day's entries, oldest first.
+    pub notes: Vec<String>,
+}
+
+/// Minutes of one category; `name` is `None` for entries without one.
+pub struct CategoryMinutes {
+    pub name: Option<String>,
+    pub minutes: u32,
+}
+
+/// Practice of one month, summed up per day and per category.
+pub struct PracticeMonth {
+    pub month: NaiveDate,
+    pub days: Vec<PracticeDay>,
+    pub categories: Vec<CategoryMinutes>,
+}
+
+impl PracticeMonth {
+    pub fn total_minutes(&self) -> u32 {
+        self.days.iter().map(|d| d.minutes).sum()
+    }
+
+    /// Average per calendar day, counting only the days of the month that have
+    /// already passed by `today`.
+    pub fn average_minutes(&self, today: NaiveDate) -> f32 {
+        let month_end = self.month + Months::new(1) - Days::new(1);
+        let last_day = month_end.min(today);
+        if last_day < self.month {
+            return 0.0;
+        }
+        let days = (last_day - self.month).num_days() + 1;
+        self.total_minutes() as f32 / days as f32
+    }
+}
+
+/// `month` is the first day of the month to show.
+pub async fn get_practice_month(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    month: NaiveDate,
+) -> anyhow::Result<PracticeMonth> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+    let (user_id, utc_offset) = (user.user_id, user.utc_offset());
+
+    let month_end = month + Months::new(1) - Days::new(1);
     let records = uow
         .daily_practice_log_repo()
         .await?
-        .get_all(user_id)
+        .get_by_period(user_id, month, month_end, utc_offset)
+        .await?;
+
+    let mut days: Vec<PracticeDay> = vec![];
+    for record in records {
+        let Some(date) = record.local_date(utc_offset) else {
+            continue;
+        };
+        match days.last_mut() {
+            Some(day) if day.date == date => {
+                day.minutes += u32::from(record.minutes);
+                day.sessions += 1;
+                day.notes.extend(record.note);
+            }
+            _ => days.push(PracticeDay {
+                date,
+                minutes: record.minutes.into(),
+                sessions: 1,
+                notes: record.note.into_iter().collect(),
+            }),
+        }
+    }
+
+    let categories = uow
+        .daily_practice_log_repo()
+        .await?
+        .get_category_totals(user_id, month, month_end, utc_offset)
+        .await?
+        .into_iter()
+        .map(|(name, minutes)| CategoryMinutes {
+            name,
+            minutes: u32::try_from(minutes).unwrap_or_default(),
+        })
+        .collect();
+
+    Ok(PracticeMonth {
+        month,
+        days,
+        categories,
+    })
+}
+
+/// Minutes per day in the user's offset within `from..=until`, oldest first;
+/// days without practice are left out.
+pub async fn get_practice_daily_totals(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    from: NaiveDate,
+    until: NaiveDate,
+) -> anyhow::Result<Vec<(NaiveDate, u32)>> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+    let (user_id, utc_offset) = (user.user_id, user.utc_offset());
+
+    let totals = uow
+        .daily_practice_log_repo()
+        .await?
+        .get_daily_totals(user_id, Some(from), Some(until), utc_offset)
+        .await?
+        .into_iter()
+        .filter_map(|(date, minutes)| Some((date.parse().ok()?, u32::try_from(minutes).ok()?)))
+        .collect();
+    Ok(totals)
+}
+
+/// Sums per-day totals into consecutive groups of `group_days` days starting
+/// at `from`, e.g. weeks when `from` is a Monday. Every group up to `until`
+/// is returned, empty ones included, keyed by its first day.
+pub fn group_practice_totals(
+    days: &[(NaiveDate, u32)],
+    from: NaiveDate,
+    until: NaiveDate,
+    group_days: u64,
+) -> Vec<(NaiveDate, u32)> {
+    let mut groups = vec![];
+    let mut start = from;
+    while start <= until {
+        let end = start + Days::new(group_days);
+        let minutes = days
+            .iter()
+            .filter(|(date, _)| (start..end).contains(date))
+            .map(|(_, minutes)| minutes)
+            .sum();
+        groups.push((start, minutes));
+        start = end;
+    }
+    groups
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PracticeGoalPeriod {
+    Daily,
+    Weekly,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PracticeGoals {
+    pub daily: Option<u16>,
+    pub weekly: Option<u16>,
+}
+
+/// Where the user stands against their goals on some day.
+pub struct PracticeProgress {
+    pub goals: PracticeGoals,
+    pub today_minutes: u32,
+    /// Since Monday, today included.
+    pub week_minutes: u32,
+    pub current_streak: u32,
+    pub longest_streak: u32,
+}
+
+/// Streaks of days that met `daily_goal`, or of days with any practice if
+/// there is no daily goal. `days` are per-day totals, oldest first. The
+/// current streak is still alive if only today is not met yet. Returns
+/// `(current, longest)`.
+pub fn practice_streaks(
+    days: &[(NaiveDate, u32)],
+    daily_goal: Option<u16>,
+    today: NaiveDate,
+) -> (u32, u32) {
+    let goal = u32::from(daily_goal.unwrap_or(1).max(1));
+    let mut longest = 0;
+    let mut run = 0;
+    let mut last_met: Option<NaiveDate> = None;
+    for &(date, minutes) in days.iter().filter(|(date, _)| *date <= today) {
+        if minutes < goal {
+            continue;
+        }
+        run = match last_met {
+            Some(last) if last + Days::new(1) == date => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        last_met = Some(date);
+    }
+
+    let current = match last_met {
+        Some(last) if last == today || last + Days::new(1) == today => run,
+        _ => 0,
+    };
+    (current, longest)
+}
+
+pub async fn get_practice_progress(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    today: NaiveDate,
+) -> anyhow::Result<PracticeProgress> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+    let (user_id, utc_offset) = (user.user_id, user.utc_offset());
+
+    let (daily, weekly) = uow.user_repo().await?.get_practice_goals(user_id).await?;
+    let goals = PracticeGoals { daily, weekly };
+
+    let days: Vec<(NaiveDate, u32)> = uow
+        .daily_practice_log_repo()
+        .await?
+        .get_daily_totals(user_id, None, None, utc_offset)
+        .await?
+        .into_iter()
+        .filter_map(|(date, minutes)| Some((date.parse().ok()?, u32::try_from(minutes).ok()?)))
+        .collect();
+
+    let monday = today - Days::new(today.weekday().num_days_from_monday().into());
+    let minutes_between = |from: NaiveDate| -> u32 {
+        days.iter()
+            .filter(|(date, _)| (from..=today).contains(date))
+            .map(|(_, minutes)| minutes)
+            .sum()
+    };
+    let (current_streak, longest_streak) = practice_streaks(&days, goals.daily, today);
+
+    Ok(PracticeProgress {
+        goals,
+        today_minutes: minutes_between(today),
+        week_minutes: minutes_between(monday),
+        current_streak,
+        longest_streak,
+    })
+}
+
+/// Sets or, with `None`, removes the goal for one period.
+pub async fn set_practice_goal(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    period: PracticeGoalPeriod,
+    minutes: Option<u16>,
+) -> anyhow::Result<PracticeGoals> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let (mut daily, mut weekly) = uow.user_repo().await?.get_practice_goals(user_id).await?;
+    match period {
+        PracticeGoalPeriod::Daily => daily = minutes,
+        PracticeGoalPeriod::Weekly => weekly = minutes,
+    }
+    uow.user_repo()
+        .await?
+        .set_practice_goals(user_id, daily, weekly)
         .await?;
-    Ok(records)
+
+    uow.commit().await?;
+    Ok(PracticeGoals { daily, weekly })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{Datelike, NaiveDate};
+    use sqlx::{Pool, Sqlite};
+
+    use crate::{
+        errors::{FuturePracticeDateError, PracticeEntryNotFoundError},
+        services::user::add_user,
+        test_utils, utils,
+    };
+
+    use super::{
+        PracticeGoalPeriod, add_daily_practice_entry, delete_practice_entry,
+        get_practice_daily_totals, get_practice_day_entries, get_practice_entry,
+        get_practice_month, get_practice_progress, get_recent_practice_entries,
+        group_practice_totals, practice_streaks, search_practice_notes, set_practice_entry_note,
+        set_practice_goal, update_practice_entry_minutes,
+    };
+
+    #[tokio::test]
+    async fn test_practice_month_sums_days() -> anyhow::Result<()> {
+        let pool = test_utils::setup_db().await;
+        let arc_pool: Arc<Pool<Sqlite>> = Arc::new(pool.clone());
+        add_user(arc_pool.clone(), 1, "alice").await?;
+
+        let day = |d| NaiveDate::from_ymd_opt(2026, 9, d).unwrap();
+        test_utils::add_practice_entry(&pool, day(1), 20).await?;
+        test_utils::add_practice_entry(&pool, day(1), 10).await?;
+        test_utils::add_practice_entry(&pool, day(30), 60).await?;
+        test_utils::add_practice_entry(&pool, NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(), 45)
+            .await?;
+
+        let month = get_practice_month(arc_pool.clone(), 1, day(1)).await?;
+        let days: Vec<(NaiveDate, u32)> = month.days.iter().map(|d| (d.date, d.minutes)).collect();
+        assert_eq!(days, vec![(day(1), 30), (day(30), 60)]);
+        assert_eq!(month.total_minutes(), 90);
+        assert_eq!(month.average_minutes(day(30) + chrono::Days::new(10)), 3.0);
+        // Only the days that have passed count towards the average.
+        assert_eq!(month.average_minutes(day(10)), 9.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_practice_streaks() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        let days = vec![
+            (day(1), 30),
+            (day(2), 45),
+            (day(3), 20),
+            (day(4), 30),
+            (day(5), 60),
+            (day(6), 30),
+            (day(8), 30),
+            (day(9), 30),
+        ];
+
+        assert_eq!(practice_streaks(&days, Some(30), day(9)), (2, 3));
+        // Today is not over yet, so yesterday's streak still counts.
+        assert_eq!(practice_streaks(&days, Some(30), day(10)), (2, 3));
+        assert_eq!(practice_streaks(&days, Some(30), day(11)), (0, 3));
+        // Without a daily goal any practice counts.
+        assert_eq!(practice_streaks(&days, None, day(9)), (2, 6));
+        assert_eq!(practice_streaks(&days, Some(60), day(9)), (0, 1));
+        // Days after `today` are ignored.
+        assert_eq!(practice_streaks(&days, None, day(3)), (3, 3));
+        assert_eq!(practice_streaks(&[], Some(30), day(9)), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_practice_progress_sums_entries_per_day() -> anyhow::Result<()> {
+        let pool = test_utils::setup_db().await;
+        let arc_pool: Arc<Pool<Sqlite>> = Arc::new(pool.clone());
+        add_user(arc_pool.clone(), 1, "alice").await?;
+
+        // 2026-10-12 is a Monday.
+        let day = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        test_utils::add_practice_entry(&pool, day(10), 40).await?;
+        test_utils::add_practice_entry(&pool, day(12), 15).await?;
+        test_utils::add_practice_entry(&pool, day(12), 15).await?;
+        test_utils::add_practice_entry(&pool, day(13), 20).await?;
+        test_utils::add_practice_entry(&pool, day(14), 10).await?;
+        test_utils::add_practice_entry(&pool, day(14), 25).await?;
+
+        set_practice_goal(arc_pool.clone(), 1, PracticeGoalPeriod::Daily, Some(30)).await?;
+        let goals =
+            set_practice_goal(arc_pool.clone(), 1, PracticeGoalPeriod::Weekly, Some(120)).await?;
+        assert_eq!((goals.daily, goals.weekly), (Some(30), Some(120)));
+
+        let progress = get_practice_progress(arc_pool.clone(), 1, day(14)).await?;
+        assert_eq!(progress.today_minutes, 35);
+        assert_eq!(progress.week_minutes, 85);
+        assert_eq!((progress.current_streak, progress.longest_streak), (1, 1));
+
+        let goals = set_practice_goal(arc_pool.clone(), 1, PracticeGoalPeriod::Daily, None).await?;
+        assert_eq!((goals.daily, goals.weekly), (None, Some(120)));
+        let progress = get_practice_progress(arc_pool.clone(), 1, day(14)).await?;
+        assert_eq!((progress.current_streak, progress.longest_streak), (3, 3));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_edit_and_delete_practice_entries() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        add_user(pool.clone(), 1, "alice").await?;
+        add_user(pool.clone(), 2, "bob").await?;
+
+        let today = utils::today();
+        let typo = add_daily_practice_entry(pool.clone(), 600, 1, None, today, None).await?;
+        let other = add_daily_practice_entry(pool.clone(), 30, 1, None, today, None).await?;
+        let bobs = add_daily_practice_entry(pool.clone(), 45, 2, None, today, None).await?;
+
+        let recent: Vec<i64> = get_recent_practice_entries(pool.clone(), 1, 10)
+            .await?
+            .iter()
+            .map(|e| e.daily_practice_log_id)
+            .collect();
+        assert_eq!(recent, vec![other, typo]);
+
+        update_practice_entry_minutes(pool.clone(), typo, 1, 60).await?;
+        assert_eq!(get_practice_entry(pool.clone(), typo, 1).await?.minutes, 60);
+
+        // Someone else's entries can't be seen, changed or removed.
+        for result in [
+            get_practice_entry(pool.clone(), bobs, 1).await.map(|_| ()),
+            update_practice_entry_minutes(pool.clone(), bobs, 1, 0).await,
+            delete_practice_entry(pool.clone(), bobs, 1).await,
+        ] {
+            let err = result.expect_err("someone else's entry");
+            assert!(err.downcast_ref::<PracticeEntryNotFoundError>().is_some());
+        }
+        assert_eq!(get_practice_entry(pool.clone(), bobs, 2).await?.minutes, 45);
+
+        delete_practice_entry(pool.clone(), typo, 1).await?;
+        let err = delete_practice_entry(pool.clone(), typo, 1)
+            .await
+            .expect_err("already deleted");
+        assert!(err.downcast_ref::<PracticeEntryNotFoundError>().is_some());
+        assert_eq!(
+            get_recent_practice_entries(pool.clone(), 1, 10)
+                .await?
+                .len(),
+            1
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_past_day_entries_are_summed_per_day() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        add_user(pool.clone(), 1, "alice").await?;
+
+        let today = utils::today();
+        let yesterday = today - chrono::Days::new(1);
+        add_daily_practice_entry(pool.clone(), 30, 1, None, yesterday, None).await?;
+        add_daily_practice_entry(pool.clone(), 15, 1, None, yesterday, None).await?;
+        add_daily_practice_entry(pool.clone(), 20, 1, None, today, None).await?;
+        let err = add_daily_practice_entry(
+            pool.clone(),
+            20,
+            1,
+            None,
+            today + chrono::Days::new(1),
+            None,
+        )
+        .await
+        .expect_err("future day");
+        assert!(err.downcast_ref::<FuturePracticeDateError>().is_some());
+
+        let sessions: Vec<u16> = get_practice_day_entries(pool.clone(), 1, yesterday)
+            .await?
+            .iter()
+            .map(|e| e.minutes)
+            .collect();
+        assert_eq!(sessions, vec![30, 15]);
+
+        let progress = get_practice_progress(pool.clone(), 1, today).await?;
+        assert_eq!(progress.today_minutes, 20);
+        assert_eq!(progress.current_streak, 2);
+
+        let month = get_practice_month(pool.clone(), 1, yesterday.with_day(1).unwrap()).await?;
+        let day = month.days.iter().find(|d| d.date == yesterday).unwrap();
+        assert_eq!((day.minutes, day.sessions), (45, 2));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_practice_totals_for_charts() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        add_user(pool.clone(), 1, "alice").await?;
+
+        let today = utils::today();
+        let week_ago = today - chrono::Days::new(7);
+        add_daily_practice_entry(pool.clone(), 30, 1, None, week_ago, None).await?;
+        add_daily_practice_entry(pool.clone(), 15, 1, None, today, None).await?;
+        add_daily_practice_entry(pool.clone(), 20, 1, None, today, None).await?;
+
+        let from = today - chrono::Days::new(6);
+        let days = get_practice_daily_totals(pool.clone(), 1, from, today).await?;
+        assert_eq!(days, vec![(today, 35)]);
+
+        let days = get_practice_daily_totals(pool.clone(), 1, week_ago, today).await?;
+        let groups = group_practice_totals(&days, week_ago, today, 7);
+        assert_eq!(
+            groups,
+            vec![(week_ago, 30), (week_ago + chrono::Days::new(7), 35)]
+        );
+
+        let groups = group_practice_totals(&days, from, today, 1);
+        assert_eq!(groups.len(), 7);
+        assert_eq!(groups.iter().map(|(_, m)| m).sum::<u32>(), 35);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_practice_notes_and_search() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        add_user(pool.clone(), 1, "alice").await?;
+        add_user(pool.clone(), 2, "bob").await?;
+
+        let today = utils::today();
+        let note = |text: &str| Some(text.to_string());
+        let scales =
+            add_daily_practice_entry(pool.clone(), 30, 1, None, today, note("Гаммы до мажор"))
+                .await?;
+        let etude = add_daily_practice_entry(pool.clone(), 20, 1, None, today, None).await?;
+        add_daily_practice_entry(pool.clone(), 45, 2, None, today, note("гаммы")).await?;
+
+        let entry = set_practice_entry_note(
+            pool.clone(),
+            etude,
+            1,
+            note("Этюд: не даются гаммы в левой"),
+        )
+        .await?;
+        assert_eq!(entry.note.as_deref(), Some("Этюд: не даются гаммы в левой"));
+        let err = set_practice_entry_note(pool.clone(), etude, 2, None)
+            .await
+            .expect_err("someone else's entry");
+        assert!(err.downcast_ref::<PracticeEntryNotFoundError>().is_some());
+
+        let found = |entries: Vec<super::DailyPracticeLog>| {
+            entries
+                .iter()
+                .map(|e| e.daily_practice_log_id)
+                .collect::<Vec<_>>()
+        };
+        // Case-insensitive, only the user's own notes, newest first.
+        assert_eq!(
+            found(search_practice_notes(pool.clone(), 1, "ГАММЫ", 10).await?),
+            vec![etude, scales]
+        );
+        assert_eq!(
+            found(search_practice_notes(pool.clone(), 1, "гаммы мажор", 10).await?),
+            vec![scales]
+        );
+        assert_eq!(
+            found(search_practice_notes(pool.clone(), 1, "гаммы", 1).await?),
+            vec![etude]
+        );
+        assert!(
+            search_practice_notes(pool.clone(), 1, "  ", 10)
+                .await?
+                .is_empty()
+        );
+
+        set_practice_entry_note(pool.clone(), scales, 1, None).await?;
+        let month = get_practice_month(pool.clone(), 1, today.with_day(1).unwrap()).await?;
+        assert_eq!(month.days[0].notes, vec!["Этюд: не даются гаммы в левой"]);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,224 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::bail;
+use chrono::{FixedOffset, NaiveDateTime, TimeZone, Utc};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+    errors::*,
+    spreadsheet::{Cell, Table},
+    uow::UnitOfWork,
+    utils,
+};
+
+/// Everything the user has stored, one table per kind of record.
+pub struct UserExport {
+    /// Timestamps in the tables are in this offset.
+    pub utc_offset: FixedOffset,
+    pub tables: Vec<Table>,
+}
+
+impl UserExport {
+    pub fn is_empty(&self) -> bool {
+        self.tables.iter().all(|table| table.rows.is_empty())
+    }
+}
+
+/// A UTC timestamp from the database as local time of the user; unparsable
+/// values are kept as they are.
+fn localize(timestamp: &str, utc_offset: FixedOffset) -> String {
+    match NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S") {
+        Ok(dt) => Utc
+            .from_utc_datetime(&dt)
+            .with_timezone(&utc_offset)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        Err(_) => timestamp.to_string(),
+    }
+}
+
+/// Classes, class deductions and practice entries of the user, with
+/// timestamps in the user's timezone.
+pub async fn get_user_export(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+) -> anyhow::Result<UserExport> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+    let user_id = user.user_id;
+    let utc_offset = user.utc_offset();
+
+    let classes = uow
+        .class_repo()
+        .await?
+        .get_all_user_classes(user_id, utils::today())
+        .await?;
+    let class_names: HashMap<i64, String> = classes
+        .iter()
+        .map(|class| (class.class_id, class.name.clone()))
+        .collect();
+    let classes = Table {
+        name: "classes",
+        header: vec![
+            "Занятие",
+            "Осталось занятий",
+            "Действует до",
+            "Напоминать при остатке",
+            "В архиве с",
+        ],
+        rows: classes
+            .into_iter()
+            .map(|class| {
+                vec![
+                    Cell::from(class.name),
+                    Cell::from(i64::from(class.quantity)),
+                    Cell::from(class.valid_until),
+                    class
+                        .low_balance_threshold
+                        .map_or(Cell::Empty, |threshold| Cell::from(i64::from(threshold))),
+                    Cell::from(
+                        class
+                            .archived_at
+                            .map(|archived_at| localize(&archived_at, utc_offset)),
+                    ),
+                ]
+            })
+            .collect(),
+    };
+
+    let deductions = Table {
+        name: "class_deductions",
+        header: vec!["Занятие", "Дата занятия", "Записано", "Заметка"],
+        rows: uow
+            .class_deduction_history_repo()
+            .await?
+            .get_user_histories(user_id)
+            .await?
+            .into_iter()
+            .map(|history| {
+                vec![
+                    Cell::from(class_names.get(&history.class_id).cloned()),
+                    Cell::from(localize(&history.created_at, utc_offset)),
+                    Cell::from(localize(&history.recorded_at, utc_offset)),
+                    Cell::from(history.note),
+                ]
+            })
+            .collect(),
+    };
+
+    let practice = Table {
+        name: "practice_log",
+        header: vec!["Дата и время", "Минуты", "Категория", "Заметка"],
+        rows: uow
+            .daily_practice_log_repo()
+            .await?
+            .get_all(user_id)
+            .await?
+            .into_iter()
+            .map(|entry| {
+                vec![
+                    Cell::from(localize(&entry.created_at, utc_offset)),
+                    Cell::from(i64::from(entry.minutes)),
+                    Cell::from(entry.category_name),
+                    Cell::from(entry.note),
+                ]
+            })
+            .collect(),
+    };
+
+    Ok(UserExport {
+        utc_offset,
+        tables: vec![classes, deductions, practice],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::FixedOffset;
+
+    use crate::{
+        config::ExpiredDeductionPolicy,
+        services::{
+            class::{add_class, deduct_class},
+            daily_practice_log::add_daily_practice_entry,
+            job::set_user_utc_offset,
+            practice_category::add_practice_category,
+            user::add_user,
+        },
+        spreadsheet::{Cell, to_csv},
+        test_utils, utils,
+    };
+
+    use super::{get_user_export, localize};
+
+    #[test]
+    fn test_localize() {
+        let offset = FixedOffset::east_opt(3 * 3600).unwrap();
+        assert_eq!(localize("2026-10-18 22:30:00", offset), "2026-10-19 01:30");
+        assert_eq!(localize("garbage", offset), "garbage");
+    }
+
+    #[tokio::test]
+    async fn test_user_export() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        add_user(pool.clone(), 1, "alice").await?;
+        add_user(pool.clone(), 2, "bob").await?;
+        set_user_utc_offset(pool.clone(), 1, FixedOffset::east_opt(3 * 3600).unwrap()).await?;
+
+        let export = get_user_export(pool.clone(), 1).await?;
+        assert!(export.is_empty());
+
+        let today = utils::today();
+        let class_id = add_class(pool.clone(), "Йога".to_string(), 5, None, None, 1).await?;
+        add_class(pool.clone(), "Танцы".to_string(), 3, None, None, 2).await?;
+        deduct_class(
+            pool.clone(),
+            class_id,
+            1,
+            today,
+            ExpiredDeductionPolicy::default(),
+        )
+        .await?;
+        let category_id = add_practice_category(pool.clone(), 1, "Гаммы").await?;
+        add_daily_practice_entry(
+            pool.clone(),
+            30,
+            1,
+            Some(category_id),
+            today,
+            Some("до мажор, медленно".to_string()),
+        )
+        .await?;
+        add_daily_practice_entry(pool.clone(), 15, 1, None, today, None).await?;
+
+        let export = get_user_export(pool.clone(), 1).await?;
+        assert_eq!(export.utc_offset, FixedOffset::east_opt(3 * 3600).unwrap());
+        let [classes, deductions, practice] = &export.tables[..] else {
+            panic!("expected three tables");
+        };
+        assert_eq!(classes.rows.len(), 1);
+        assert!(matches!(&classes.rows[0][1], Cell::Number(4)));
+        assert_eq!(deductions.rows.len(), 1);
+        assert!(matches!(&deductions.rows[0][0], Cell::Text(name) if name == "Йога"));
+        assert_eq!(practice.rows.len(), 2);
+        assert!(matches!(&practice.rows[0][2], Cell::Text(name) if name == "Гаммы"));
+        assert!(matches!(&practice.rows[1][2], Cell::Empty));
+
+        let csv = String::from_utf8(to_csv(practice))?;
+        assert!(csv.contains(",30,Гаммы,\"до мажор, медленно\"\r\n"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,327 @@
+use std::sync::Arc;
+
+use anyhow::bail;
+use chrono::{DateTime, FixedOffset, Utc};
+use sqlx::{Pool, Sqlite};
+
+use crate::{errors::*, repositories::job::NewJob, scheduler::cron::CronSchedule, uow::UnitOfWork};
+
+pub enum JobTiming {
+    Once(DateTime<Utc>),
+    /// Fires on every match of the expression in the given offset.
+    Cron {
+        schedule: CronSchedule,
+        utc_offset: FixedOffset,
+    },
+}
+
+impl JobTiming {
+    /// The first run strictly after `now`.
+    pub fn first_run_after(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            JobTiming::Once(run_at) => Some(*run_at),
+            JobTiming::Cron {
+                schedule,
+                utc_offset,
+            } => next_cron_run(schedule, *utc_offset, now),
+        }
+    }
+}
+
+/// Next occurrence of `schedule` after `after`, read in `utc_offset`.
+pub fn next_cron_run(
+    schedule: &CronSchedule,
+    utc_offset: FixedOffset,
+    after: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let local = after.with_timezone(&utc_offset).naive_local();
+    let next = schedule.next_after(local)?;
+    Some((next - utc_offset).and_utc())
+}
+
+/// Stores a job for the scheduler. `telegram_user_id` ties the job to a user,
+/// so it is dropped together with them; system jobs pass `None`. A job with
+/// the same `unique_key` is replaced, which makes it safe to re-schedule on
+/// every settings change or restart.
+pub async fn schedule_job(
+    db_pool: Arc<Pool<Sqlite>>,
+    kind: &str,
+    payload: serde_json::Value,
+    telegram_user_id: Option<i64>,
+    timing: JobTiming,
+    unique_key: Option<&str>,
+) -> anyhow::Result<i64> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match telegram_user_id {
+        Some(telegram_user_id) => match uow
+            .user_repo()
+            .await?
+            .get_user_by_telegram_id(telegram_user_id)
+            .await?
+        {
+            Some(u) => Some(u.user_id),
+            None => {
+                bail!(UserNotFoundError);
+            }
+        },
+        None => None,
+    };
+
+    let job_id = schedule_job_in(&mut uow, kind, payload, user_id, timing, unique_key).await?;
+
+    uow.commit().await?;
+    Ok(job_id)
+}
+
+/// The body of [`schedule_job`], for services that schedule as part of a
+/// larger transaction.
+pub(crate) async fn schedule_job_in(
+    uow: &mut UnitOfWork<'_>,
+    kind: &str,
+    payload: serde_json::Value,
+    user_id: Option<i64>,
+    timing: JobTiming,
+    unique_key: Option<&str>,
+) -> anyhow::Result<i64> {
+    let Some(run_at) = timing.first_run_after(Utc::now()) else {
+        bail!(JobTimingError);
+    };
+    let (cron, utc_offset_minutes) = match &timing {
+        JobTiming::Once(_) => (None, 0),
+        JobTiming::Cron {
+            schedule,
+            utc_offset,
+        } => (
+            Some(schedule.to_string()),
+            utc_offset.local_minus_utc() / 60,
+        ),
+    };
+
+    let payload = payload.to_string();
+    let job_id = uow
+        .job_repo()
+        .await?
+        .create(&NewJob {
+            kind,
+            payload: &payload,
+            run_at,
+            cron: cron.as_deref(),
+            utc_offset_minutes,
+            unique_key,
+            user_id,
+        })
+        .await?;
+    Ok(job_id)
+}
+
+/// Cancels the job scheduled under `unique_key` unless it has already run,
+/// as part of the caller's transaction. Returns whether there was such a job.
+pub(crate) async fn cancel_job_in(
+    uow: &mut UnitOfWork<'_>,
+    unique_key: &str,
+) -> anyhow::Result<bool> {
+    uow.job_repo().await?.cancel_by_key(unique_key).await
+}
+
+/// The offset the user's recurring jobs are read in.
+pub async fn get_user_utc_offset(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+) -> anyhow::Result<FixedOffset> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => Ok(u.utc_offset()),
+        None => {
+            bail!(UserNotFoundError);
+        }
+    }
+}
+
+/// Moves the user and their recurring jobs to another offset.
+pub async fn set_user_utc_offset(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    utc_offset: FixedOffset,
+) -> anyhow::Result<()> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let utc_offset_minutes = utc_offset.local_minus_utc() / 60;
+    uow.user_repo()
+        .await?
+        .set_utc_offset(user_id, Some(utc_offset_minutes))
+        .await?;
+
+    // Recurring jobs follow the user to the new offset.
+    let now = Utc::now();
+    let jobs = uow
+        .job_repo()
+        .await?
+        .get_user_recurring_jobs(user_id)
+        .await?;
+    for job in jobs {
+        let Some(schedule) = job.cron.and_then(|cron| cron.parse::<CronSchedule>().ok()) else {
+            continue;
+        };
+        if let Some(run_at) = next_cron_run(&schedule, utc_offset, now) {
+            uow.job_repo()
+                .await?
+                .set_timing(job.job_id, run_at, utc_offset_minutes)
+                .await?;
+        }
+    }
+
+    uow.commit().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{Duration, FixedOffset, Utc};
+
+    use crate::{errors::*, services::user::add_user, test_utils, uow::UnitOfWork};
+
+    use super::{JobTiming, cancel_job_in, get_user_utc_offset, schedule_job, set_user_utc_offset};
+
+    fn daily(utc_offset_hours: i32) -> JobTiming {
+        JobTiming::Cron {
+            schedule: "30 8 * * *".parse().unwrap(),
+            utc_offset: FixedOffset::east_opt(utc_offset_hours * 3600).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_schedule_job_replaces_by_key() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        add_user(pool.clone(), 1, "user").await?;
+
+        let first = schedule_job(
+            pool.clone(),
+            "record",
+            serde_json::json!({ "n": 1 }),
+            Some(1),
+            daily(3),
+            Some("key"),
+        )
+        .await?;
+        let second = schedule_job(
+            pool.clone(),
+            "record",
+            serde_json::json!({ "n": 2 }),
+            Some(1),
+            daily(3),
+            Some("key"),
+        )
+        .await?;
+        assert_ne!(first, second);
+
+        let jobs: Vec<(i64, String, String)> =
+            sqlx::query_as("select job_id, payload, run_at from job")
+                .fetch_all(pool.as_ref())
+                .await?;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].0, second);
+        assert_eq!(jobs[0].1, r#"{"n":2}"#);
+        assert!(jobs[0].2.ends_with("05:30:00"));
+        assert!(jobs[0].2 > Utc::now().format("%Y-%m-%d %H:%M:%S").to_string());
+
+        let err = schedule_job(
+            pool.clone(),
+            "record",
+            serde_json::json!({}),
+            Some(2),
+            daily(3),
+            None,
+        )
+        .await
+        .expect_err("unknown user");
+        assert_eq!(
+            err.downcast_ref::<UserNotFoundError>(),
+            Some(&UserNotFoundError)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_user_utc_offset_moves_jobs() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        add_user(pool.clone(), 1, "user").await?;
+        let job_id = schedule_job(
+            pool.clone(),
+            "record",
+            serde_json::json!({}),
+            Some(1),
+            daily(3),
+            None,
+        )
+        .await?;
+
+        let utc_offset = FixedOffset::west_opt(5 * 3600).unwrap();
+        set_user_utc_offset(pool.clone(), 1, utc_offset).await?;
+        assert_eq!(get_user_utc_offset(pool.clone(), 1).await?, utc_offset);
+
+        let (run_at, utc_offset_minutes): (String, i32) =
+            sqlx::query_as("select run_at, utc_offset_minutes from job where job_id = ?")
+                .bind(job_id)
+                .fetch_one(pool.as_ref())
+                .await?;
+        // 08:30 at UTC-5.
+        assert!(run_at.ends_with("13:30:00"));
+        assert_eq!(utc_offset_minutes, -300);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancel_once_job() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        let run_at = Utc::now() + Duration::hours(1);
+        schedule_job(
+            pool.clone(),
+            "record",
+            serde_json::json!({}),
+            None,
+            JobTiming::Once(run_at),
+            Some("once"),
+        )
+        .await?;
+
+        let (stored_run_at, cron): (String, Option<String>) =
+            sqlx::query_as("select run_at, cron from job")
+                .fetch_one(pool.as_ref())
+                .await?;
+        assert_eq!(
+            stored_run_at,
+            run_at.format("%Y-%m-%d %H:%M:%S").to_string()
+        );
+        assert_eq!(cron, None);
+
+        let mut uow = UnitOfWork::new_transactional(pool.as_ref()).await?;
+        assert!(cancel_job_in(&mut uow, "once").await?);
+        assert!(!cancel_job_in(&mut uow, "once").await?);
+        assert!(!cancel_job_in(&mut uow, "unknown").await?);
+        uow.commit().await?;
+
+        let status: String = sqlx::query_scalar("select status from job")
+            .fetch_one(pool.as_ref())
+            .await?;
+        assert_eq!(status, "cancelled");
+        Ok(())
+    }
+}
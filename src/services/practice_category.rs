@@ -0,0 +1,197 @@
+use std::sync::Arc;
+
+use anyhow::bail;
+use sqlx::{Pool, Sqlite};
+
+use crate::{errors::*, repositories::practice_category::PracticeCategory, uow::UnitOfWork};
+
+/// Maps a failed insert of `practice_category.name` to a user-facing error,
+/// recognising SQLite unique constraint violations.
+fn map_category_name_error(err: sqlx::Error) -> anyhow::Error {
+    if let Some(db_err) = err.as_database_error()
+        && let Some("2067") | Some("1555") = db_err.code().as_deref()
+    {
+        return DuplicatePracticeCategoryError.into();
+    }
+    SomethingWentWrongError.into()
+}
+
+pub async fn add_practice_category(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    name: &str,
+) -> anyhow::Result<i64> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let practice_category_id = match uow
+        .practice_category_repo()
+        .await?
+        .create(name, user_id)
+        .await
+    {
+        Ok(practice_category_id) => practice_category_id,
+        Err(err) => return Err(map_category_name_error(err)),
+    };
+
+    uow.commit().await?;
+    Ok(practice_category_id)
+}
+
+pub async fn get_practice_categories(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+) -> anyhow::Result<Vec<PracticeCategory>> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let categories = uow
+        .practice_category_repo()
+        .await?
+        .get_user_categories(user_id)
+        .await?;
+    Ok(categories)
+}
+
+/// Entries of the category stay in the log without a category.
+pub async fn delete_practice_category(
+    db_pool: Arc<Pool<Sqlite>>,
+    practice_category_id: i64,
+    telegram_user_id: i64,
+) -> anyhow::Result<()> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let mut category_repo = uow.practice_category_repo().await?;
+    if category_repo
+        .get_user_category_by_id(practice_category_id, user_id)
+        .await?
+        .is_none()
+    {
+        bail!(PracticeCategoryNotFoundError);
+    }
+    category_repo.delete(practice_category_id).await?;
+
+    uow.commit().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::Datelike;
+
+    use crate::{
+        errors::*,
+        services::{
+            daily_practice_log::{PracticeMonth, add_daily_practice_entry, get_practice_month},
+            user::add_user,
+        },
+        test_utils, utils,
+    };
+
+    use super::{add_practice_category, delete_practice_category, get_practice_categories};
+
+    #[tokio::test]
+    async fn test_practice_categories() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        add_user(pool.clone(), 1, "alice").await?;
+        add_user(pool.clone(), 2, "bob").await?;
+
+        let today = utils::today();
+        let technique = add_practice_category(pool.clone(), 1, "Техника").await?;
+        let theory = add_practice_category(pool.clone(), 1, "Теория").await?;
+        // Names are unique per user only.
+        let bobs = add_practice_category(pool.clone(), 2, "Техника").await?;
+        let err = add_practice_category(pool.clone(), 1, "Техника")
+            .await
+            .expect_err("duplicate name");
+        assert!(
+            err.downcast_ref::<DuplicatePracticeCategoryError>()
+                .is_some()
+        );
+
+        let names: Vec<String> = get_practice_categories(pool.clone(), 1)
+            .await?
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(names, vec!["Теория", "Техника"]);
+
+        add_daily_practice_entry(pool.clone(), 30, 1, Some(technique), today, None).await?;
+        add_daily_practice_entry(pool.clone(), 15, 1, Some(technique), today, None).await?;
+        add_daily_practice_entry(pool.clone(), 20, 1, Some(theory), today, None).await?;
+        add_daily_practice_entry(pool.clone(), 10, 1, None, today, None).await?;
+        let err = add_daily_practice_entry(pool.clone(), 10, 1, Some(bobs), today, None)
+            .await
+            .expect_err("someone else's category");
+        assert!(
+            err.downcast_ref::<PracticeCategoryNotFoundError>()
+                .is_some()
+        );
+
+        let month = today.with_day(1).unwrap();
+        let breakdown = |month: &PracticeMonth| {
+            month
+                .categories
+                .iter()
+                .map(|c| (c.name.clone(), c.minutes))
+                .collect::<Vec<_>>()
+        };
+        let practice = get_practice_month(pool.clone(), 1, month).await?;
+        assert_eq!(
+            breakdown(&practice),
+            vec![
+                (Some("Техника".to_string()), 45),
+                (Some("Теория".to_string()), 20),
+                (None, 10),
+            ]
+        );
+
+        let err = delete_practice_category(pool.clone(), technique, 2)
+            .await
+            .expect_err("someone else's category");
+        assert!(
+            err.downcast_ref::<PracticeCategoryNotFoundError>()
+                .is_some()
+        );
+        delete_practice_category(pool.clone(), technique, 1).await?;
+        let practice = get_practice_month(pool.clone(), 1, month).await?;
+        assert_eq!(
+            breakdown(&practice),
+            vec![(None, 55), (Some("Теория".to_string()), 20)]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,402 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use anyhow::bail;
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+    errors::*,
+    services::job::{JobTiming, cancel_job_in, schedule_job_in},
+    uow::UnitOfWork,
+};
+
+pub const PRACTICE_DIGEST_JOB: &str = "practice_digest";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestPeriod {
+    Week,
+    Month,
+}
+
+impl DigestPeriod {
+    /// Mondays and the 1st of the month at 09:00 in the user's offset.
+    fn cron(self) -> &'static str {
+        match self {
+            DigestPeriod::Week => "0 9 * * 1",
+            DigestPeriod::Month => "0 9 1 * *",
+        }
+    }
+
+    fn job_key(self, user_id: i64) -> String {
+        match self {
+            DigestPeriod::Week => format!("practice_digest_week:{user_id}"),
+            DigestPeriod::Month => format!("practice_digest_month:{user_id}"),
+        }
+    }
+
+    /// The last full week or month before the one `day` falls in, as
+    /// `(first day, last day)`.
+    pub fn previous(self, day: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let start = match self {
+            DigestPeriod::Week => day - Days::new(day.weekday().num_days_from_monday().into()),
+            DigestPeriod::Month => day.with_day(1).unwrap_or(day),
+        };
+        let from = match self {
+            DigestPeriod::Week => start - Days::new(7),
+            DigestPeriod::Month => start - Months::new(1),
+        };
+        (from, start - Days::new(1))
+    }
+}
+
+pub struct PracticeDigest {
+    pub period: DigestPeriod,
+    pub from: NaiveDate,
+    pub until: NaiveDate,
+    pub total_minutes: u32,
+    pub practice_days: u32,
+    pub best_day: Option<(NaiveDate, u32)>,
+    pub previous_total_minutes: u32,
+    /// Classes attended, by class name.
+    pub classes: Vec<(String, u32)>,
+}
+
+impl PracticeDigest {
+    /// Average per calendar day of the period.
+    pub fn average_minutes(&self) -> f32 {
+        let days = (self.until - self.from).num_days() + 1;
+        self.total_minutes as f32 / days as f32
+    }
+}
+
+/// Summary of the period before the one `today` is in, compared with the
+/// period before that. Days are taken in the user's offset, the same one the
+/// digest job runs in.
+pub async fn get_practice_digest(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    period: DigestPeriod,
+    today: NaiveDate,
+) -> anyhow::Result<PracticeDigest> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+    let (user_id, utc_offset) = (user.user_id, user.utc_offset());
+
+    let (from, until) = period.previous(today);
+    let (previous_from, _) = period.previous(from);
+    let records = uow
+        .daily_practice_log_repo()
+        .await?
+        .get_by_period(user_id, previous_from, until, utc_offset)
+        .await?;
+
+    let mut days: BTreeMap<NaiveDate, u32> = BTreeMap::new();
+    let mut previous_total_minutes = 0;
+    for record in records {
+        let Some(date) = record.local_date(utc_offset) else {
+            continue;
+        };
+        if date < from {
+            previous_total_minutes += u32::from(record.minutes);
+        } else {
+            *days.entry(date).or_default() += u32::from(record.minutes);
+        }
+    }
+    // The earliest of equally long days wins.
+    let best_day =
+        days.iter().fold(
+            None,
+            |best: Option<(NaiveDate, u32)>, (&date, &minutes)| match best {
+                Some((_, best_minutes)) if best_minutes >= minutes => best,
+                _ => Some((date, minutes)),
+            },
+        );
+
+    let classes = uow
+        .class_deduction_history_repo()
+        .await?
+        .count_by_class(user_id, from, until, utc_offset)
+        .await?;
+
+    Ok(PracticeDigest {
+        period,
+        from,
+        until,
+        total_minutes: days.values().sum(),
+        practice_days: days.len() as u32,
+        best_day,
+        previous_total_minutes,
+        classes,
+    })
+}
+
+/// `(weekly, monthly)`.
+pub async fn get_digest_subscriptions(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+) -> anyhow::Result<(bool, bool)> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let subscriptions = uow
+        .user_repo()
+        .await?
+        .get_digest_subscriptions(user_id)
+        .await?;
+    Ok(subscriptions)
+}
+
+/// Opts in or out of one digest. Returns the updated `(weekly, monthly)`.
+pub async fn set_digest_subscription(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    period: DigestPeriod,
+    enabled: bool,
+) -> anyhow::Result<(bool, bool)> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let (mut weekly, mut monthly) = uow
+        .user_repo()
+        .await?
+        .get_digest_subscriptions(user.user_id)
+        .await?;
+    match period {
+        DigestPeriod::Week => weekly = enabled,
+        DigestPeriod::Month => monthly = enabled,
+    }
+    uow.user_repo()
+        .await?
+        .set_digest_subscriptions(user.user_id, weekly, monthly)
+        .await?;
+
+    let job_key = period.job_key(user.user_id);
+    if enabled {
+        schedule_job_in(
+            &mut uow,
+            PRACTICE_DIGEST_JOB,
+            serde_json::json!({ "period": period }),
+            Some(user.user_id),
+            JobTiming::Cron {
+                schedule: period.cron().parse()?,
+                utc_offset: user.utc_offset(),
+            },
+            Some(&job_key),
+        )
+        .await?;
+    } else {
+        cancel_job_in(&mut uow, &job_key).await?;
+    }
+
+    uow.commit().await?;
+    Ok((weekly, monthly))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{FixedOffset, NaiveDate};
+    use sqlx::{Pool, Sqlite};
+
+    use crate::{
+        services::{
+            class::add_class, daily_practice_log::get_practice_progress, job::set_user_utc_offset,
+            user::add_user,
+        },
+        test_utils,
+    };
+
+    use super::{DigestPeriod, get_practice_digest, set_digest_subscription};
+
+    fn day(month: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, d).unwrap()
+    }
+
+    #[test]
+    fn test_previous_period() {
+        // 2026-10-21 is a Wednesday.
+        assert_eq!(
+            DigestPeriod::Week.previous(day(10, 21)),
+            (day(10, 12), day(10, 18))
+        );
+        assert_eq!(
+            DigestPeriod::Week.previous(day(10, 19)),
+            (day(10, 12), day(10, 18))
+        );
+        assert_eq!(
+            DigestPeriod::Month.previous(day(10, 1)),
+            (day(9, 1), day(9, 30))
+        );
+        assert_eq!(
+            DigestPeriod::Month.previous(day(3, 15)),
+            (day(2, 1), day(2, 28))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_weekly_digest() -> anyhow::Result<()> {
+        let pool = test_utils::setup_db().await;
+        let arc_pool: Arc<Pool<Sqlite>> = Arc::new(pool.clone());
+        add_user(arc_pool.clone(), 1, "alice").await?;
+
+        // The week before: 2026-10-05..11.
+        test_utils::add_practice_entry(&pool, day(10, 7), 60).await?;
+        // The digest week: 2026-10-12..18.
+        test_utils::add_practice_entry(&pool, day(10, 12), 20).await?;
+        test_utils::add_practice_entry(&pool, day(10, 12), 25).await?;
+        test_utils::add_practice_entry(&pool, day(10, 14), 45).await?;
+        test_utils::add_practice_entry(&pool, day(10, 18), 30).await?;
+        // This week, not in the digest.
+        test_utils::add_practice_entry(&pool, day(10, 19), 90).await?;
+
+        let class_id = add_class(arc_pool.clone(), "Йога".into(), 10, None, None, 1).await?;
+        for d in [13, 15] {
+            sqlx::query(
+                "INSERT INTO class_deduction_history (class_id, user_id, created_at) VALUES (?, 1, ?)",
+            )
+            .bind(class_id)
+            .bind(format!("2026-10-{d} 10:00:00"))
+            .execute(&pool)
+            .await?;
+        }
+
+        let digest =
+            get_practice_digest(arc_pool.clone(), 1, DigestPeriod::Week, day(10, 19)).await?;
+        assert_eq!((digest.from, digest.until), (day(10, 12), day(10, 18)));
+        assert_eq!(digest.total_minutes, 120);
+        assert_eq!(digest.practice_days, 3);
+        assert_eq!(digest.average_minutes(), 120.0 / 7.0);
+        // Both entries of the 12th add up to the best day.
+        assert_eq!(digest.best_day, Some((day(10, 12), 45)));
+        assert_eq!(digest.previous_total_minutes, 60);
+        assert_eq!(digest.classes, vec![("Йога".to_string(), 2)]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_digest_days_follow_user_offset() -> anyhow::Result<()> {
+        let pool = test_utils::setup_db().await;
+        let arc_pool: Arc<Pool<Sqlite>> = Arc::new(pool.clone());
+        add_user(arc_pool.clone(), 1, "alice").await?;
+        // Vladivostok, UTC+10: 14:30 UTC is already half past midnight.
+        set_user_utc_offset(
+            arc_pool.clone(),
+            1,
+            FixedOffset::east_opt(10 * 3600).unwrap(),
+        )
+        .await?;
+
+        let class_id = add_class(arc_pool.clone(), "Йога".into(), 10, None, None, 1).await?;
+        for (minutes, created_at) in [
+            // Monday 2026-10-12, 00:30 local.
+            (20, "2026-10-11 14:30:00"),
+            // Monday 2026-10-19, 00:30 local: this week, not the digest one.
+            (30, "2026-10-18 14:30:00"),
+        ] {
+            sqlx::query(
+                "INSERT INTO daily_practice_log (minutes, user_id, created_at) VALUES (?, 1, ?)",
+            )
+            .bind(minutes)
+            .bind(created_at)
+            .execute(&pool)
+            .await?;
+            sqlx::query(
+                "INSERT INTO class_deduction_history (class_id, user_id, created_at) VALUES (?, 1, ?)",
+            )
+            .bind(class_id)
+            .bind(created_at)
+            .execute(&pool)
+            .await?;
+        }
+
+        let digest =
+            get_practice_digest(arc_pool.clone(), 1, DigestPeriod::Week, day(10, 19)).await?;
+        assert_eq!(digest.total_minutes, 20);
+        assert_eq!(digest.best_day, Some((day(10, 12), 20)));
+        assert_eq!(digest.previous_total_minutes, 0);
+        assert_eq!(digest.classes, vec![("Йога".to_string(), 1)]);
+
+        // Goals and streaks count the same days.
+        let progress = get_practice_progress(arc_pool.clone(), 1, day(10, 19)).await?;
+        assert_eq!((progress.today_minutes, progress.week_minutes), (30, 30));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_digest_subscription() -> anyhow::Result<()> {
+        let pool = test_utils::setup_db().await;
+        let arc_pool: Arc<Pool<Sqlite>> = Arc::new(pool.clone());
+        add_user(arc_pool.clone(), 1, "alice").await?;
+
+        assert_eq!(
+            set_digest_subscription(arc_pool.clone(), 1, DigestPeriod::Week, true).await?,
+            (true, false)
+        );
+        assert_eq!(
+            set_digest_subscription(arc_pool.clone(), 1, DigestPeriod::Month, true).await?,
+            (true, true)
+        );
+        assert_eq!(
+            set_digest_subscription(arc_pool.clone(), 1, DigestPeriod::Week, false).await?,
+            (false, true)
+        );
+
+        let jobs: Vec<(String, String, String, String)> =
+            sqlx::query_as("select unique_key, cron, payload, status from job order by job_id")
+                .fetch_all(&pool)
+                .await?;
+        assert_eq!(
+            jobs,
+            vec![
+                (
+                    "practice_digest_week:1".to_string(),
+                    "0 9 * * 1".to_string(),
+                    r#"{"period":"week"}"#.to_string(),
+                    "cancelled".to_string()
+                ),
+                (
+                    "practice_digest_month:1".to_string(),
+                    "0 9 1 * *".to_string(),
+                    r#"{"period":"month"}"#.to_string(),
+                    "scheduled".to_string()
+                ),
+            ]
+        );
+        Ok(())
+    }
+}
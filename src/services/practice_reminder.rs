@@ -0,0 +1,288 @@
+use std::sync::Arc;
+
+use anyhow::bail;
+use chrono::{DateTime, FixedOffset, NaiveTime, TimeDelta, Timelike, Utc};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+    errors::*,
+    scheduler::cron::CronSchedule,
+    services::job::{JobTiming, cancel_job_in, schedule_job_in},
+    uow::UnitOfWork,
+};
+
+pub const PRACTICE_REMINDER_JOB: &str = "practice_reminder";
+
+const REMIND_AT_FORMAT: &str = "%H:%M";
+
+fn reminder_key(user_id: i64) -> String {
+    format!("practice_reminder:{user_id}")
+}
+
+fn snooze_key(user_id: i64) -> String {
+    format!("practice_reminder_snooze:{user_id}")
+}
+
+/// The reminder time in the user's offset, `None` if the reminder is off.
+pub async fn get_practice_reminder(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+) -> anyhow::Result<Option<NaiveTime>> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let remind_at = uow
+        .user_repo()
+        .await?
+        .get_practice_reminder_at(user.user_id)
+        .await?;
+    Ok(remind_at.and_then(|t| NaiveTime::parse_from_str(&t, REMIND_AT_FORMAT).ok()))
+}
+
+/// Reminds the user every day at `remind_at` in their offset, replacing the
+/// previous time.
+pub async fn set_practice_reminder(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    remind_at: NaiveTime,
+) -> anyhow::Result<()> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let schedule: CronSchedule =
+        format!("{} {} * * *", remind_at.minute(), remind_at.hour()).parse()?;
+    schedule_job_in(
+        &mut uow,
+        PRACTICE_REMINDER_JOB,
+        serde_json::json!({}),
+        Some(user.user_id),
+        JobTiming::Cron {
+            schedule,
+            utc_offset: user.utc_offset(),
+        },
+        Some(&reminder_key(user.user_id)),
+    )
+    .await?;
+    uow.user_repo()
+        .await?
+        .set_practice_reminder_at(
+            user.user_id,
+            Some(&remind_at.format(REMIND_AT_FORMAT).to_string()),
+        )
+        .await?;
+
+    uow.commit().await?;
+    Ok(())
+}
+
+/// Turns the reminder off, including a snoozed one.
+pub async fn disable_practice_reminder(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+) -> anyhow::Result<()> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    cancel_job_in(&mut uow, &reminder_key(user.user_id)).await?;
+    cancel_job_in(&mut uow, &snooze_key(user.user_id)).await?;
+    uow.user_repo()
+        .await?
+        .set_practice_reminder_at(user.user_id, None)
+        .await?;
+
+    uow.commit().await?;
+    Ok(())
+}
+
+/// Reminds once more in `minutes`. A newer snooze replaces the older one.
+/// Returns when, in the user's offset.
+pub async fn snooze_practice_reminder(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    minutes: u16,
+) -> anyhow::Result<DateTime<FixedOffset>> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let run_at = Utc::now() + TimeDelta::minutes(minutes.into());
+    schedule_job_in(
+        &mut uow,
+        PRACTICE_REMINDER_JOB,
+        serde_json::json!({}),
+        Some(user.user_id),
+        JobTiming::Once(run_at),
+        Some(&snooze_key(user.user_id)),
+    )
+    .await?;
+
+    uow.commit().await?;
+    Ok(run_at.with_timezone(&user.utc_offset()))
+}
+
+/// Whether anything was logged since midnight of `now` in the user's offset.
+pub async fn is_practice_logged_today(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    now: DateTime<Utc>,
+) -> anyhow::Result<bool> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let utc_offset = user.utc_offset();
+    let midnight = now
+        .with_timezone(&utc_offset)
+        .date_naive()
+        .and_time(NaiveTime::MIN)
+        - utc_offset;
+    let minutes = uow
+        .daily_practice_log_repo()
+        .await?
+        .get_minutes_since(user.user_id, midnight)
+        .await?;
+    Ok(minutes > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{FixedOffset, NaiveTime, TimeDelta, Utc};
+    use sqlx::{Pool, Sqlite};
+
+    use crate::{
+        services::{
+            daily_practice_log::add_daily_practice_entry, job::set_user_utc_offset, user::add_user,
+        },
+        test_utils, utils,
+    };
+
+    use super::{
+        disable_practice_reminder, get_practice_reminder, is_practice_logged_today,
+        set_practice_reminder, snooze_practice_reminder,
+    };
+
+    async fn jobs(pool: &Pool<Sqlite>) -> Vec<(String, Option<String>, String)> {
+        sqlx::query_as("select unique_key, cron, status from job order by job_id")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_set_and_disable_practice_reminder() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        add_user(pool.clone(), 1, "user").await?;
+        set_user_utc_offset(pool.clone(), 1, FixedOffset::east_opt(3 * 3600).unwrap()).await?;
+        assert_eq!(get_practice_reminder(pool.clone(), 1).await?, None);
+
+        set_practice_reminder(pool.clone(), 1, NaiveTime::from_hms_opt(20, 30, 0).unwrap()).await?;
+        set_practice_reminder(pool.clone(), 1, NaiveTime::from_hms_opt(21, 0, 0).unwrap()).await?;
+        assert_eq!(
+            get_practice_reminder(pool.clone(), 1).await?,
+            NaiveTime::from_hms_opt(21, 0, 0)
+        );
+        let run_at: (String,) = sqlx::query_as("select run_at from job")
+            .fetch_one(pool.as_ref())
+            .await?;
+        // 21:00 at UTC+3.
+        assert!(run_at.0.ends_with("18:00:00"));
+
+        let remind_at = snooze_practice_reminder(pool.clone(), 1, 30).await?;
+        let expected = Utc::now() + TimeDelta::minutes(30);
+        assert!((remind_at.to_utc() - expected).num_seconds().abs() < 5);
+        assert_eq!(remind_at.offset().local_minus_utc(), 3 * 3600);
+
+        let user_id = 1;
+        assert_eq!(
+            jobs(pool.as_ref()).await,
+            vec![
+                (
+                    format!("practice_reminder:{user_id}"),
+                    Some("0 21 * * *".to_string()),
+                    "scheduled".to_string()
+                ),
+                (
+                    format!("practice_reminder_snooze:{user_id}"),
+                    None,
+                    "scheduled".to_string()
+                ),
+            ]
+        );
+
+        disable_practice_reminder(pool.clone(), 1).await?;
+        assert_eq!(get_practice_reminder(pool.clone(), 1).await?, None);
+        assert!(
+            jobs(pool.as_ref())
+                .await
+                .iter()
+                .all(|(_, _, status)| status == "cancelled")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_is_practice_logged_today() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        add_user(pool.clone(), 1, "user").await?;
+
+        let now = Utc::now();
+        assert!(!is_practice_logged_today(pool.clone(), 1, now).await?);
+
+        add_daily_practice_entry(pool.clone(), 20, 1, None, utils::today(), None).await?;
+        assert!(is_practice_logged_today(pool.clone(), 1, Utc::now()).await?);
+        // An entry made today does not count for tomorrow.
+        let tomorrow = Utc::now() + TimeDelta::days(1);
+        assert!(!is_practice_logged_today(pool.clone(), 1, tomorrow).await?);
+        Ok(())
+    }
+}
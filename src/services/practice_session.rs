@@ -0,0 +1,313 @@
+use std::sync::Arc;
+
+use anyhow::bail;
+use chrono::{DateTime, TimeDelta, Utc};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+    errors::*,
+    repositories::practice_session::PracticeSession,
+    services::job::{JobTiming, cancel_job_in, schedule_job_in},
+    uow::UnitOfWork,
+};
+
+pub const PRACTICE_SESSION_TIMEOUT_JOB: &str = "practice_session_timeout";
+
+fn timeout_key(user_id: i64) -> String {
+    format!("practice_session_timeout:{user_id}")
+}
+
+/// Starts the stopwatch at `now`. It is stopped automatically once
+/// `max_duration` has passed since the start, pauses included.
+pub async fn start_practice_session(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    now: DateTime<Utc>,
+    max_duration: TimeDelta,
+) -> anyhow::Result<PracticeSession> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let mut session_repo = uow.practice_session_repo().await?;
+    if session_repo.get_by_user_id(user_id).await?.is_some() {
+        bail!(PracticeSessionAlreadyStartedError);
+    }
+    session_repo.create(user_id, now).await?;
+    let Some(session) = session_repo.get_by_user_id(user_id).await? else {
+        bail!(SomethingWentWrongError);
+    };
+
+    schedule_job_in(
+        &mut uow,
+        PRACTICE_SESSION_TIMEOUT_JOB,
+        serde_json::json!({}),
+        Some(user_id),
+        JobTiming::Once(now + max_duration),
+        Some(&timeout_key(user_id)),
+    )
+    .await?;
+
+    uow.commit().await?;
+    Ok(session)
+}
+
+/// The running or paused stopwatch, if any.
+pub async fn get_practice_session(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+) -> anyhow::Result<Option<PracticeSession>> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let session = uow
+        .practice_session_repo()
+        .await?
+        .get_by_user_id(user_id)
+        .await?;
+    Ok(session)
+}
+
+/// Pausing a paused stopwatch changes nothing.
+pub async fn pause_practice_session(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    now: DateTime<Utc>,
+) -> anyhow::Result<PracticeSession> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let mut session_repo = uow.practice_session_repo().await?;
+    let Some(session) = session_repo.get_by_user_id(user_id).await? else {
+        bail!(PracticeSessionNotStartedError);
+    };
+    if !session.is_paused() {
+        session_repo.pause(session.practice_session_id, now).await?;
+    }
+    let Some(session) = session_repo.get_by_user_id(user_id).await? else {
+        bail!(SomethingWentWrongError);
+    };
+
+    uow.commit().await?;
+    Ok(session)
+}
+
+/// Resuming a running stopwatch changes nothing.
+pub async fn resume_practice_session(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    now: DateTime<Utc>,
+) -> anyhow::Result<PracticeSession> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let mut session_repo = uow.practice_session_repo().await?;
+    let Some(session) = session_repo.get_by_user_id(user_id).await? else {
+        bail!(PracticeSessionNotStartedError);
+    };
+    if let Some(paused_at) = session.paused_at_utc() {
+        let paused_secs = (now - paused_at).num_seconds().max(0);
+        session_repo
+            .resume(
+                session.practice_session_id,
+                session.paused_secs + paused_secs,
+            )
+            .await?;
+    }
+    let Some(session) = session_repo.get_by_user_id(user_id).await? else {
+        bail!(SomethingWentWrongError);
+    };
+
+    uow.commit().await?;
+    Ok(session)
+}
+
+#[derive(Debug)]
+pub struct StoppedPracticeSession {
+    pub minutes: u16,
+    /// `None` when the session was too short to be logged.
+    pub daily_practice_log_id: Option<i64>,
+    /// The session ran past the maximum and was cut down to it.
+    pub capped: bool,
+}
+
+/// Stops the stopwatch and logs the measured minutes, rounded, on the day the
+/// session started. At most `max_duration` is logged.
+pub async fn stop_practice_session(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+    now: DateTime<Utc>,
+    max_duration: TimeDelta,
+) -> anyhow::Result<StoppedPracticeSession> {
+    let mut uow = UnitOfWork::new_transactional(db_pool.as_ref()).await?;
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let mut session_repo = uow.practice_session_repo().await?;
+    let Some(session) = session_repo.get_by_user_id(user_id).await? else {
+        bail!(PracticeSessionNotStartedError);
+    };
+    session_repo.delete(session.practice_session_id).await?;
+
+    let elapsed = session.elapsed(now);
+    let capped = elapsed > max_duration;
+    let seconds = elapsed.min(max_duration).num_seconds();
+    let minutes = u16::try_from((seconds + 30) / 60).unwrap_or(u16::MAX);
+    let daily_practice_log_id = if minutes > 0 {
+        Some(
+            uow.daily_practice_log_repo()
+                .await?
+                .create(minutes, user_id, None, Some(session.started_at), None)
+                .await?,
+        )
+    } else {
+        None
+    };
+    cancel_job_in(&mut uow, &timeout_key(user_id)).await?;
+
+    uow.commit().await?;
+    Ok(StoppedPracticeSession {
+        minutes,
+        daily_practice_log_id,
+        capped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{TimeDelta, TimeZone, Utc};
+
+    use crate::{errors::*, services::user::add_user, test_utils};
+
+    use super::{
+        get_practice_session, pause_practice_session, resume_practice_session,
+        start_practice_session, stop_practice_session,
+    };
+
+    #[tokio::test]
+    async fn test_practice_session_with_pause() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        add_user(pool.clone(), 1, "alice").await?;
+        let max = TimeDelta::hours(4);
+        let start = Utc.with_ymd_and_hms(2026, 10, 18, 9, 0, 0).unwrap();
+        let at = |minutes: i64| start + TimeDelta::minutes(minutes);
+
+        start_practice_session(pool.clone(), 1, start, max).await?;
+        let err = start_practice_session(pool.clone(), 1, at(1), max)
+            .await
+            .expect_err("already started");
+        assert!(
+            err.downcast_ref::<PracticeSessionAlreadyStartedError>()
+                .is_some()
+        );
+
+        let session = pause_practice_session(pool.clone(), 1, at(10)).await?;
+        assert!(session.is_paused());
+        // The stopwatch stands still while paused.
+        assert_eq!(session.elapsed(at(20)), TimeDelta::minutes(10));
+        pause_practice_session(pool.clone(), 1, at(20)).await?;
+
+        let session = resume_practice_session(pool.clone(), 1, at(25)).await?;
+        assert!(!session.is_paused());
+        assert_eq!(session.elapsed(at(45)), TimeDelta::minutes(30));
+
+        let stopped = stop_practice_session(pool.clone(), 1, at(45), max).await?;
+        assert_eq!((stopped.minutes, stopped.capped), (30, false));
+        assert!(stopped.daily_practice_log_id.is_some());
+        assert!(get_practice_session(pool.clone(), 1).await?.is_none());
+
+        let (minutes, created_at): (i64, String) =
+            sqlx::query_as("select minutes, created_at from daily_practice_log")
+                .fetch_one(pool.as_ref())
+                .await?;
+        assert_eq!((minutes, created_at.as_str()), (30, "2026-10-18 09:00:00"));
+        let (status,): (String,) = sqlx::query_as("select status from job")
+            .fetch_one(pool.as_ref())
+            .await?;
+        assert_eq!(status, "cancelled");
+
+        let err = stop_practice_session(pool.clone(), 1, at(50), max)
+            .await
+            .expect_err("not started");
+        assert!(
+            err.downcast_ref::<PracticeSessionNotStartedError>()
+                .is_some()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_practice_session_is_capped() -> anyhow::Result<()> {
+        let pool = Arc::new(test_utils::setup_db().await);
+        add_user(pool.clone(), 1, "alice").await?;
+        let max = TimeDelta::hours(4);
+        let start = Utc.with_ymd_and_hms(2026, 10, 18, 9, 0, 0).unwrap();
+
+        start_practice_session(pool.clone(), 1, start, max).await?;
+        let (run_at,): (String,) = sqlx::query_as("select run_at from job")
+            .fetch_one(pool.as_ref())
+            .await?;
+        assert_eq!(run_at, "2026-10-18 13:00:00");
+
+        let stopped =
+            stop_practice_session(pool.clone(), 1, start + TimeDelta::hours(9), max).await?;
+        assert_eq!((stopped.minutes, stopped.capped), (240, true));
+
+        // Less than half a minute is not worth an entry.
+        start_practice_session(pool.clone(), 1, start, max).await?;
+        let stopped =
+            stop_practice_session(pool.clone(), 1, start + TimeDelta::seconds(20), max).await?;
+        assert_eq!((stopped.minutes, stopped.daily_practice_log_id), (0, None));
+        Ok(())
+    }
+}
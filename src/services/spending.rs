@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use anyhow::bail;
+use sqlx::{Pool, Sqlite};
+
+use crate::{errors::*, repositories::class_pack::MonthlySpend, uow::UnitOfWork};
+
+/// How many recent months the spending report covers.
+const REPORT_MONTHS: u32 = 6;
+
+/// Spending on one class in one currency. Amounts are in minor units.
+pub struct ClassSpending {
+    pub name: String,
+    pub currency: String,
+    pub attended: i64,
+    pub attended_cost: i64,
+    pub prepaid_value: i64,
+}
+
+impl ClassSpending {
+    pub fn cost_per_class(&self) -> Option<i64> {
+        if self.attended == 0 {
+            return None;
+        }
+        Some((self.attended_cost as f64 / self.attended as f64).round() as i64)
+    }
+}
+
+pub struct SpendingReport {
+    pub classes: Vec<ClassSpending>,
+    pub months: Vec<MonthlySpend>,
+}
+
+/// Only packs bought with a price count; a deduction costs the price of its
+/// pack divided by the number of classes in it.
+pub async fn get_spending_report(
+    db_pool: Arc<Pool<Sqlite>>,
+    telegram_user_id: i64,
+) -> anyhow::Result<SpendingReport> {
+    let mut uow = UnitOfWork::new_readonly(db_pool.as_ref());
+    let user_id = match uow
+        .user_repo()
+        .await?
+        .get_user_by_telegram_id(telegram_user_id)
+        .await?
+    {
+        Some(u) => u.user_id,
+        None => {
+            bail!(UserNotFoundError);
+        }
+    };
+
+    let usage = uow
+        .class_pack_repo()
+        .await?
+        .get_priced_pack_usage(user_id)
+        .await?;
+
+    let mut classes: Vec<(i64, ClassSpending)> = vec![];
+    for pack in usage {
+        let unit_price = pack.price as f64 / pack.quantity as f64;
+        let position = classes
+            .iter()
+            .position(|(class_id, s)| *class_id == pack.class_id && s.currency == pack.currency);
+        let spending = match position {
+            Some(i) => &mut classes[i].1,
+            None => {
+                classes.push((
+                    pack.class_id,
+                    ClassSpending {
+                        name: pack.name.clone(),
+                        currency: pack.currency.clone(),
+                        attended: 0,
+                        attended_cost: 0,
+                        prepaid_value: 0,
+                    },
+                ));
+                &mut classes.last_mut().expect("just pushed").1
+            }
+        };
+        spending.attended += pack.attended;
+        spending.attended_cost += (unit_price * pack.attended as f64).round() as i64;
+        spending.prepaid_value += (unit_price * pack.remaining as f64).round() as i64;
+    }
+
+    let months = uow
+        .class_pack_repo()
+        .await?
+        .get_monthly_spend(user_id, REPORT_MONTHS)
+        .await?;
+
+    Ok(SpendingReport {
+        classes: classes.into_iter().map(|(_, s)| s).collect(),
+        months,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use sqlx::{Pool, Sqlite};
+
+    use crate::{
+        config::ExpiredDeductionPolicy,
+        repositories::class_pack::PackPrice,
+        services::{
+            class::{add_class, deduct_class, top_up_class},
+            user::add_user,
+        },
+        test_utils, utils,
+    };
+
+    use super::get_spending_report;
+
+    fn rub(amount: i64) -> Option<PackPrice> {
+        Some(PackPrice {
+            amount,
+            currency: "RUB".into(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_spending_report() -> anyhow::Result<()> {
+        let pool = test_utils::setup_db().await;
+        let arc_pool: Arc<Pool<Sqlite>> = Arc::new(pool);
+        add_user(arc_pool.clone(), 1, "alice").await?;
+        let class_id = add_class(arc_pool.clone(), "Йога".into(), 2, None, rub(200000), 1).await?;
+        top_up_class(arc_pool.clone(), class_id, 1, 4, None, rub(600000)).await?;
+        add_class(arc_pool.clone(), "Пилатес".into(), 3, None, None, 1).await?;
+
+        for _ in 0..3 {
+            deduct_class(
+                arc_pool.clone(),
+                class_id,
+                1,
+                utils::today(),
+                ExpiredDeductionPolicy::Warn,
+            )
+            .await?;
+        }
+
+        let report = get_spending_report(arc_pool.clone(), 1).await?;
+        assert_eq!(report.classes.len(), 1);
+        let yoga = &report.classes[0];
+        assert_eq!(yoga.attended, 3);
+        // Two classes at 1000 and one at 1500.
+        assert_eq!(yoga.attended_cost, 350000);
+        assert_eq!(yoga.cost_per_class(), Some(116667));
+        assert_eq!(yoga.prepaid_value, 450000);
+
+        assert_eq!(report.months.len(), 1);
+        assert_eq!(report.months[0].total, 800000);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+//! Tables of user data written out as CSV or XLSX files.
+
+pub mod xlsx;
+
+pub enum Cell {
+    Text(String),
+    Number(i64),
+    Empty,
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Cell::Text(text)
+    }
+}
+
+impl From<Option<String>> for Cell {
+    fn from(text: Option<String>) -> Self {
+        text.map_or(Cell::Empty, Cell::Text)
+    }
+}
+
+impl From<i64> for Cell {
+    fn from(number: i64) -> Self {
+        Cell::Number(number)
+    }
+}
+
+/// One table: a file in CSV or a sheet in XLSX.
+pub struct Table {
+    /// Used for the CSV file name and the XLSX sheet name, so it has to be
+    /// a valid name for both.
+    pub name: &'static str,
+    pub header: Vec<&'static str>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// RFC 4180 CSV with a UTF-8 byte order mark, without which Excel misreads
+/// Cyrillic.
+pub fn to_csv(table: &Table) -> Vec<u8> {
+    let mut csv = String::from('\u{feff}');
+    let header: Vec<String> = table.header.iter().map(|h| csv_field(h)).collect();
+    csv.push_str(&header.join(","));
+    csv.push_str("\r\n");
+    for row in &table.rows {
+        let fields: Vec<String> = row
+            .iter()
+            .map(|cell| match cell {
+                Cell::Text(text) => csv_field(text),
+                Cell::Number(number) => number.to_string(),
+                Cell::Empty => String::new(),
+            })
+            .collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_csv() {
+        let table = Table {
+            name: "practice",
+            header: vec!["Дата", "Минуты", "Заметка"],
+            rows: vec![
+                vec![
+                    Cell::from("2026-10-18 09:30".to_string()),
+                    Cell::from(45),
+                    Cell::from(Some("гаммы, \"этюд\"".to_string())),
+                ],
+                vec![
+                    Cell::from("2026-10-19 10:00".to_string()),
+                    Cell::from(20),
+                    Cell::from(None),
+                ],
+            ],
+        };
+        let csv = String::from_utf8(to_csv(&table)).unwrap();
+        assert_eq!(
+            csv,
+            "\u{feff}Дата,Минуты,Заметка\r\n\
+             2026-10-18 09:30,45,\"гаммы, \"\"этюд\"\"\"\r\n\
+             2026-10-19 10:00,20,\r\n"
+        );
+    }
+}
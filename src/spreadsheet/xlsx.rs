@@ -0,0 +1,200 @@
+//! A bare-bones XLSX writer: one sheet per table, strings written inline,
+//! packed into a deflated ZIP archive.
+
+use std::io::{Cursor, Write};
+
+use zip::{ZipWriter, write::SimpleFileOptions};
+
+use super::{Cell, Table};
+
+const XML_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#;
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            // Other control characters are not allowed in XML 1.0.
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// `A`, `B`, …, `Z`, `AA`, … for a zero-based column index.
+fn column_name(mut index: usize) -> String {
+    let mut name = vec![];
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+fn sheet_xml(table: &Table) -> String {
+    let header: Vec<Cell> = table
+        .header
+        .iter()
+        .map(|h| Cell::Text(h.to_string()))
+        .collect();
+    let mut xml = format!(
+        r#"{XML_HEADER}<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#
+    );
+    for (row_index, row) in std::iter::once(&header).chain(&table.rows).enumerate() {
+        let row_number = row_index + 1;
+        xml.push_str(&format!(r#"<row r="{row_number}">"#));
+        for (column_index, cell) in row.iter().enumerate() {
+            let reference = format!("{}{}", column_name(column_index), row_number);
+            match cell {
+                Cell::Text(text) => xml.push_str(&format!(
+                    r#"<c r="{reference}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                    escape_xml(text)
+                )),
+                Cell::Number(number) => {
+                    xml.push_str(&format!(r#"<c r="{reference}"><v>{number}</v></c>"#))
+                }
+                Cell::Empty => {}
+            }
+        }
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+/// A workbook with a sheet per table, in the given order.
+pub fn to_xlsx(tables: &[Table]) -> Vec<u8> {
+    let mut content_types = format!(
+        r#"{XML_HEADER}<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#
+    );
+    let mut sheets = String::new();
+    let mut relationships = String::new();
+    for (index, table) in tables.iter().enumerate() {
+        let id = index + 1;
+        content_types.push_str(&format!(
+            r#"<Override PartName="/xl/worksheets/sheet{id}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#
+        ));
+        sheets.push_str(&format!(
+            r#"<sheet name="{}" sheetId="{id}" r:id="rId{id}"/>"#,
+            escape_xml(table.name)
+        ));
+        relationships.push_str(&format!(
+            r#"<Relationship Id="rId{id}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{id}.xml"/>"#
+        ));
+    }
+    content_types.push_str("</Types>");
+
+    let mut parts = vec![("[Content_Types].xml".to_string(), content_types)];
+    parts.push((
+        "_rels/.rels".to_string(),
+        format!(
+            r#"{XML_HEADER}<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#
+        ),
+    ));
+    parts.push((
+        "xl/workbook.xml".to_string(),
+        format!(
+            r#"{XML_HEADER}<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets>{sheets}</sheets></workbook>"#
+        ),
+    ));
+    parts.push((
+        "xl/_rels/workbook.xml.rels".to_string(),
+        format!(
+            r#"{XML_HEADER}<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{relationships}</Relationships>"#
+        ),
+    ));
+    for (index, table) in tables.iter().enumerate() {
+        parts.push((
+            format!("xl/worksheets/sheet{}.xml", index + 1),
+            sheet_xml(table),
+        ));
+    }
+
+    let mut zip = ZipWriter::new(Cursor::new(vec![]));
+    for (name, content) in parts {
+        // Writing to memory cannot fail.
+        zip.start_file(name, SimpleFileOptions::default())
+            .expect("ZIP entry is written to memory");
+        zip.write_all(content.as_bytes())
+            .expect("ZIP entry is written to memory");
+    }
+    zip.finish()
+        .expect("ZIP archive is written to memory")
+        .into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use zip::ZipArchive;
+
+    use super::*;
+
+    #[test]
+    fn test_column_name() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(27), "AB");
+        assert_eq!(column_name(701), "ZZ");
+        assert_eq!(column_name(702), "AAA");
+    }
+
+    #[test]
+    fn test_to_xlsx() {
+        let tables = [
+            Table {
+                name: "classes",
+                header: vec!["Название"],
+                rows: vec![vec![Cell::from("Йога <утро> & вечер".to_string())]],
+            },
+            Table {
+                name: "practice",
+                header: vec!["Минуты"],
+                rows: vec![vec![Cell::from(45)]],
+            },
+        ];
+        let xlsx = to_xlsx(&tables);
+
+        let mut archive = ZipArchive::new(Cursor::new(xlsx)).unwrap();
+        // Four package parts and a part per sheet.
+        let names: Vec<&str> = archive.file_names().collect();
+        assert_eq!(names.len(), 6);
+        for name in [
+            "[Content_Types].xml",
+            "_rels/.rels",
+            "xl/workbook.xml",
+            "xl/_rels/workbook.xml.rels",
+            "xl/worksheets/sheet1.xml",
+            "xl/worksheets/sheet2.xml",
+        ] {
+            let mut entry = archive.by_name(name).unwrap();
+            let mut content = String::new();
+            entry.read_to_string(&mut content).unwrap();
+            assert_eq!(entry.crc32(), crc32fast::hash(content.as_bytes()), "{name}");
+            assert!(content.starts_with(XML_HEADER), "{name}");
+        }
+        for (index, table) in tables.iter().enumerate() {
+            let mut entry = archive
+                .by_name(&format!("xl/worksheets/sheet{}.xml", index + 1))
+                .unwrap();
+            let mut content = String::new();
+            entry.read_to_string(&mut content).unwrap();
+            assert_eq!(content, sheet_xml(table));
+        }
+
+        let sheet = sheet_xml(&tables[0]);
+        assert!(sheet.contains("<t xml:space=\"preserve\">Йога &lt;утро&gt; &amp; вечер</t>"));
+        assert!(sheet_xml(&tables[1]).contains(r#"<c r="A2"><v>45</v></c>"#));
+    }
+}
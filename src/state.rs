@@ -1,4 +1,12 @@
-#[derive(Clone, Default)]
+use serde::{Deserialize, Serialize};
+use teloxide::{dispatching::dialogue::ErasedStorage, prelude::Dialogue};
+
+use crate::{services::daily_practice_log::PracticeGoalPeriod, utils};
+
+pub type DialogueStorage = ErasedStorage<State>;
+pub type BotDialogue = Dialogue<State, DialogueStorage>;
+
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub enum State {
     #[default]
     Idle,
@@ -8,12 +16,94 @@ pub enum State {
     AddingClassReceiveQuantity {
         name: String,
     },
+    AddingClassReceiveValidUntil {
+        name: String,
+        quantity: u8,
+    },
+    AddingClassReceivePrice {
+        name: String,
+        quantity: u8,
+        valid_until: Option<String>,
+    },
 
     // Update class quantity states
     UpdatingClassReceiveQuantity {
         class_id: i64,
     },
 
+    // Edit class states
+    RenamingClassReceiveName {
+        class_id: i64,
+    },
+    UpdatingClassReceiveValidUntil {
+        class_id: i64,
+    },
+    FreezingClassReceiveDays {
+        class_id: i64,
+    },
+    UpdatingClassReceiveLowBalanceThreshold {
+        class_id: i64,
+    },
+
+    // Top up class states
+    ToppingUpClassReceiveQuantity {
+        class_id: i64,
+    },
+    ToppingUpClassReceiveValidUntil {
+        class_id: i64,
+        quantity: u8,
+    },
+    ToppingUpClassReceivePrice {
+        class_id: i64,
+        quantity: u8,
+        valid_until: Option<String>,
+    },
+
+    // Class schedule states
+    AddingClassScheduleReceiveSlot {
+        class_id: i64,
+    },
+
+    // Deduction note states
+    AddingDeductionNoteReceiveText {
+        class_deduction_history_id: i64,
+    },
+
     // Add daily practice states
     AddingDailyPracticeReceiveMinutes,
+    AddingPastDailyPracticeReceiveMinutes {
+        practiced_on: String,
+    },
+    AddingDailyPracticeReceiveCategory {
+        minutes: u16,
+        /// Missing from dialogues saved before past days could be picked.
+        #[serde(default = "today")]
+        practiced_on: String,
+    },
+    AddingDailyPracticeReceiveNote {
+        minutes: u16,
+        practiced_on: String,
+        practice_category_id: Option<i64>,
+    },
+    AddingPracticeNoteReceiveText {
+        daily_practice_log_id: i64,
+    },
+    EditingDailyPracticeReceiveMinutes {
+        daily_practice_log_id: i64,
+    },
+
+    // Practice category states
+    AddingPracticeCategoryReceiveName,
+
+    // Practice reminder states
+    SettingPracticeReminderReceiveTime,
+
+    // Practice goal states
+    SettingPracticeGoalReceiveMinutes {
+        period: PracticeGoalPeriod,
+    },
+}
+
+fn today() -> String {
+    utils::today().to_string()
 }
@@ -1,3 +1,4 @@
+use chrono::{Local, NaiveDate, TimeZone};
 use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
 
 #[cfg(test)]
@@ -15,3 +16,132 @@ pub async fn setup_db() -> SqlitePool {
 
     pool
 }
+
+/// Applies the migrations up to and including `version`, to set up data
+/// the way an older schema held it before testing the next migration.
+#[cfg(test)]
+pub async fn migrate_up_to(pool: &SqlitePool, version: i64) {
+    let mut migrator = sqlx::migrate!("./migrations");
+    migrator.migrations = migrator
+        .migrations
+        .iter()
+        .filter(|m| m.version <= version)
+        .cloned()
+        .collect::<Vec<_>>()
+        .into();
+
+    migrator.run(pool).await.expect("Failed to run migrations");
+}
+
+/// A practice entry of user 1 at noon local time on `day`, stored as UTC
+/// like `current_timestamp` does.
+#[cfg(test)]
+pub async fn add_practice_entry(
+    pool: &SqlitePool,
+    day: NaiveDate,
+    minutes: u16,
+) -> anyhow::Result<()> {
+    let created_at = Local
+        .from_local_datetime(&day.and_hms_opt(12, 0, 0).unwrap())
+        .unwrap()
+        .naive_utc()
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+    sqlx::query("INSERT INTO daily_practice_log (minutes, user_id, created_at) VALUES (?, 1, ?)")
+        .bind(minutes)
+        .bind(created_at)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::migrate_up_to;
+
+    #[tokio::test]
+    async fn test_class_name_unique_per_user_migration() -> anyhow::Result<()> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await?;
+        migrate_up_to(&pool, 20261018100000).await;
+
+        sqlx::query(
+            "INSERT INTO user (user_id, telegram_id, username) VALUES (1, 100, 'alice'), (2, 200, 'bob')",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "INSERT INTO class (class_id, name, quantity, user_id, archived_at)
+             VALUES (3, 'Йога', 5, 1, NULL), (7, 'Пилатес', 2, 2, '2026-10-01 10:00:00')",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "INSERT INTO class_deduction_history (class_deduction_history_id, created_at, class_id, user_id)
+             VALUES (10, '2026-10-01 09:00:00', 3, 1), (11, '2026-10-02 09:00:00', 7, 2)",
+        )
+        .execute(&pool)
+        .await?;
+
+        migrate_up_to(&pool, 20261018110000).await;
+
+        let classes: Vec<(i64, String, i64, i64, Option<String>)> = sqlx::query_as(
+            "SELECT class_id, name, quantity, user_id, archived_at FROM class ORDER BY class_id",
+        )
+        .fetch_all(&pool)
+        .await?;
+        assert_eq!(
+            classes,
+            vec![
+                (3, "Йога".into(), 5, 1, None),
+                (
+                    7,
+                    "Пилатес".into(),
+                    2,
+                    2,
+                    Some("2026-10-01 10:00:00".into())
+                ),
+            ]
+        );
+        let histories: Vec<(i64, String, i64, i64)> = sqlx::query_as(
+            "SELECT class_deduction_history_id, created_at, class_id, user_id
+             FROM class_deduction_history ORDER BY class_deduction_history_id",
+        )
+        .fetch_all(&pool)
+        .await?;
+        assert_eq!(
+            histories,
+            vec![
+                (10, "2026-10-01 09:00:00".into(), 3, 1),
+                (11, "2026-10-02 09:00:00".into(), 7, 2),
+            ]
+        );
+
+        let violations = sqlx::query("PRAGMA foreign_key_check")
+            .fetch_all(&pool)
+            .await?;
+        assert!(violations.is_empty());
+        // The history still references the rebuilt table.
+        sqlx::query("DELETE FROM class WHERE class_id = 7")
+            .execute(&pool)
+            .await?;
+        let (left,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM class_deduction_history")
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(left, 1);
+
+        sqlx::query("INSERT INTO class (name, quantity, user_id) VALUES ('Йога', 1, 1)")
+            .execute(&pool)
+            .await
+            .expect_err("a name is unique per user");
+        sqlx::query("INSERT INTO class (name, quantity, user_id) VALUES ('Йога', 1, 2)")
+            .execute(&pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,6 +1,10 @@
 use crate::repositories::{
-    class::ClassRepository, class_deduction_history::ClassDeductionHistoryRepository,
-    daily_practice_log::DailyPracticeLogRepository, user::UserRepository,
+    class::ClassRepository, class_attendance::ClassAttendanceRepository,
+    class_deduction_history::ClassDeductionHistoryRepository, class_freeze::ClassFreezeRepository,
+    class_pack::ClassPackRepository, class_schedule::ClassScheduleRepository,
+    daily_practice_log::DailyPracticeLogRepository, job::JobRepository,
+    practice_category::PracticeCategoryRepository, practice_session::PracticeSessionRepository,
+    user::UserRepository,
 };
 use sqlx::{Pool, Sqlite, SqliteConnection, SqlitePool, Transaction, pool::PoolConnection};
 
@@ -74,10 +78,53 @@ impl<'a> UnitOfWork<'a> {
         Ok(ClassDeductionHistoryRepository::new(conn))
     }
 
+    pub async fn class_freeze_repo(&mut self) -> Result<ClassFreezeRepository<'_>, sqlx::Error> {
+        let conn = self.connection().await?;
+        Ok(ClassFreezeRepository::new(conn))
+    }
+
+    pub async fn class_pack_repo(&mut self) -> Result<ClassPackRepository<'_>, sqlx::Error> {
+        let conn = self.connection().await?;
+        Ok(ClassPackRepository::new(conn))
+    }
+
+    pub async fn class_schedule_repo(
+        &mut self,
+    ) -> Result<ClassScheduleRepository<'_>, sqlx::Error> {
+        let conn = self.connection().await?;
+        Ok(ClassScheduleRepository::new(conn))
+    }
+
+    pub async fn class_attendance_repo(
+        &mut self,
+    ) -> Result<ClassAttendanceRepository<'_>, sqlx::Error> {
+        let conn = self.connection().await?;
+        Ok(ClassAttendanceRepository::new(conn))
+    }
+
     pub async fn daily_practice_log_repo(
         &mut self,
     ) -> Result<DailyPracticeLogRepository<'_>, sqlx::Error> {
         let conn = self.connection().await?;
         Ok(DailyPracticeLogRepository::new(conn))
     }
+
+    pub async fn job_repo(&mut self) -> Result<JobRepository<'_>, sqlx::Error> {
+        let conn = self.connection().await?;
+        Ok(JobRepository::new(conn))
+    }
+
+    pub async fn practice_category_repo(
+        &mut self,
+    ) -> Result<PracticeCategoryRepository<'_>, sqlx::Error> {
+        let conn = self.connection().await?;
+        Ok(PracticeCategoryRepository::new(conn))
+    }
+
+    pub async fn practice_session_repo(
+        &mut self,
+    ) -> Result<PracticeSessionRepository<'_>, sqlx::Error> {
+        let conn = self.connection().await?;
+        Ok(PracticeSessionRepository::new(conn))
+    }
 }
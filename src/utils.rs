@@ -1,4 +1,4 @@
-use chrono::Weekday;
+use chrono::{FixedOffset, Local, NaiveDate, NaiveTime, Utc, Weekday};
 
 use teloxide::types::{Update, UpdateKind, User};
 
@@ -26,6 +26,24 @@ pub fn get_russian_weekday_name(weekday: Weekday, short_form: bool) -> &'static
     }
 }
 
+pub fn get_russian_month_name(month: u32) -> &'static str {
+    match month {
+        1 => "Январь",
+        2 => "Февраль",
+        3 => "Март",
+        4 => "Апрель",
+        5 => "Май",
+        6 => "Июнь",
+        7 => "Июль",
+        8 => "Август",
+        9 => "Сентябрь",
+        10 => "Октябрь",
+        11 => "Ноябрь",
+        12 => "Декабрь",
+        _ => "",
+    }
+}
+
 pub fn get_user(update: &Update) -> Option<&User> {
     match &update.kind {
         UpdateKind::Message(msg)
@@ -38,3 +56,274 @@ pub fn get_user(update: &Update) -> Option<&User> {
         _ => None,
     }
 }
+
+/// Today's date in the bot's local timezone (`TZ`).
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Today's date at the given offset, e.g. the user's.
+pub fn today_in(utc_offset: FixedOffset) -> NaiveDate {
+    Utc::now().with_timezone(&utc_offset).date_naive()
+}
+
+/// An SQLite date modifier shifting a UTC timestamp to `utc_offset`, as in
+/// `date(created_at, ?)`.
+pub fn sqlite_offset_modifier(utc_offset: FixedOffset) -> String {
+    format!("{:+} seconds", utc_offset.local_minus_utc())
+}
+
+/// Parses a date typed by the user as `ДД.ММ.ГГГГ`.
+pub fn parse_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text.trim(), "%d.%m.%Y").ok()
+}
+
+pub fn format_date(date: NaiveDate) -> String {
+    date.format("%d.%m.%Y").to_string()
+}
+
+/// Parses a `YYYY-MM` month from callback data into its first day.
+pub fn parse_month(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{}-01", text), "%Y-%m-%d").ok()
+}
+
+/// Parses a price typed as `4500`, `4500.50` or `4500,50 EUR` into an amount
+/// in minor units and a currency code, falling back to `default_currency`.
+pub fn parse_price(text: &str, default_currency: &str) -> Option<(i64, String)> {
+    let mut parts = text.split_whitespace();
+    let amount = parts.next()?.replace(',', ".");
+    let currency = match parts.next() {
+        Some(currency) => currency.to_uppercase(),
+        None => default_currency.to_string(),
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let (units, fraction) = amount.split_once('.').unwrap_or((&amount, ""));
+    if units.is_empty()
+        || fraction.len() > 2
+        || !units.chars().all(|c| c.is_ascii_digit())
+        || !fraction.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let units: i64 = units.parse().ok()?;
+    let fraction: i64 = format!("{:0<2}", fraction).parse().ok()?;
+
+    Some((units.checked_mul(100)?.checked_add(fraction)?, currency))
+}
+
+/// Formats an amount in minor units, dropping zero kopecks/cents.
+pub fn format_money(amount: i64) -> String {
+    if amount % 100 == 0 {
+        format!("{}", amount / 100)
+    } else {
+        format!("{}.{:02}", amount / 100, amount % 100)
+    }
+}
+
+/// Parses a weekday written in Russian, full or short (`пн`, `Вторник`).
+pub fn parse_russian_weekday(text: &str) -> Option<Weekday> {
+    let text = text.to_lowercase();
+    [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ]
+    .into_iter()
+    .find(|&weekday| {
+        text == get_russian_weekday_name(weekday, true).to_lowercase()
+            || text == get_russian_weekday_name(weekday, false).to_lowercase()
+    })
+}
+
+/// Parses timetable slots typed as `пн, ср 19:00`: one or more weekdays
+/// followed by a start time.
+pub fn parse_schedule_slot(text: &str) -> Option<(Vec<Weekday>, NaiveTime)> {
+    let mut tokens: Vec<&str> = text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .collect();
+    let starts_at = NaiveTime::parse_from_str(tokens.pop()?, "%H:%M").ok()?;
+
+    let mut weekdays = vec![];
+    for token in tokens {
+        let weekday = parse_russian_weekday(token)?;
+        if !weekdays.contains(&weekday) {
+            weekdays.push(weekday);
+        }
+    }
+    if weekdays.is_empty() {
+        return None;
+    }
+    Some((weekdays, starts_at))
+}
+
+/// Parses a UTC offset typed as `+3`, `-5`, `+05:30` or `UTC+3`.
+pub fn parse_utc_offset(text: &str) -> Option<FixedOffset> {
+    let text = text.trim();
+    let text = text
+        .strip_prefix("UTC")
+        .or_else(|| text.strip_prefix("utc"))
+        .unwrap_or(text)
+        .trim();
+    let first = text.chars().next()?;
+    let (sign, rest) = match first {
+        '+' => (1, &text[first.len_utf8()..]),
+        '-' | '−' => (-1, &text[first.len_utf8()..]),
+        _ => (1, text),
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((hours, minutes)) if minutes.len() == 2 => {
+            (hours.parse::<i32>().ok()?, minutes.parse::<i32>().ok()?)
+        }
+        Some(_) => return None,
+        None => (rest.parse::<i32>().ok()?, 0),
+    };
+    if !(0..=14).contains(&hours) || !(0..60).contains(&minutes) {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Formats an offset as `UTC+3` or `UTC+5:30`.
+pub fn format_utc_offset(offset: FixedOffset) -> String {
+    let seconds = offset.local_minus_utc();
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let (hours, minutes) = (seconds.abs() / 3600, seconds.abs() % 3600 / 60);
+    if minutes == 0 {
+        format!("UTC{sign}{hours}")
+    } else {
+        format!("UTC{sign}{hours}:{minutes:02}")
+    }
+}
+
+/// Telegram rejects messages longer than this, in characters.
+pub const MAX_MESSAGE_LENGTH: usize = 4096;
+
+/// `text` on a single line, cut to at most `max_chars` characters with an
+/// ellipsis.
+pub fn shorten(text: &str, max_chars: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+    let shortened: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    format!("{}…", shortened.trim_end())
+}
+
+/// Drops the trailing lines of `text` that do not fit into a message and
+/// marks the cut with an ellipsis. Every line must be complete HTML on its
+/// own for the result to stay valid.
+pub fn fit_message(text: String) -> String {
+    if text.chars().count() <= MAX_MESSAGE_LENGTH {
+        return text;
+    }
+    let mut fitted = String::new();
+    let mut length = 0;
+    for line in text.lines() {
+        let line_length = line.chars().count() + 1;
+        // One character is left for the ellipsis.
+        if length + line_length >= MAX_MESSAGE_LENGTH {
+            break;
+        }
+        fitted.push_str(line);
+        fitted.push('\n');
+        length += line_length;
+    }
+    fitted.push('…');
+    fitted
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, NaiveTime, Weekday};
+
+    use super::{
+        MAX_MESSAGE_LENGTH, fit_message, format_money, format_utc_offset, parse_price,
+        parse_schedule_slot, parse_utc_offset, shorten, sqlite_offset_modifier,
+    };
+
+    #[test]
+    fn test_parse_price() {
+        assert_eq!(
+            parse_price("4500", "RUB"),
+            Some((450000, "RUB".to_string()))
+        );
+        assert_eq!(
+            parse_price("12,5 eur", "RUB"),
+            Some((1250, "EUR".to_string()))
+        );
+
+        assert!(parse_price("", "RUB").is_none());
+        assert!(parse_price("-5", "RUB").is_none());
+        assert!(parse_price("1.999", "RUB").is_none());
+        assert!(parse_price("10 RUB extra", "RUB").is_none());
+    }
+
+    #[test]
+    fn test_shorten() {
+        assert_eq!(shorten("гаммы  и\nэтюды", 20), "гаммы и этюды");
+        assert_eq!(shorten("медленно, с метрономом", 10), "медленно,…");
+    }
+
+    #[test]
+    fn test_fit_message() {
+        assert_eq!(fit_message("short\ntext".to_string()), "short\ntext");
+
+        let line = "я".repeat(99);
+        let text = vec![line.as_str(); 100].join("\n");
+        let fitted = fit_message(text);
+        assert!(fitted.chars().count() <= MAX_MESSAGE_LENGTH);
+        assert!(fitted.ends_with(&format!("{}\n…", line)));
+    }
+
+    #[test]
+    fn test_format_money() {
+        assert_eq!(format_money(450000), "4500");
+        assert_eq!(format_money(1250), "12.50");
+    }
+
+    #[test]
+    fn test_parse_schedule_slot() {
+        let (weekdays, starts_at) = parse_schedule_slot("Пн, ср 19:30").unwrap();
+        assert_eq!(weekdays, vec![Weekday::Mon, Weekday::Wed]);
+        assert_eq!(starts_at, NaiveTime::from_hms_opt(19, 30, 0).unwrap());
+
+        let (weekdays, _) = parse_schedule_slot("суббота 9:00").unwrap();
+        assert_eq!(weekdays, vec![Weekday::Sat]);
+
+        assert!(parse_schedule_slot("19:30").is_none());
+        assert!(parse_schedule_slot("пн").is_none());
+        assert!(parse_schedule_slot("пн 25:00").is_none());
+    }
+
+    #[test]
+    fn test_parse_utc_offset() {
+        let offset = |hours: i32, minutes: i32| {
+            FixedOffset::east_opt(hours * 3600 + hours.signum() * minutes * 60).unwrap()
+        };
+        assert_eq!(parse_utc_offset("+3"), Some(offset(3, 0)));
+        assert_eq!(parse_utc_offset("3"), Some(offset(3, 0)));
+        assert_eq!(parse_utc_offset("UTC-5"), Some(offset(-5, 0)));
+        assert_eq!(parse_utc_offset("+05:30"), Some(offset(5, 30)));
+        assert_eq!(parse_utc_offset("0"), Some(offset(0, 0)));
+        assert!(parse_utc_offset("+15").is_none());
+        assert!(parse_utc_offset("+3:5").is_none());
+        assert!(parse_utc_offset("Москва").is_none());
+        assert!(parse_utc_offset("").is_none());
+
+        assert_eq!(format_utc_offset(offset(3, 0)), "UTC+3");
+        assert_eq!(format_utc_offset(offset(5, 30)), "UTC+5:30");
+        assert_eq!(format_utc_offset(offset(-5, 0)), "UTC-5");
+
+        assert_eq!(sqlite_offset_modifier(offset(3, 0)), "+10800 seconds");
+        assert_eq!(sqlite_offset_modifier(offset(-5, 0)), "-18000 seconds");
+        assert_eq!(sqlite_offset_modifier(offset(0, 0)), "+0 seconds");
+    }
+}